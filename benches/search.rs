@@ -0,0 +1,84 @@
+// benches/search.rs
+// Criterion benchmarks for loading, searching and cross-referencing (run with `cargo bench`)
+
+use std::hint::black_box;
+use std::io::Write;
+use criterion::{criterion_group, criterion_main, Criterion};
+use bible_tool::bible::{load_bible, Bible, Verse};
+use bible_tool::search::{query_groups, search_chapters, search_verses, SearchOptions};
+use bible_tool::similarity::{cross_references, XrefOptions};
+use bible_tool::synonyms::SynonymMapper;
+
+const VERSE_COUNT: usize = 5_000;
+
+const WORDS: &[&str] = &[
+    "and", "the", "lord", "said", "unto", "people", "faith", "hope", "love", "shepherd",
+    "light", "darkness", "water", "spirit", "king", "city", "land", "heaven", "earth", "word",
+    "life", "death", "grace", "mercy", "truth", "way", "house", "son", "father", "peace",
+];
+
+// Generate `count` synthetic verses spread over books of 50 chapters of 25 verses, with
+// deterministic pseudo-random text so runs are comparable
+fn synthetic_verses(count: usize) -> Vec<Verse> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+
+    (0..count)
+        .map(|i| {
+            let length = 8 + next() % 16;
+            let text: Vec<&str> = (0..length).map(|_| WORDS[next() % WORDS.len()]).collect();
+            Verse::new(format!("Book {}", i / 1250 + 1), (i / 25 % 50) as u32 + 1, (i % 25) as u32 + 1, text.join(" "))
+        })
+        .collect()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file, "SYN\nSynthetic Bible").unwrap();
+    for verse in synthetic_verses(VERSE_COUNT) {
+        writeln!(file, "{}", verse.plain_line()).unwrap();
+    }
+    let path = file.path().to_str().unwrap().to_string();
+
+    c.bench_function("load_bible", |b| b.iter(|| load_bible(black_box(&path)).unwrap()));
+}
+
+fn bench_search(c: &mut Criterion) {
+    let bible = Bible::new(synthetic_verses(VERSE_COUNT));
+    let groups = query_groups(&SynonymMapper::new(), "faith hope love", false, false);
+    let options = SearchOptions::default();
+
+    c.bench_function("search_verses", |b| b.iter(|| search_verses(black_box(&bible), &groups, &options).len()));
+    // Interactive and batch use search the same Bible many times
+    let queries: Vec<Vec<Vec<String>>> = ["faith hope love", "Lord", "shepherd water", "GRACE truth", "the king"]
+        .iter()
+        .map(|query| query_groups(&SynonymMapper::new(), query, false, false))
+        .collect();
+    c.bench_function("search_verses_repeated", |b| {
+        b.iter(|| queries.iter().map(|groups| search_verses(black_box(&bible), groups, &options).len()).sum::<usize>())
+    });
+    c.bench_function("search_chapters", |b| b.iter(|| search_chapters(black_box(&bible), &groups, &options).len()));
+}
+
+fn bench_cross_references(c: &mut Criterion) {
+    let verses = synthetic_verses(VERSE_COUNT);
+    let mapper = SynonymMapper::new();
+    let source = verses[0].clone();
+    let jaccard = XrefOptions { limit: Some(10), ..Default::default() };
+    let trigrams = XrefOptions { similarity: "3-gram".to_string(), limit: Some(10), ..Default::default() };
+
+    c.bench_function("cross_references_jaccard", |b| {
+        b.iter(|| cross_references(black_box(&verses), &mapper, &source, &jaccard).len())
+    });
+    c.bench_function("cross_references_3gram", |b| {
+        b.iter(|| cross_references(black_box(&verses), &mapper, &source, &trigrams).len())
+    });
+}
+
+criterion_group!(benches, bench_load, bench_search, bench_cross_references);
+criterion_main!(benches);
@@ -1,147 +1,778 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::ops::Range;
+use std::rc::Rc;
 use std::io::{self, BufRead, Write};
 use regex::Regex;
 use lazy_static::lazy_static;
 use colored::*;
+use crate::bookmarks::Bookmarks;
+use crate::error::{BibleError, ErrorFormat};
 use crate::synonyms::SynonymMapper;
+use crate::search::{search_bible_cli, SearchOptions};
+use crate::reference::{canonicalize_reference, invalid_reference_reason, parse_reference, Reference};
+use crate::books::{book_rank, resolve_book};
+use crate::filter::VerseFilter;
+use crate::highlight::apply_highlights;
+use crate::similarity::{chapter_cross_references, compare_metrics, cross_references_of, find_like, format_keywords, format_explanation, format_metric_description, parse_similarity_metric, similarity_bar, SimilarityMetric, Xref, XrefOptions, XrefResults, XrefScope, BAR_WIDTH};
+use crate::output::{format_records, OutputFormat, VerseRecord};
+use crate::passage::{print_passage, PassageStyle};
+use crate::random::{random_chapter, random_passage, random_verse, shuffled_verses, Rng};
+use crate::translations::TranslationRegistry;
+use crate::json_parser::{load_bible_report_auto, EmbeddedSynonyms};
 
 // Structure to hold a single Bible verse.
-#[derive(Debug, Clone)]
+// Verses compare by canonical position (see Ord below), and are equal only when their
+// reference, text and notes all match.
+#[derive(Debug, Clone, Default)]
 pub struct Verse {
     pub book: String,
     pub chapter: u32,
     pub verse: u32,
     pub text: String,
+    // Study notes taken out of the text (see `extract_notes`), like the transliteration in
+    // "love [agape]"; empty unless the Bible was loaded with notes
+    pub notes: Vec<String>,
 }
 
+// The reference and text; the notes are printed only through `Verse::shown`
 impl std::fmt::Display for Verse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.shown(NoteDisplay::default()).fmt(f)
+    }
+}
+
+/// A verse as printed, with its notes after the text when they are shown (see `Verse::shown`)
+pub struct ShownVerse<'a> {
+    verse: &'a Verse,
+    notes: NoteDisplay,
+}
+
+impl std::fmt::Display for ShownVerse<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verse = self.verse;
         write!(
             f,
             "{} {}:{} {}",
-            self.book.cyan(),
-            self.chapter.to_string().cyan(),
-            self.verse.to_string().cyan(),
-            self.text
-        )
+            verse.book.cyan(),
+            verse.chapter.to_string().cyan(),
+            verse.verse.to_string().cyan(),
+            verse.text
+        )?;
+        if self.notes.show {
+            for note in verse.bracketed_notes(self.notes.brackets) {
+                write!(f, " {}", note.dimmed())?;
+            }
+        }
+        Ok(())
     }
 }
 
-// Similarity metric types
-enum SimilarityMetric {
-    Jaccard(f32),  // Threshold value
-    NGram(usize),  // N-gram size (2-gram, 3-gram, etc.)
+// Canonical order: book order of the Bible, then chapter, then verse. Unknown books sort after
+// the known ones, alphabetically. Verses with the same reference are ordered by text, then by
+// their notes, so that only equal verses compare as equal.
+impl Ord for Verse {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let rank = |verse: &Verse| book_rank(&verse.book).unwrap_or(usize::MAX);
+        rank(self)
+            .cmp(&rank(other))
+            .then_with(|| self.book.cmp(&other.book))
+            .then(self.chapter.cmp(&other.chapter))
+            .then(self.verse.cmp(&other.verse))
+            .then_with(|| self.text.cmp(&other.text))
+            .then_with(|| self.notes.cmp(&other.notes))
+    }
 }
 
-// Parse similarity metric from string
-fn parse_similarity_metric(s: &str) -> SimilarityMetric {
-    let s = s.trim().to_lowercase();
-    
-    // Check for n-gram pattern
-    if s.ends_with("-gram") || s.ends_with("gram") {
-        let n_str = s.trim_end_matches("-gram").trim_end_matches("gram");
-        if let Ok(n) = n_str.parse::<usize>() {
-            if n > 0 {
-                return SimilarityMetric::NGram(n);
+// Equal exactly when `cmp` says so
+impl PartialEq for Verse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Verse {}
+
+impl PartialOrd for Verse {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Verse {
+    /// A verse with no study notes
+    pub fn new(book: impl Into<String>, chapter: u32, verse: u32, text: impl Into<String>) -> Self {
+        Verse { book: book.into(), chapter, verse, text: text.into(), ..Default::default() }
+    }
+
+    /// The colored `Book Chapter:Verse text` line, followed by the notes when `notes` shows them
+    pub fn shown(&self, notes: NoteDisplay) -> ShownVerse<'_> {
+        ShownVerse { verse: self, notes }
+    }
+
+    /// Each note in `brackets`, like `[agape]`
+    pub fn bracketed_notes(&self, brackets: NoteBrackets) -> impl Iterator<Item = String> + '_ {
+        let NoteBrackets { open, close } = brackets;
+        self.notes.iter().map(move |note| format!("{}{}{}", open, note, close))
+    }
+
+    /// The text with its notes after it in `brackets` (`God is love. [agape]`), for writing
+    /// the verse back out to a Bible file
+    pub fn text_with_notes(&self, brackets: NoteBrackets) -> String {
+        let mut text = self.text.clone();
+        for note in self.bracketed_notes(brackets) {
+            text.push(' ');
+            text.push_str(&note);
+        }
+        text
+    }
+
+    /// The text as printed: with its notes when `notes` shows them (--show-notes)
+    pub fn shown_text(&self, notes: NoteDisplay) -> String {
+        if notes.show {
+            self.text_with_notes(notes.brackets)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    /// `Book Chapter:Verse`, uncolored
+    pub fn reference(&self) -> String {
+        format!("{} {}:{}", self.book, self.chapter, self.verse)
+    }
+
+    /// `Book Chapter:Verse<TAB>Text`, uncolored; the same layout the text loader reads
+    pub fn plain_line(&self) -> String {
+        self.delimited_line(Delimiter::default(), NoteDisplay::default())
+    }
+
+    /// `Book Chapter:Verse`, `delimiter`, then the text as `notes` shows it: a plain line with
+    /// another separator
+    pub fn delimited_line(&self, delimiter: Delimiter, notes: NoteDisplay) -> String {
+        format!("{} {}:{}{}{}", self.book, self.chapter, self.verse, delimiter.0, self.shown_text(notes))
+    }
+}
+
+/// The character between the reference and the text of a plain line (--delimiter): a tab
+/// unless another is chosen, for both --plain output and the text loader
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delimiter(pub char);
+
+impl Default for Delimiter {
+    fn default() -> Self {
+        Delimiter('\t')
+    }
+}
+
+impl Delimiter {
+    /// `tab`, `comma`, `pipe`, or any single character that can't appear in a reference
+    /// (letters, digits, spaces and `:` can't be used)
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let c = match s.to_lowercase().as_str() {
+            "tab" | "\\t" => '\t',
+            "comma" => ',',
+            "pipe" => '|',
+            _ => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err(format!("'{}' isn't a single character; use tab, comma, pipe or one character", s)),
+                }
             }
+        };
+        if c.is_alphanumeric() || c == ':' || (c.is_whitespace() && c != '\t') {
+            return Err(format!("'{}' can't be a delimiter: it can appear in a reference like 'Song of Solomon 2:1'", c));
         }
+        Ok(Delimiter(c))
     }
-    
-    // Otherwise treat as Jaccard threshold
-    match s.parse::<f32>() {
-        Ok(threshold) => SimilarityMetric::Jaccard(threshold.clamp(0.0, 1.0)),
-        Err(_) => {
-            eprintln!("Warning: Invalid similarity metric '{}', using default 0.3", s);
-            SimilarityMetric::Jaccard(0.3)
+
+    pub fn is_tab(&self) -> bool {
+        self.0 == '\t'
+    }
+}
+
+/// The brackets study notes are written in (--note-brackets): `[agape]` unless others are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteBrackets {
+    pub open: char,
+    pub close: char,
+}
+
+impl Default for NoteBrackets {
+    fn default() -> Self {
+        NoteBrackets { open: '[', close: ']' }
+    }
+}
+
+impl NoteBrackets {
+    /// The opening and closing characters together, like `[]`, `{}` or `<>`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let chars: Vec<char> = s.chars().collect();
+        match chars[..] {
+            [open, close] if open != close && !open.is_alphanumeric() && !close.is_alphanumeric() && !open.is_whitespace() && !close.is_whitespace() => {
+                Ok(NoteBrackets { open, close })
+            }
+            _ => Err(format!("'{}' isn't a pair of brackets; give the opening and closing characters, like '[]' or '{{}}'", s)),
+        }
+    }
+
+    /// Take the bracketed notes out of a text: the text without them, and the notes in order.
+    /// A bracket that is never closed is left in the text.
+    pub fn extract(&self, text: &str) -> (String, Vec<String>) {
+        let mut kept = String::with_capacity(text.len());
+        let mut notes = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(self.open) {
+            let inner = start + self.open.len_utf8();
+            let Some(length) = rest[inner..].find(self.close) else { break };
+            // The space before a note goes with it: "love [agape], and" keeps "love, and"
+            kept.push_str(rest[..start].trim_end());
+            let note = rest[inner..inner + length].trim();
+            if !note.is_empty() {
+                notes.push(note.to_string());
+            }
+            rest = &rest[inner + length + self.close.len_utf8()..];
         }
+        kept.push_str(rest);
+        (kept.trim().to_string(), notes)
     }
 }
 
-// Format metric description for display
-fn format_metric_description(metric: &SimilarityMetric) -> String {
-    match metric {
-        SimilarityMetric::Jaccard(threshold) => format!("similarity >= {:.1}%", threshold * 100.0),
-        SimilarityMetric::NGram(n) => format!("{}-gram phrase matching", n),
+/// How a Bible's study notes are printed: in the brackets they were written in, and after the
+/// text only when `show` is set (--show-notes); they can be searched without being shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoteDisplay {
+    pub brackets: NoteBrackets,
+    pub show: bool,
+}
+
+/// Move the bracketed notes of each verse out of its text into its `notes` (--search-notes,
+/// --show-notes), so that searches of the text no longer see them
+pub fn extract_notes(verses: &mut [Verse], brackets: NoteBrackets) {
+    for verse in verses {
+        let (text, notes) = brackets.extract(&verse.text);
+        verse.text = text;
+        verse.notes.extend(notes);
+    }
+}
+
+// The chapters of one book, each with its range in the verse list
+type ChapterIndex = Vec<(u32, Range<usize>)>;
+
+/// A loaded Bible: the flat verse list plus a book/chapter index for cheap navigation.
+///
+/// Derefs to `[Verse]`, so it can be passed anywhere a verse slice is expected.
+#[derive(Debug, Clone, Default)]
+pub struct Bible {
+    verses: Vec<Verse>,
+    // Each book in order, with the range of `verses` covered by each of its chapters
+    index: Vec<(String, ChapterIndex)>,
+    // Lowercased text of each verse, computed once for case-insensitive searches
+    lowercase: Vec<String>,
+    // Each verse's notes joined into one text, as written and lowercased (--search-notes);
+    // empty for verses without notes
+    notes: Vec<String>,
+    lowercase_notes: Vec<String>,
+    // How the notes are printed, for the printers to pass on to `Verse::shown` and the like
+    note_display: NoteDisplay,
+}
+
+impl Bible {
+    /// Build a Bible from loaded verses. Books keep the order they first appear in;
+    /// within a book, verses are ordered by chapter and verse.
+    pub fn new(mut verses: Vec<Verse>) -> Self {
+        let mut book_order: HashMap<String, usize> = HashMap::new();
+        for verse in &verses {
+            let next = book_order.len();
+            book_order.entry(verse.book.clone()).or_insert(next);
+        }
+        verses.sort_by_key(|v| (book_order[&v.book], v.chapter, v.verse));
+
+        let mut index: Vec<(String, ChapterIndex)> = Vec::new();
+        for (position, verse) in verses.iter().enumerate() {
+            if index.last().map(|(book, _)| book != &verse.book).unwrap_or(true) {
+                index.push((verse.book.clone(), Vec::new()));
+            }
+            let chapters = &mut index.last_mut().unwrap().1;
+            match chapters.last_mut() {
+                Some((chapter, range)) if *chapter == verse.chapter => range.end = position + 1,
+                _ => chapters.push((verse.chapter, position..position + 1)),
+            }
+        }
+        let lowercase = verses.iter().map(|v| v.text.to_lowercase()).collect();
+        let notes: Vec<String> = verses.iter().map(|v| v.notes.join(" ")).collect();
+        let lowercase_notes = notes.iter().map(|notes| notes.to_lowercase()).collect();
+        Bible { verses, index, lowercase, notes, lowercase_notes, note_display: NoteDisplay::default() }
+    }
+
+    /// The same Bible, printing its notes as `notes` says
+    pub fn with_note_display(mut self, notes: NoteDisplay) -> Self {
+        self.note_display = notes;
+        self
+    }
+
+    /// How the notes are printed: in which brackets, and whether at all
+    pub fn note_display(&self) -> NoteDisplay {
+        self.note_display
+    }
+
+    pub fn verses(&self) -> &[Verse] {
+        &self.verses
+    }
+
+    /// Lowercased verse texts, parallel to `verses()`
+    pub fn lowercase_texts(&self) -> &[String] {
+        &self.lowercase
+    }
+
+    /// Each verse's notes as one text, parallel to `verses()`, as written and lowercased
+    pub fn note_texts(&self) -> (&[String], &[String]) {
+        (&self.notes, &self.lowercase_notes)
+    }
+
+    pub fn into_verses(self) -> Vec<Verse> {
+        self.verses
+    }
+
+    /// Book names in order, as they appear in the data
+    pub fn books(&self) -> impl Iterator<Item = &str> {
+        self.index.iter().map(|(book, _)| book.as_str())
+    }
+
+    /// Chapter numbers of a book (name or abbreviation), in order
+    pub fn chapters(&self, book: &str) -> impl Iterator<Item = u32> + '_ {
+        self.book_chapters(book).iter().map(|(chapter, _)| *chapter)
+    }
+
+    /// The verses of the chapter after `book chapter` in Bible order, from the next book's first
+    /// chapter at the end of a book, however the file orders them. `None` after the last
+    /// chapter, or when the book isn't present.
+    pub fn next_chapter(&self, book: &str, chapter: u32) -> Option<&[Verse]> {
+        let position = self.book_position(book)?;
+        let later = self.index[position].1.iter().filter(|(number, _)| *number > chapter).min_by_key(|(number, _)| *number);
+        let range = match later {
+            Some((_, range)) => range.clone(),
+            None => {
+                let next_book = (0..self.index.len()).filter(|&other| self.book_order(other) > self.book_order(position)).min_by_key(|&other| self.book_order(other))?;
+                self.first_chapter_of(next_book)?
+            }
+        };
+        Some(&self.verses[range])
+    }
+
+    /// The verses of the first chapter in Bible order; `None` for an empty Bible
+    pub fn first_chapter(&self) -> Option<&[Verse]> {
+        let first_book = (0..self.index.len()).min_by_key(|&position| self.book_order(position))?;
+        Some(&self.verses[self.first_chapter_of(first_book)?])
+    }
+
+    // Where the book at `position` in `index` comes in Bible order: canonical books by their
+    // rank, then any others in the order they appear
+    fn book_order(&self, position: usize) -> (usize, usize) {
+        (book_rank(&self.index[position].0).unwrap_or(usize::MAX), position)
+    }
+
+    // The verses of the lowest-numbered chapter of the book at `position` in `index`
+    fn first_chapter_of(&self, position: usize) -> Option<Range<usize>> {
+        self.index[position].1.iter().min_by_key(|(number, _)| *number).map(|(_, range)| range.clone())
+    }
+
+    /// The verses of one chapter; empty when the book or chapter isn't present
+    pub fn chapter_verses(&self, book: &str, chapter: u32) -> &[Verse] {
+        self.book_chapters(book)
+            .iter()
+            .find(|(number, _)| *number == chapter)
+            .map(|(_, range)| &self.verses[range.clone()])
+            .unwrap_or(&[])
+    }
+
+    /// The verses of a reference, found through the index rather than by scanning every verse:
+    /// a range costs a binary search within its chapter plus its own length. Empty when the
+    /// passage isn't present.
+    pub fn passage(&self, reference: &Reference) -> &[Verse] {
+        let chapters = self.book_chapters(reference.book());
+        let (Some((_, head)), Some((_, tail))) = (chapters.first(), chapters.last()) else {
+            return &[];
+        };
+        let (first_chapter, last_chapter, first_verse, last_verse) = match *reference {
+            Reference::Book(_) => return &self.verses[head.start..tail.end],
+            Reference::Chapter(_, chapter) => return self.chapter_verses(reference.book(), chapter),
+            Reference::Chapters(_, first, last) => (first, last, 0, u32::MAX),
+            Reference::Verse(_, chapter, verse) => (chapter, chapter, verse, verse),
+            Reference::Range(_, chapter, start, end) => (chapter, chapter, start, end),
+        };
+        // Verses are stored in chapter and verse order within a book
+        let verses = &self.verses[head.start..tail.end];
+        let start = verses.partition_point(|v| (v.chapter, v.verse) < (first_chapter, first_verse));
+        let end = verses.partition_point(|v| (v.chapter, v.verse) <= (last_chapter, last_verse));
+        &verses[start..end.max(start)]
+    }
+
+    /// Whether this Bible has a reference (--exists). A chapter range needs each of its chapters,
+    /// and a verse range each of its verses; with `any` one of them is enough. A book, chapter or
+    /// verse is present when it has any verses.
+    pub fn has_reference(&self, reference: &Reference, any: bool) -> bool {
+        let passage = self.passage(reference);
+        if any || passage.is_empty() {
+            return !passage.is_empty();
+        }
+        match *reference {
+            Reference::Chapters(ref book, first, last) => (first..=last).all(|chapter| !self.chapter_verses(book, chapter).is_empty()),
+            Reference::Range(_, _, start, end) => (start..=end).all(|number| passage.iter().any(|v| v.verse == number)),
+            Reference::Book(_) | Reference::Chapter(..) | Reference::Verse(..) => true,
+        }
+    }
+
+    /// A verse with up to `n` verses before and after it, for reading it in context. The
+    /// neighbors stay within the verse's chapter, so there are fewer of them near its start or
+    /// end. `None` unless the reference is a single verse present in this Bible.
+    pub fn neighbors(&self, reference: &Reference, n: usize) -> Option<(Vec<&Verse>, &Verse, Vec<&Verse>)> {
+        let Reference::Verse(ref book, chapter, number) = *reference else {
+            return None;
+        };
+        let verses = self.chapter_verses(book, chapter);
+        let index = verses.iter().position(|v| v.verse == number)?;
+        let before = verses[index.saturating_sub(n)..index].iter().collect();
+        let after = verses[index + 1..verses.len().min(index + 1 + n)].iter().collect();
+        Some((before, &verses[index], after))
+    }
+
+    /// The verses on either side of a missing passage: the last verse of the book before
+    /// `chapter:first` and the first one after `chapter:last`. Used to suggest where a verse
+    /// lives in a translation with different versification.
+    pub fn nearest_verses(&self, book: &str, chapter: u32, first: u32, last: u32) -> Vec<&Verse> {
+        let chapters = self.book_chapters(book);
+        let (Some((_, head)), Some((_, tail))) = (chapters.first(), chapters.last()) else {
+            return Vec::new();
+        };
+        let verses = &self.verses[head.start..tail.end];
+        let before = verses.partition_point(|v| (v.chapter, v.verse) < (chapter, first));
+        let after = verses.partition_point(|v| (v.chapter, v.verse) <= (chapter, last));
+        before.checked_sub(1).and_then(|i| verses.get(i)).into_iter().chain(verses.get(after)).collect()
+    }
+
+    // Index entry for a book: an exact name match first, then through the abbreviation table
+    fn book_chapters(&self, book: &str) -> &[(u32, Range<usize>)] {
+        self.book_position(book).map(|position| self.index[position].1.as_slice()).unwrap_or(&[])
+    }
+
+    // Where a book (name or abbreviation) comes in `index`
+    fn book_position(&self, book: &str) -> Option<usize> {
+        self.index.iter().position(|(name, _)| name.eq_ignore_ascii_case(book)).or_else(|| {
+            let canonical = resolve_book(book)?;
+            self.index.iter().position(|(name, _)| resolve_book(name) == Some(canonical))
+        })
+    }
+}
+
+impl std::ops::Deref for Bible {
+    type Target = [Verse];
+
+    fn deref(&self) -> &[Verse] {
+        &self.verses
     }
 }
 
 // Parses the bible.txt file and returns a Vector of Verse structs.
 pub fn load_bible(filename: &str) -> io::Result<Vec<Verse>> {
+    load_bible_reader(io::BufReader::new(File::open(filename)?))
+}
+
+/// Load a text Bible from any reader (stdin, a network stream, an in-memory buffer), in the
+/// same format as `load_bible`
+pub fn load_bible_reader(reader: impl BufRead) -> io::Result<Vec<Verse>> {
+    read_text_bible(reader, Delimiter::default(), 0, |_| {}).map(|load| load.verses)
+}
+
+/// Load a text Bible whose lines separate the reference from the text with `delimiter`
+/// (as written by `--plain --delimiter`)
+pub fn load_bible_delimited(reader: impl BufRead, delimiter: Delimiter) -> io::Result<Vec<Verse>> {
+    read_text_bible(reader, delimiter, 0, |_| {}).map(|load| load.verses)
+}
+
+/// Load a text Bible like `load_bible`, calling `progress` with the number of verses parsed so
+/// far after every `every` verses (never when `every` is 0), e.g. to drive a progress bar
+pub fn load_bible_with_progress(filename: &str, every: usize, progress: impl FnMut(usize)) -> io::Result<Vec<Verse>> {
+    read_text_bible(io::BufReader::new(File::open(filename)?), Delimiter::default(), every, progress).map(|load| load.verses)
+}
+
+/// Result of loading a text Bible, keeping the lines that didn't parse as verses
+pub struct TextLoad {
+    pub verses: Vec<Verse>,
+    /// Line number (1-based) and content of each non-blank line that wasn't a verse
+    pub malformed: Vec<(usize, String)>,
+    /// References listed more than once; every copy is still in `verses`
+    pub duplicates: Vec<DuplicateReference>,
+    /// Synonym groups a JSON Bible carries in its top-level `synonyms` object
+    pub synonyms: Option<HashMap<String, Vec<String>>>,
+    /// For a directory, the files that loaded, with the number of verses each contributed
+    pub loaded_files: Vec<(String, usize)>,
+    /// For a directory, the files that were skipped, with the reason
+    pub skipped_files: Vec<(String, String)>,
+}
+
+/// A reference a text Bible lists a second (or later) time
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateReference {
+    pub reference: String,
+    /// Line number (1-based) of the first copy, the one `drop_duplicates` keeps
+    pub first_line: usize,
+    /// Line number of this copy
+    pub line: usize,
+}
+
+impl TextLoad {
+    /// Keep only the first verse of each reference listed more than once
+    pub fn drop_duplicates(&mut self) {
+        if self.duplicates.is_empty() {
+            return;
+        }
+        let mut seen = HashSet::new();
+        self.verses.retain(|v| seen.insert((v.book.clone(), v.chapter, v.verse)));
+    }
+}
+
+// Verses loaded from a source with no lines to report on (JSON, a directory)
+impl From<Vec<Verse>> for TextLoad {
+    fn from(verses: Vec<Verse>) -> Self {
+        TextLoad { verses, malformed: Vec::new(), duplicates: Vec::new(), synonyms: None, loaded_files: Vec::new(), skipped_files: Vec::new() }
+    }
+}
+
+// Parse a single plain line: the reference before the first delimiter and the text after it
+// (which may itself contain the delimiter). Lines split by whitespace, as in the usual text
+// Bibles, are still read, so --delimiter can be given for the output alone.
+pub fn parse_delimited_line(line: &str, delimiter: Delimiter) -> Option<Verse> {
+    lazy_static! {
+        static ref REFERENCE: Regex = Regex::new(r"^(?P<book>.+?)\s+(?P<chapter>\d+):(?P<verse>\d+)$").unwrap();
+    }
+    if delimiter.is_tab() {
+        return parse_verse_line(line);
+    }
+
+    let split = line.split_once(delimiter.0).and_then(|(reference, text)| {
+        let text = text.trim();
+        Some((REFERENCE.captures(reference.trim())?, text)).filter(|_| !text.is_empty())
+    });
+    let Some((caps, text)) = split else {
+        return parse_verse_line(line);
+    };
+    Some(Verse::new(&caps["book"], caps["chapter"].parse().ok()?, caps["verse"].parse().ok()?, text))
+}
+
+// Parse a single "Book Chapter:Verse Text" line.
+pub fn parse_verse_line(line: &str) -> Option<Verse> {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"^(?P<book>.+?)\s(?P<chapter>\d+):(?P<verse>\d+)\t(?P<text>.+)$").unwrap();
+        // Any run of whitespace (tabs, spaces or a mix) may separate the reference from the text
+        static ref RE: Regex = Regex::new(r"^(?P<book>.+?)\s+(?P<chapter>\d+):(?P<verse>\d+)\s+(?P<text>.+)$").unwrap();
     }
 
-    let file = File::open(filename)?;
-    let reader = io::BufReader::new(file);
-    let mut bible = Vec::new();
+    let caps = RE.captures(line)?;
+    Some(Verse::new(&caps["book"], caps["chapter"].parse().ok()?, caps["verse"].parse().ok()?, &caps["text"]))
+}
+
+// Undo what Windows editors do to text files: drop the byte-order mark before the first line
+// and a carriage return left at the end of a line (lines() only strips one before a newline).
+pub fn clean_line(line: &str, line_index: usize) -> &str {
+    let line = if line_index == 0 { line.strip_prefix('\u{feff}').unwrap_or(line) } else { line };
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+// Loads a text Bible like load_bible, also reporting the lines that were skipped.
+pub fn load_bible_report(filename: &str) -> io::Result<TextLoad> {
+    load_bible_report_reader(io::BufReader::new(File::open(filename)?))
+}
+
+/// Load a text Bible from a reader like `load_bible_reader`, also reporting the skipped lines
+pub fn load_bible_report_reader(reader: impl BufRead) -> io::Result<TextLoad> {
+    load_bible_report_delimited(reader, Delimiter::default())
+}
+
+/// Load a text Bible like `load_bible_delimited`, also reporting the skipped lines
+pub fn load_bible_report_delimited(reader: impl BufRead, delimiter: Delimiter) -> io::Result<TextLoad> {
+    read_text_bible(reader, delimiter, 0, |_| {})
+}
+
+fn read_text_bible(reader: impl BufRead, delimiter: Delimiter, every: usize, mut progress: impl FnMut(usize)) -> io::Result<TextLoad> {
+    let mut load = TextLoad::from(Vec::new());
+    let mut first_lines = HashMap::new();
 
-    // Skip the first two header lines.
-    for line in reader.lines().skip(2) {
+    for (index, line) in reader.lines().enumerate() {
         let line = line?;
-        if let Some(caps) = RE.captures(&line) {
-            let verse = Verse {
-                book: caps["book"].to_string(),
-                chapter: caps["chapter"].parse().unwrap_or(0),
-                verse: caps["verse"].parse().unwrap_or(0),
-                text: caps["text"].to_string(),
-            };
-            bible.push(verse);
+        let line = clean_line(&line, index);
+        match parse_delimited_line(line, delimiter) {
+            Some(verse) => {
+                let first_line = *first_lines.entry((verse.book.clone(), verse.chapter, verse.verse)).or_insert(index + 1);
+                if first_line != index + 1 {
+                    load.duplicates.push(DuplicateReference { reference: verse.reference(), first_line, line: index + 1 });
+                }
+                load.verses.push(verse);
+                if every > 0 && load.verses.len().is_multiple_of(every) {
+                    progress(load.verses.len());
+                }
+            }
+            // The first two lines are the translation's header, unless they are already verses
+            // (as in files written by --plain)
+            None if index < 2 || line.trim().is_empty() => {}
+            None => load.malformed.push((index + 1, line.to_string())),
         }
     }
-    Ok(bible)
+    Ok(load)
 }
 
-// CLI version of verse lookup
-pub fn lookup_verse_cli(bible: &[Verse], reference: &str) {
-    lazy_static! {
-        static ref LOOKUP_RE: Regex = Regex::new(r"^(?P<book>.+?)\s(?P<chapter>\d+):(?P<verse>\d+)$").unwrap();
-    }
+// Sort verses into canonical order (the order of `Ord for Verse`)
+pub fn sort_canonical(verses: &mut [Verse]) {
+    verses.sort();
+}
+
+// CLI version of verse lookup. Accepts a whole book, a chapter, a verse range or a single verse.
+// When the passage is missing but the book exists, the nearest verses are offered instead.
+pub fn lookup_verse_cli(bible: &Bible, reference: &str, style: PassageStyle, use_color: bool) -> Result<(), BibleError> {
+    let reference = parse_reference(reference).ok_or_else(|| BibleError::InvalidReference {
+        reason: invalid_reference_reason(reference),
+        expected: "'Book', 'Book Chapter', 'Book Chapter-Chapter', 'Book Chapter:Verse' or 'Book Chapter:Verse-Verse'",
+    })?;
 
-    if let Some(caps) = LOOKUP_RE.captures(reference.trim()) {
-        let book = &caps["book"];
-        let chapter: u32 = caps["chapter"].parse().unwrap();
-        let verse: u32 = caps["verse"].parse().unwrap();
+    // Find the verses in our loaded Bible data.
+    let found: Vec<&Verse> = bible.passage(&reference).iter().collect();
 
-        // Find the verse in our loaded Bible data.
-        let found_verse = bible.iter().find(|v| {
-            v.book.eq_ignore_ascii_case(book) && v.chapter == chapter && v.verse == verse
+    if found.is_empty() {
+        let message = match reference {
+            Reference::Book(_) => "Book not found.",
+            Reference::Chapter(..) | Reference::Chapters(..) => "Chapter not found.",
+            Reference::Verse(..) | Reference::Range(..) => "Verse not found.",
+        };
+        let nearest = match reference {
+            Reference::Book(_) => Vec::new(),
+            Reference::Chapter(ref book, chapter) | Reference::Chapters(ref book, chapter, _) => bible.nearest_verses(book, chapter, 0, u32::MAX),
+            Reference::Verse(ref book, chapter, verse) => bible.nearest_verses(book, chapter, verse, verse),
+            Reference::Range(ref book, chapter, start, end) => bible.nearest_verses(book, chapter, start, end),
+        };
+        return Err(BibleError::NotFound {
+            message: message.to_string(),
+            nearest: nearest.into_iter().cloned().collect(),
         });
+    }
+
+    print_passage(&found, style, bible.note_display(), use_color);
+    Ok(())
+}
 
-        match found_verse {
-            Some(v) => println!("{}", v),
-            None => println!("{}", "Verse not found.".red()),
+// Print the canonical form of a reference (--canonical-ref), provided the Bible has it
+pub fn print_canonical_reference(bible: &Bible, reference: &str) -> Result<(), BibleError> {
+    let invalid = || BibleError::InvalidReference {
+        reason: invalid_reference_reason(reference),
+        expected: "'Book', 'Book Chapter', 'Book Chapter-Chapter', 'Book Chapter:Verse' or 'Book Chapter:Verse-Verse' with a known book",
+    };
+    let canonical = canonicalize_reference(reference).ok_or_else(invalid)?;
+    let parsed = parse_reference(&canonical).ok_or_else(invalid)?;
+    if bible.passage(&parsed).is_empty() {
+        return Err(BibleError::NotFound { message: format!("{} not found.", canonical), nearest: Vec::new() });
+    }
+    println!("{}", canonical);
+    Ok(())
+}
+
+// Find the first or last verse, in canonical order, that passes a filter
+pub fn boundary_verse<'a>(bible: &'a [Verse], filter: &VerseFilter, last: bool) -> Option<&'a Verse> {
+    let position = |v: &&Verse| (book_rank(&v.book).unwrap_or(usize::MAX), v.chapter, v.verse);
+    let candidates = bible.iter().filter(|v| filter.matches(v));
+    if last { candidates.max_by_key(position) } else { candidates.min_by_key(position) }
+}
+
+// Print the first or last verse passing a filter (--first / --last)
+pub fn print_boundary_verse(bible: &Bible, filter: &VerseFilter, last: bool, plain: Option<Delimiter>) -> Result<(), BibleError> {
+    match (boundary_verse(bible, filter, last), plain) {
+        (Some(verse), Some(delimiter)) => println!("{}", verse.delimited_line(delimiter, bible.note_display())),
+        (Some(verse), None) => println!("{}", verse.shown(bible.note_display())),
+        (None, _) => {
+            return Err(BibleError::NoResults { message: format!("No verses found in {}.", filter.describe()), hint: None });
         }
-    } else {
-        println!("{}", "Invalid reference format. Please use 'Book Chapter:Verse'.".red());
     }
+    Ok(())
 }
 
 // ... and so on for the rest of the functions
-pub fn get_random_verse(bible: &[Verse]) {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let mut hasher = DefaultHasher::new();
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos().hash(&mut hasher);
-    let index = (hasher.finish() as usize) % bible.len();
-    
-    let verse = &bible[index];
-    println!("{}", verse);
+pub fn get_random_verse(bible: &Bible, filter: &VerseFilter, rng: &mut Rng, plain: Option<Delimiter>) -> Result<(), BibleError> {
+    match (random_verse(bible, filter, rng), plain) {
+        (Some(verse), Some(delimiter)) => println!("{}", verse.delimited_line(delimiter, bible.note_display())),
+        (Some(verse), None) => println!("{}", verse.shown(bible.note_display())),
+        (None, _) => {
+            return Err(BibleError::NoResults { message: format!("No verses found in {}.", filter.describe()), hint: None });
+        }
+    }
+    Ok(())
+}
+
+// Print `n` distinct random verses passing a filter, in random order (--shuffle)
+pub fn print_shuffled_verses(bible: &Bible, filter: &VerseFilter, n: usize, rng: &mut Rng, style: PassageStyle, use_color: bool) -> Result<(), BibleError> {
+    let verses = shuffled_verses(bible, filter, n, rng);
+    if verses.is_empty() {
+        return Err(BibleError::NoResults { message: format!("No verses found in {}.", filter.describe()), hint: None });
+    }
+    print_passage(&verses, style, bible.note_display(), use_color);
+    Ok(())
+}
+
+// Print a random passage passing a filter: `length` consecutive verses (--random-passage), or a
+// whole chapter when no length is given (--random-chapter)
+pub fn print_random_passage(bible: &Bible, filter: &VerseFilter, length: Option<usize>, rng: &mut Rng, style: PassageStyle, use_color: bool) -> Result<(), BibleError> {
+    let passage = match length {
+        Some(length) => random_passage(bible, filter, length, rng),
+        None => random_chapter(bible, filter, rng),
+    };
+    let Some(passage) = passage else {
+        return Err(BibleError::NoResults { message: format!("No verses found in {}.", filter.describe()), hint: None });
+    };
+    let verses: Vec<&Verse> = passage.iter().collect();
+    print_passage(&verses, style, bible.note_display(), use_color);
+    Ok(())
 }
 
-// Interactive mode
-pub fn interactive_mode(bible: &[Verse], synonym_mapper: &SynonymMapper) {
+// Interactive mode, starting on `bible` (the Bible last put in `translations`). `use` also
+// switches to the new translation's synonyms, falling back on `synonyms_file`; with None (a
+// synonyms file was asked for) the loaded synonyms are kept for every translation.
+pub fn interactive_mode(mut bible: Rc<Bible>, mut translations: TranslationCache, synonym_mapper: &mut SynonymMapper, synonyms_file: Option<&str>, registry: &TranslationRegistry, bookmarks: &mut Bookmarks, title: &str) {
     println!("\n{}", "=== Interactive Bible Search Tool ===".bright_cyan().bold());
-    
+
+    let mut title = title.to_string();
     loop {
-        print_menu();
+        print_menu(&title, registry);
         let mut choice = String::new();
         io::stdin().read_line(&mut choice).expect("Failed to read line");
+        let choice = choice.trim();
 
-        match choice.trim() {
-            "1" => lookup_verse(bible),
-            "2" => search_bible_interactive(bible, synonym_mapper),
+        // "use <code>" swaps the Bible in place, with the synonyms it embeds (or the synonyms
+        // file's); the Bibles used before stay loaded, so switching back doesn't load one again
+        if let Some(code) = choice.strip_prefix("use ") {
+            match translations.switch(registry, code.trim()) {
+                Ok((loaded, name, cached)) => {
+                    let from = if cached { ", already loaded" } else { "" };
+                    println!("✅ Switched to {} ({} verses{}).", name, loaded.len(), from);
+                    if let Some(synonyms_file) = synonyms_file {
+                        switch_synonyms(synonym_mapper, translations.synonyms(), synonyms_file, &name);
+                    }
+                    bible = loaded;
+                    title = name;
+                }
+                Err(e) => e.report(ErrorFormat::Human),
+            }
+            continue;
+        }
+
+        // "bookmark <ref>" saves a reference for later sessions; "bookmarks" reads them back
+        if let Some(reference) = choice.strip_prefix("bookmark ") {
+            add_bookmark(&bible, bookmarks, reference);
+            continue;
+        }
+        if choice == "bookmarks" {
+            print_bookmarks(&bible, bookmarks);
+            continue;
+        }
+
+        match choice {
+            "1" => lookup_verse(&bible),
+            "2" => search_bible_interactive(&bible, synonym_mapper),
             "3" => {
                 println!("Goodbye! 🙏");
                 break;
@@ -151,26 +782,147 @@ pub fn interactive_mode(bible: &[Verse], synonym_mapper: &SynonymMapper) {
     }
 }
 
-fn print_menu() {
-    println!("\n--- Bible Tool Menu ---");
+// Use the synonym groups embedded in the translation switched to, or without any those of
+// the synonyms file; either way the expansions of the previous translation are forgotten
+fn switch_synonyms(synonym_mapper: &mut SynonymMapper, embedded: Option<&EmbeddedSynonyms>, synonyms_file: &str, name: &str) {
+    match embedded {
+        Some(groups) => {
+            synonym_mapper.replace_groups(groups.clone());
+            println!("✅ Loaded {} synonym groups embedded in {}", synonym_mapper.get_synonym_count(), name);
+        }
+        None => match synonym_mapper.reload(synonyms_file) {
+            Ok(()) => println!("✅ Loaded {} synonym groups from {}", synonym_mapper.get_synonym_count(), synonyms_file),
+            Err(e) => {
+                synonym_mapper.replace_groups(HashMap::new());
+                println!("⚠️  Could not load synonyms file ({}): {}", synonyms_file, e);
+                println!("   Using exact word matching only.");
+            }
+        },
+    }
+}
+
+// Bookmark a reference that is in the loaded Bible, saving the list straight away
+fn add_bookmark(bible: &Bible, bookmarks: &mut Bookmarks, input: &str) {
+    let in_bible = parse_reference(input).is_some_and(|reference| !bible.passage(&reference).is_empty());
+    if !in_bible && canonicalize_reference(input).is_some() {
+        println!("{}", format!("{} not found in this translation.", input.trim()).red());
+        return;
+    }
+    match bookmarks.add(input) {
+        Ok((reference, false)) => println!("{} is already bookmarked.", reference),
+        Ok((reference, true)) => match bookmarks.save() {
+            Ok(()) => println!("🔖 Bookmarked {}", reference),
+            Err(e) => eprintln!("🔥 Could not save bookmarks to {}: {}", bookmarks.file(), e),
+        },
+        Err(e) => e.report(ErrorFormat::Human),
+    }
+}
+
+fn print_bookmarks(bible: &Bible, bookmarks: &Bookmarks) {
+    if bookmarks.references().is_empty() {
+        println!("No bookmarks yet. Type 'bookmark <reference>' to add one.");
+        return;
+    }
+    println!("🔖 {} bookmark(s) in {}:", bookmarks.references().len(), bookmarks.file());
+    for reference in bookmarks.references() {
+        if let Err(e) = lookup_verse_cli(bible, reference, PassageStyle::Lines, true) {
+            e.report(ErrorFormat::Human);
+        }
+    }
+}
+
+/// Load the Bible registered under `code`, returned with the name to show for it, the file it
+/// was loaded from and the synonym groups that file embeds (JSON Bibles only)
+pub fn switch_translation(registry: &TranslationRegistry, code: &str) -> Result<(Bible, String, String, Option<EmbeddedSynonyms>), BibleError> {
+    let translation = registry.resolve(code).map_err(BibleError::UnknownTranslation)?;
+    let load = load_bible_report_auto(&translation.path, Delimiter::default()).map_err(|e| BibleError::load(&translation.path, &e))?;
+    let name = translation.name.clone().unwrap_or_else(|| translation.code.clone());
+    Ok((Bible::new(load.verses), name, translation.path.clone(), load.synonyms))
+}
+
+/// Bibles (with their indexes) kept loaded by interactive mode, most at once
+pub const TRANSLATION_CACHE_SIZE: usize = 4;
+
+/// The Bibles loaded in an interactive session, keyed by file, so switching back to a
+/// translation with `use` is instant. At most `capacity` are kept; loading another drops the
+/// least recently used.
+pub struct TranslationCache {
+    capacity: usize,
+    // File, name, Bible and embedded synonyms; least recently used first
+    bibles: Vec<(String, String, Rc<Bible>, Option<EmbeddedSynonyms>)>,
+}
+
+impl TranslationCache {
+    pub fn new(capacity: usize) -> Self {
+        TranslationCache { capacity: capacity.max(1), bibles: Vec::new() }
+    }
+
+    /// Keep a Bible loaded from `file`, with the synonym groups the file embeds (as the most
+    /// recently used), returning it shared
+    pub fn insert(&mut self, file: &str, name: &str, bible: Bible, synonyms: Option<EmbeddedSynonyms>) -> Rc<Bible> {
+        self.bibles.retain(|(cached, _, _, _)| cached != file);
+        if self.bibles.len() == self.capacity {
+            self.bibles.remove(0);
+        }
+        let bible = Rc::new(bible);
+        self.bibles.push((file.to_string(), name.to_string(), Rc::clone(&bible), synonyms));
+        bible
+    }
+
+    /// The synonym groups embedded in the file of the most recently used Bible
+    pub fn synonyms(&self) -> Option<&EmbeddedSynonyms> {
+        self.bibles.last().and_then(|(_, _, _, synonyms)| synonyms.as_ref())
+    }
+
+    /// The Bible registered under `code` and the name to show for it, loaded by
+    /// `switch_translation` unless it is cached; the flag tells which
+    pub fn switch(&mut self, registry: &TranslationRegistry, code: &str) -> Result<(Rc<Bible>, String, bool), BibleError> {
+        // A cached Bible is used even if its file has since gone
+        let cached = registry.get(code).and_then(|translation| self.bibles.iter().position(|(file, _, _, _)| *file == translation.path));
+        if let Some(position) = cached {
+            let entry = self.bibles.remove(position);
+            let (bible, name) = (Rc::clone(&entry.2), entry.1.clone());
+            self.bibles.push(entry);
+            return Ok((bible, name, true));
+        }
+        let (bible, name, file, synonyms) = switch_translation(registry, code)?;
+        Ok((self.insert(&file, &name, bible, synonyms), name, false))
+    }
+
+    /// How many Bibles are loaded
+    pub fn len(&self) -> usize {
+        self.bibles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bibles.is_empty()
+    }
+}
+
+fn print_menu(title: &str, registry: &TranslationRegistry) {
+    println!("\n--- Bible Tool Menu ({}) ---", title);
     println!("1. Lookup Verse (e.g., Genesis 1:1)");
     println!("2. Search Text");
     println!("3. Exit");
+    println!("Type 'use <code>' to switch translation ({})", registry.codes().join(", "));
+    println!("Type 'bookmark <reference>' to save a reference, 'bookmarks' to list them");
     print!("> ");
     io::stdout().flush().unwrap();
 }
 
-fn lookup_verse(bible: &[Verse]) {
-    print!("Enter reference (e.g., John 3:16): ");
+fn lookup_verse(bible: &Bible) {
+    print!("Enter reference (e.g., John 3:16, John 3 or John): ");
     io::stdout().flush().unwrap();
 
     let mut reference = String::new();
     io::stdin().read_line(&mut reference).expect("Failed to read line");
 
-    lookup_verse_cli(bible, &reference);
+    if let Err(e) = lookup_verse_cli(bible, &reference, PassageStyle::Lines, true) {
+        e.report(ErrorFormat::Human);
+    }
 }
 
-fn search_bible_interactive(bible: &[Verse], synonym_mapper: &SynonymMapper) {
+fn search_bible_interactive(bible: &Bible, synonym_mapper: &SynonymMapper) {
     print!("Enter search query: ");
     io::stdout().flush().unwrap();
 
@@ -190,213 +942,86 @@ fn search_bible_interactive(bible: &[Verse], synonym_mapper: &SynonymMapper) {
     io::stdin().read_line(&mut synonym_choice).expect("Failed to read line");
     let use_synonyms = synonym_choice.trim().to_lowercase().starts_with('y');
 
-    search_bible_cli(bible, synonym_mapper, query, use_synonyms, false, None, None, true);
-}
-
-pub fn search_bible_cli(bible: &[Verse], synonym_mapper: &SynonymMapper, query: &str, use_synonyms: bool, case_sensitive: bool, book_filter: Option<&str>, limit: Option<usize>, use_color: bool) {
-    if query.trim().is_empty() {
-        println!("{}", "Search query cannot be empty.".yellow());
-        return;
-    }
-
-    let search_terms = if use_synonyms {
-        synonym_mapper.expand_query(query)
-    } else {
-        query.split_whitespace().map(|s| s.to_string()).collect()
+    let options = SearchOptions {
+        use_synonyms,
+        use_color: true,
+        term_colors: true,
+        paged: true,
+        ..Default::default()
     };
-
-    if use_synonyms && search_terms.len() > query.split_whitespace().count() {
-        println!("Searching for '{}' (with synonyms: {})...", query, search_terms.join(", "));
-    } else if use_synonyms {
-        println!("Searching for '{}' (no synonyms defined for these terms)...", query);
-    } else {
-        println!("Searching for '{}'...", query);
-    }
-
-    let mut results_found = 0;
-    let mut results = Vec::new();
-
-    for verse in bible {
-        // Apply book filter if specified
-        if let Some(book) = book_filter {
-            if !verse.book.to_lowercase().contains(&book.to_lowercase()) {
-                continue;
-            }
-        }
-
-        let text_to_search = if case_sensitive {
-            verse.text.clone()
-        } else {
-            verse.text.to_lowercase()
-        };
-
-        // Check if any search term matches
-        let matches = search_terms.iter().any(|term| {
-            if case_sensitive {
-                verse.text.contains(term)
-            } else {
-                text_to_search.contains(&term.to_lowercase())
-            }
-        });
-
-        if matches {
-            results.push(verse);
-            results_found += 1;
-            
-            // Apply limit if specified
-            if let Some(limit) = limit {
-                if results_found >= limit {
-                    break;
-                }
-            }
-        }
+    if let Err(e) = search_bible_cli(bible, synonym_mapper, query, &options) {
+        e.report(ErrorFormat::Human);
     }
+}
 
-    if results.is_empty() {
-        println!("{}", "No results found.".red());
-    } else {
-        println!();
-        for verse in results {
-            // Create highlighted version of the text
-            let mut highlighted_text = verse.text.clone();
-            
-            // Highlight matching terms
-            if use_color {
-                for term in &search_terms {
-                    if case_sensitive {
-                        if verse.text.contains(term) {
-                            highlighted_text = highlighted_text.replace(term, &term.black().on_yellow().to_string());
-                        }
-                    } else {
-                        // Case-insensitive highlighting is more complex
-                        let lower_text = verse.text.to_lowercase();
-                        let lower_term = term.to_lowercase();
-                        if let Some(pos) = lower_text.find(&lower_term) {
-                            let original_term = &verse.text[pos..pos + term.len()];
-                            highlighted_text = highlighted_text.replace(original_term, &original_term.black().on_yellow().to_string());
-                        }
-                    }
-                }
-            }
-
-            println!(
-                "{} {}:{} {}",
-                verse.book.cyan(),
-                verse.chapter.to_string().cyan(),
-                verse.verse.to_string().cyan(),
-                highlighted_text
-            );
-        }
-        println!("\nFound {} matching verses.", results_found);
-    }
+/// How cross-references are printed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XrefDisplay {
+    pub format: OutputFormat,
+    /// Draw each similarity as a bar (--bars)
+    pub bars: bool,
+    pub use_color: bool,
+    /// Print the words the source verse is compared by (--show-keywords)
+    pub show_keywords: bool,
+    /// Print how each score was reached under it (--explain-ranking)
+    pub explain: bool,
 }
 
 // Cross-reference finder - find similar verses
-// Note: signature changed to accept String instead of f32
-pub fn find_cross_references(bible: &[Verse], synonym_mapper: &SynonymMapper, reference: &str, similarity_str: &str, use_synonyms: bool, limit: Option<usize>, use_color: bool) {
-    lazy_static! {
-        static ref LOOKUP_RE: Regex = Regex::new(r"^(?P<book>.+?)\s(?P<chapter>\d+):(?P<verse>\d+)$").unwrap();
-    }
-
-    // Parse the reference
-    let (book, chapter, verse_num) = if let Some(caps) = LOOKUP_RE.captures(reference.trim()) {
-        let book = caps["book"].to_string();
-        let chapter: u32 = caps["chapter"].parse().unwrap();
-        let verse: u32 = caps["verse"].parse().unwrap();
-        (book, chapter, verse)
-    } else {
-        println!("{}", "Invalid reference format. Please use 'Book Chapter:Verse'.".red());
-        return;
-    };
-
-    // Find the source verse
-    let source_verse = bible.iter().find(|v| {
-        v.book.eq_ignore_ascii_case(&book) && v.chapter == chapter && v.verse == verse_num
-    });
-
-    let source_verse = match source_verse {
-        Some(v) => v,
-        None => {
-            println!("{}", "Source verse not found.".red());
-            return;
-        }
-    };
+pub fn find_cross_references(bible: &Bible, synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions, display: XrefDisplay) -> Result<(), BibleError> {
+    let XrefDisplay { format, bars, use_color, show_keywords, explain } = display;
+    let use_synonyms = options.use_synonyms;
+    let XrefResults { source, keywords, xrefs: similarities } = cross_references_of(bible, synonym_mapper, reference, options)?;
 
     // Display source verse
-    if use_color {
-        println!("{}", "Source Verse:".bright_green().bold());
-    } else {
-        println!("Source Verse:");
+    if !format.is_json() {
+        if use_color {
+            println!("{}", "Source Verse:".bright_green().bold());
+        } else {
+            println!("Source Verse:");
+        }
+        println!("{}\n", source.shown(bible.note_display()));
     }
-    println!("{}\n", source_verse);
 
     // Parse similarity metric
-    let similarity_metric = parse_similarity_metric(similarity_str);
+    let similarity_metric = parse_similarity_metric(&options.similarity);
 
-    // Extract words from source verse
-    let source_words = extract_words(&source_verse.text, synonym_mapper, use_synonyms);
-    
-    if source_words.is_empty() {
-        println!("{}", "No significant words found in source verse.".yellow());
-        return;
+    if show_keywords && !format.is_json() {
+        let line = format_keywords(&keywords);
+        println!("{}\n", if use_color { line.bright_black().to_string() } else { line });
     }
 
-    // Calculate similarity for all other verses
-    let mut similarities: Vec<(f32, &Verse)> = bible.iter()
-        .filter(|v| {
-            // Exclude the source verse itself
-            !(v.book.eq_ignore_ascii_case(&source_verse.book) 
-              && v.chapter == source_verse.chapter 
-              && v.verse == source_verse.verse)
-        })
-        .filter_map(|v| {
-            let similarity = match similarity_metric {
-                SimilarityMetric::Jaccard(threshold) => {
-            let target_words = extract_words(&v.text, synonym_mapper, use_synonyms);
-                    let sim = calculate_jaccard_similarity(&source_words, &target_words);
-                    if sim >= threshold {
-                        Some(sim)
-                    } else {
-                        None
-                    }
-                }
-                SimilarityMetric::NGram(n) => {
-                    if has_ngram_match(&source_verse.text, &v.text, n, synonym_mapper, use_synonyms) {
-                        let score = count_ngram_matches(&source_verse.text, &v.text, n, synonym_mapper, use_synonyms);
-                        Some(score)
-                    } else {
-                        None
-                    }
-                }
-            };
-            similarity.map(|s| (s, v))
-        })
-        .collect();
-
-    // Sort by similarity (highest first)
-    similarities.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    // --top ranks by score alone, so there is no threshold to report
+    let description = match similarity_metric {
+        SimilarityMetric::Jaccard(_) if options.top.is_some() => "the highest word similarity".to_string(),
+        _ => format_metric_description(&similarity_metric),
+    };
 
-    // Apply limit if specified
-    if let Some(limit) = limit {
-        similarities.truncate(limit);
+    if similarities.is_empty() {
+        return Err(BibleError::NoResults {
+            message: format!("No cross-references found with {}", description),
+            hint: Some(format!(
+                "Try adjusting the --similarity threshold or n-gram size{}",
+                if options.scope == XrefScope::All { "" } else { ", or widening --xref-scope" }
+            )),
+        });
     }
 
-    if similarities.is_empty() {
-        if use_color {
-            println!("{}", format!("No cross-references found with {}", format_metric_description(&similarity_metric)).red());
-        } else {
-            println!("No cross-references found with {}", format_metric_description(&similarity_metric));
-        }
-        println!("Try adjusting the --similarity threshold or n-gram size");
-        return;
+    if format.is_json() {
+        let records = similarities
+            .iter()
+            .map(|xref| VerseRecord { score: Some(xref.score), ..VerseRecord::new(xref.verse, bible.note_display()) })
+            .collect();
+        println!("{}", format_records(records, format));
+        return Ok(());
     }
 
     if use_color {
         println!("{}", format!("Found {} cross-reference(s) with {}:", 
-            similarities.len(), format_metric_description(&similarity_metric)).green().bold());
+            similarities.len(), description).green().bold());
     } else {
         println!("Found {} cross-reference(s) with {}:", 
-            similarities.len(), format_metric_description(&similarity_metric));
+            similarities.len(), description);
     }
     
     if use_synonyms {
@@ -404,9 +1029,13 @@ pub fn find_cross_references(bible: &[Verse], synonym_mapper: &SynonymMapper, re
     }
     println!();
 
-    for (similarity, verse) in similarities {
+    // N-gram scores are counts, so their bars are scaled to the best match
+    let best = similarities.first().map_or(1.0, |xref| xref.score.max(1.0));
+    for xref in &similarities {
+        let Xref { verse, score: similarity, shared, spans, .. } = xref;
+        let similarity = *similarity;
         let score_display = match similarity_metric {
-            SimilarityMetric::Jaccard(_) => {
+            SimilarityMetric::Jaccard(_) | SimilarityMetric::Names => {
                 if use_color {
             format!("{:.1}%", similarity * 100.0).yellow().bold().to_string()
         } else {
@@ -422,151 +1051,220 @@ pub fn find_cross_references(bible: &[Verse], synonym_mapper: &SynonymMapper, re
             }
         };
 
+        let score_display = if bars {
+            let fraction = match similarity_metric {
+                SimilarityMetric::NGram(_) => similarity / best,
+                SimilarityMetric::Jaccard(_) | SimilarityMetric::Names => similarity,
+            };
+            format!("{} {}", similarity_bar(fraction, BAR_WIDTH, use_color), score_display)
+        } else {
+            score_display
+        };
+
+        // Highlight the phrases shared with the source verse
+        let text = if use_color && !spans.is_empty() {
+            apply_highlights(&verse.text, spans)
+        } else {
+            verse.text.clone()
+        };
+
         println!("{} - {} {}:{} {}", 
             score_display,
             verse.book.cyan(),
             verse.chapter.to_string().cyan(),
             verse.verse.to_string().cyan(),
-            verse.text
+            text
         );
+        // The shared phrases of the n-gram metric are listed under the verse, or with
+        // --explain-ranking what the score is made of
+        if explain || !spans.is_empty() {
+            let shared = if explain {
+                format!("   {}", format_explanation(xref))
+            } else {
+                let phrases: Vec<String> = shared.iter().map(|phrase| format!("\"{}\"", phrase)).collect();
+                format!("   shared: {}", phrases.join(", "))
+            };
+            if use_color {
+                println!("{}", shared.bright_black());
+            } else {
+                println!("{}", shared);
+            }
+        }
         println!();
     }
+    Ok(())
 }
 
-// Extract significant words from text, optionally expanding with synonyms
-fn extract_words(text: &str, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> Vec<String> {
-    // Common words to exclude (stop words)
-    let stop_words: std::collections::HashSet<&str> = [
-        "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from",
-        "has", "he", "in", "is", "it", "its", "of", "on", "that", "the", "to",
-        "was", "will", "with", "shall", "unto", "thee", "thou", "thy", "ye",
-        "hath", "his", "her", "him", "them", "they", "their", "all", "not",
-        "which", "there", "this", "these", "those", "when", "who", "what",
-        "into", "upon", "out", "up", "have", "had", "do", "did", "done",
-        "said", "came", "went", "been", "were", "being", "of"
-    ].iter().cloned().collect();
-
-    let words: Vec<String> = text
-        .to_lowercase()
-        .split_whitespace()
-        .map(|w| w.trim_matches(|c: char| !c.is_alphabetic()))
-        .filter(|w| !w.is_empty() && w.len() > 2 && !stop_words.contains(w))
-        .map(|w| w.to_string())
-        .collect();
+// Most shared words listed under a similar chapter before the rest are counted
+const CHAPTER_SHARED_SHOWN: usize = 12;
 
-    if use_synonyms {
-        let mut expanded_words = Vec::new();
-        for word in words {
-            if let Some(synonyms) = synonym_mapper.synonyms.get(&word) {
-                expanded_words.extend(synonyms.clone());
-            } else {
-                expanded_words.push(word);
-            }
-        }
-        expanded_words.sort();
-        expanded_words.dedup();
-        expanded_words
-    } else {
-        let mut unique_words = words;
-        unique_words.sort();
-        unique_words.dedup();
-        unique_words
+/// Print the chapters most like the chapter at `reference` (--cross-references-chapter), each
+/// with its score and what it shares with the source; JSON gives every shared word or phrase
+pub fn find_chapter_cross_references(bible: &Bible, synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions, display: XrefDisplay) -> Result<(), BibleError> {
+    let XrefDisplay { format, bars, use_color, .. } = display;
+    let xrefs = chapter_cross_references(bible, synonym_mapper, reference, options)?;
+    let metric = parse_similarity_metric(&options.similarity);
+    let description = match metric {
+        SimilarityMetric::Jaccard(_) => "word similarity".to_string(),
+        _ => format_metric_description(&metric),
+    };
+    if xrefs.is_empty() {
+        return Err(BibleError::NoResults {
+            message: format!("No chapters share anything with {} by {}", reference, description),
+            hint: (options.scope != XrefScope::All).then(|| "Try widening --xref-scope".to_string()),
+        });
     }
-}
 
-// Calculate Jaccard similarity between two word sets
-fn calculate_jaccard_similarity(words1: &[String], words2: &[String]) -> f32 {
-    if words1.is_empty() || words2.is_empty() {
-        return 0.0;
+    if format.is_json() {
+        let records: Vec<serde_json::Value> = xrefs
+            .iter()
+            .map(|xref| serde_json::json!({ "reference": xref.reference(), "verses": xref.verses.len(), "score": xref.score, "shared": xref.shared }))
+            .collect();
+        let text = match format {
+            OutputFormat::JsonLines => records.iter().map(|r| r.to_string()).collect::<Vec<_>>().join("\n"),
+            _ => serde_json::to_string_pretty(&records).unwrap_or_default(),
+        };
+        println!("{}", text);
+        return Ok(());
     }
 
-    let set1: std::collections::HashSet<_> = words1.iter().collect();
-    let set2: std::collections::HashSet<_> = words2.iter().collect();
+    let heading = format!("Found {} chapter(s) like {} by {}:", xrefs.len(), reference, description);
+    println!("{}\n", if use_color { heading.green().bold().to_string() } else { heading });
+    let best = xrefs[0].score.max(1.0);
+    for xref in &xrefs {
+        let (score, fraction) = match metric {
+            SimilarityMetric::NGram(_) => (format!("{:.0} match(es)", xref.score), xref.score / best),
+            _ => (format!("{:.1}%", xref.score * 100.0), xref.score),
+        };
+        let score = if use_color { score.yellow().bold().to_string() } else { score };
+        let score = if bars { format!("{} {}", similarity_bar(fraction, BAR_WIDTH, use_color), score) } else { score };
+        println!("{} - {} ({} verses)", score, xref.reference().cyan(), xref.verses.len());
 
-    let intersection = set1.intersection(&set2).count();
-    let union = set1.union(&set2).count();
+        let mut shown: Vec<String> = xref.shared.iter().take(CHAPTER_SHARED_SHOWN).cloned().collect();
+        if xref.shared.len() > CHAPTER_SHARED_SHOWN {
+            shown.push(format!("and {} more", xref.shared.len() - CHAPTER_SHARED_SHOWN));
+        }
+        let shared = format!("   shared: {}", shown.join(", "));
+        println!("{}\n", if use_color { shared.bright_black().to_string() } else { shared });
+    }
+    Ok(())
+}
 
-    if union == 0 {
-        0.0
-    } else {
-        intersection as f32 / union as f32
+// A metric's column heading in --xref-compare, and its key in JSON
+fn metric_label(metric: &SimilarityMetric) -> String {
+    match metric {
+        SimilarityMetric::Jaccard(_) => "jaccard".to_string(),
+        SimilarityMetric::NGram(n) => format!("{}-gram", n),
+        SimilarityMetric::Names => "names".to_string(),
     }
 }
 
-// Extract n-grams from text
-fn extract_ngrams(text: &str, n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> Vec<Vec<String>> {
-    let words = extract_words(text, synonym_mapper, false);
-    
-    if words.len() < n {
-        return vec![];
+/// Print the best cross-references of the verse at `reference` by each metric in columns side
+/// by side (--xref-compare), for choosing a `--similarity`: percentages for Jaccard and names,
+/// shared phrase counts for n-grams. JSON gives one record per metric.
+pub fn print_metric_comparison(bible: &Bible, synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions, format: OutputFormat, use_color: bool) -> Result<(), BibleError> {
+    let rankings = compare_metrics(bible, synonym_mapper, reference, options)?;
+    if rankings.iter().all(|ranking| ranking.xrefs.is_empty()) {
+        return Err(BibleError::NoResults {
+            message: format!("No verses share anything with {}", reference),
+            hint: (options.scope != XrefScope::All).then(|| "Try widening --xref-scope".to_string()),
+        });
     }
-    
-    let mut ngrams = Vec::new();
-    
-    for i in 0..=words.len() - n {
-        let ngram: Vec<String> = words[i..i+n].to_vec();
-        
-        if use_synonyms {
-            // Generate all synonym variations of this n-gram
-            let mut variations = vec![ngram.clone()];
-            
-            for (idx, word) in ngram.iter().enumerate() {
-                if let Some(synonyms) = synonym_mapper.synonyms.get(word) {
-                    let mut new_variations = Vec::new();
-                    for variation in &variations {
-                        for synonym in synonyms {
-                            let mut new_var = variation.clone();
-                            new_var[idx] = synonym.clone();
-                            new_variations.push(new_var);
-                        }
-                    }
-                    variations.extend(new_variations);
-                }
-            }
-            
-            ngrams.extend(variations);
-        } else {
-            ngrams.push(ngram);
-        }
+
+    if format.is_json() {
+        let records: Vec<serde_json::Value> = rankings
+            .iter()
+            .map(|ranking| {
+                let results: Vec<VerseRecord> = ranking
+                    .xrefs
+                    .iter()
+                    .map(|xref| VerseRecord { score: Some(xref.score), ..VerseRecord::new(xref.verse, bible.note_display()) })
+                    .collect();
+                serde_json::json!({ "metric": metric_label(&ranking.metric), "results": results })
+            })
+            .collect();
+        let text = match format {
+            OutputFormat::JsonLines => records.iter().map(|r| r.to_string()).collect::<Vec<_>>().join("\n"),
+            _ => serde_json::to_string_pretty(&records).unwrap_or_default(),
+        };
+        println!("{}", text);
+        return Ok(());
     }
-    
-    ngrams
-}
 
-// Check if two texts share at least one n-gram
-fn has_ngram_match(text1: &str, text2: &str, n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> bool {
-    let ngrams1 = extract_ngrams(text1, n, synonym_mapper, use_synonyms);
-    let ngrams2 = extract_ngrams(text2, n, synonym_mapper, use_synonyms);
-    
-    let set2: std::collections::HashSet<_> = ngrams2.iter().collect();
-    
-    for ngram in &ngrams1 {
-        if set2.contains(ngram) {
-            return true;
-        }
+    // Cells are laid out plain, so only the headings are colored
+    let columns: Vec<Vec<String>> = rankings
+        .iter()
+        .map(|ranking| {
+            ranking
+                .xrefs
+                .iter()
+                .enumerate()
+                .map(|(rank, xref)| {
+                    let score = match ranking.metric {
+                        SimilarityMetric::NGram(_) => format!("{:.0} shared", xref.score),
+                        _ => format!("{:.1}%", xref.score * 100.0),
+                    };
+                    format!("{}. {} ({})", rank + 1, xref.verse.reference(), score)
+                })
+                .collect()
+        })
+        .collect();
+    let headings: Vec<String> = rankings.iter().map(|ranking| metric_label(&ranking.metric)).collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .zip(&headings)
+        .map(|(cells, heading)| cells.iter().map(|cell| cell.chars().count()).chain([heading.len()]).max().unwrap_or(0) + 3)
+        .collect();
+
+    let title = format!("Cross-references of {} by metric:", reference);
+    println!("{}\n", if use_color { title.green().bold().to_string() } else { title });
+    let header: String = headings.iter().zip(&widths).map(|(heading, width)| format!("{:<width$}", heading, width = width)).collect();
+    println!("{}", if use_color { header.trim_end().bold().to_string() } else { header.trim_end().to_string() });
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+    for row in 0..rows {
+        let line: String = columns
+            .iter()
+            .zip(&widths)
+            .map(|(cells, width)| format!("{:<width$}", cells.get(row).map(String::as_str).unwrap_or("-"), width = width))
+            .collect();
+        println!("{}", line.trim_end());
     }
-    
-    false
+    Ok(())
 }
 
-// Count number of matching n-grams
-fn count_ngram_matches(text1: &str, text2: &str, n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> f32 {
-    let ngrams1 = extract_ngrams(text1, n, synonym_mapper, use_synonyms);
-    let ngrams2 = extract_ngrams(text2, n, synonym_mapper, use_synonyms);
-    
-    let set2: std::collections::HashSet<_> = ngrams2.iter().collect();
-    
-    let mut count = 0;
-    let mut counted = std::collections::HashSet::new();
-    
-    for ngram in &ngrams1 {
-        if set2.contains(ngram) && !counted.contains(ngram) {
-            count += 1;
-            counted.insert(ngram);
+/// Print the verses whose wording is closest to `text` (--find-like), for finding a verse that
+/// is only roughly remembered. Scores are character n-gram overlap, shown as percentages.
+pub fn find_like_cli(bible: &Bible, text: &str, top: Option<usize>, format: OutputFormat, bars: bool, use_color: bool) -> Result<(), BibleError> {
+    let matches = find_like(bible, text, top);
+    if matches.is_empty() {
+        return Err(BibleError::NoResults {
+            message: format!("No verses resemble '{}'.", text),
+            hint: Some("Type more of the words you remember".to_string()),
+        });
     }
+
+    if format.is_json() {
+        let records = matches
+            .iter()
+            .map(|found| VerseRecord { score: Some(found.score), ..VerseRecord::new(found.verse, bible.note_display()) })
+            .collect();
+        println!("{}", format_records(records, format));
+        return Ok(());
     }
-    
-    count as f32
+
+    let heading = format!("Verses most like \"{}\":", text);
+    if use_color {
+        println!("{}\n", heading.green().bold());
+    } else {
+        println!("{}\n", heading);
+    }
+    for found in &matches {
+        let score = format!("{:.1}%", found.score * 100.0);
+        let score = if use_color { score.yellow().bold().to_string() } else { score };
+        let score = if bars { format!("{} {}", similarity_bar(found.score, BAR_WIDTH, use_color), score) } else { score };
+        println!("{} - {} {}:{} {}", score, found.verse.book.cyan(), found.verse.chapter.to_string().cyan(), found.verse.verse.to_string().cyan(), found.verse.text);
+    }
+    Ok(())
 }
 
 
@@ -577,10 +1275,10 @@ mod tests {
     #[test]
     fn test_synonym_expansion() {
         let mut mapper = SynonymMapper::new();
-        mapper.synonyms.insert("god".to_string(), vec!["god".to_string(), "lord".to_string()]);
-        mapper.synonyms.insert("love".to_string(), vec!["love".to_string(), "beloved".to_string()]);
+        mapper.set_group("god", vec!["god".to_string(), "lord".to_string()]);
+        mapper.set_group("love", vec!["love".to_string(), "beloved".to_string()]);
         
-        let expanded = mapper.expand_query("god love");
+        let expanded = mapper.expanded_terms("god love");
         
         assert!(expanded.contains(&"god".to_string()));
         assert!(expanded.contains(&"lord".to_string()));
@@ -588,14 +1286,429 @@ mod tests {
         assert!(expanded.contains(&"beloved".to_string()));
     }
     
+    // Parsed in memory; `test_load_from_reader` checks files load the same way
+    fn load_text(content: &str) -> Vec<Verse> {
+        load_bible_reader(io::Cursor::new(content)).unwrap()
+    }
+
+    #[test]
+    fn test_load_tab_separated() {
+        let verses = load_text("TST\nTest Version\nGenesis 1:1\tIn the beginning.\n1 John 4:8\tGod is love.\n");
+
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[1].book, "1 John");
+        assert_eq!((verses[1].chapter, verses[1].verse), (4, 8));
+        assert_eq!(verses[1].text, "God is love.");
+    }
+
+    #[test]
+    fn test_load_space_separated() {
+        let verses = load_text("TST\nTest Version\nGenesis 1:1    In the beginning.\nSong of Solomon 2:1 I am the rose of Sharon.\n");
+
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[0].text, "In the beginning.");
+        assert_eq!(verses[1].book, "Song of Solomon");
+        assert_eq!(verses[1].text, "I am the rose of Sharon.");
+    }
+
+    #[test]
+    fn test_load_mixed_separators() {
+        let verses = load_text("TST\nTest Version\nJohn 11:35 \t Jesus wept.\nJohn  11:36\t\tThen said the Jews.\n");
+
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[0].text, "Jesus wept.");
+        assert_eq!((verses[1].book.as_str(), verses[1].verse), ("John", 36));
+        assert_eq!(verses[1].text, "Then said the Jews.");
+    }
+
+    #[test]
+    fn test_load_from_reader() {
+        let content = "TST\nTest Version\nJohn 11:35\tJesus wept.\nnot a verse\nJohn 11:36\tThen said the Jews.\n";
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(content.as_bytes()).unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let verses = load_bible_reader(io::Cursor::new(content)).unwrap();
+
+        assert_eq!(verses, load_bible(path).unwrap());
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[1].reference(), "John 11:36");
+        let report = load_bible_report_reader(io::Cursor::new(content)).unwrap();
+        assert_eq!(report.malformed, vec![(4, "not a verse".to_string())]);
+        assert_eq!(report.verses, verses);
+        assert!(load_bible_reader(io::Cursor::new("")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_references() {
+        let content = "TST\nTest Version\nJohn 11:35\tJesus wept.\nJohn 11:36\tThen said the Jews, Behold how he loved him!\nJohn 11:35  Jesus cried.\n";
+        let mut load = load_bible_report_reader(io::Cursor::new(content)).unwrap();
+
+        assert_eq!(load.duplicates, vec![DuplicateReference { reference: "John 11:35".to_string(), first_line: 3, line: 5 }]);
+        // Every copy is loaded until the duplicates are dropped, which keeps the first
+        assert_eq!(load.verses.len(), 3);
+        load.drop_duplicates();
+        let texts: Vec<&str> = load.verses.iter().map(|v| v.text.as_str()).collect();
+        assert_eq!(texts, ["Jesus wept.", "Then said the Jews, Behold how he loved him!"]);
+    }
+
+    #[test]
+    fn test_load_bom_and_crlf() {
+        // A BOM must not end up in the first book name, nor a '\r' at the end of each text
+        let verses = load_text("\u{feff}Genesis 1:1\tIn the beginning.\r\nGenesis 1:2\tAnd the earth was without form.\r\n");
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[0].book, "Genesis");
+        assert_eq!(verses[0].text, "In the beginning.");
+        assert_eq!(verses[1].text, "And the earth was without form.");
+
+        // The header lines are still skipped, and a final line without a newline keeps no '\r'
+        let verses = load_text("\u{feff}TST\r\nTest Version\r\nJohn 11:35\tJesus wept.\r");
+        assert_eq!(verses.len(), 1);
+        assert_eq!(verses[0].text, "Jesus wept.");
+    }
+
+    #[test]
+    fn test_plain_output_round_trips() {
+        let verses = load_text("TST\nTest Version\nGenesis 1:1\tIn the beginning.\nSong of Solomon 2:1  I am the rose of Sharon.\n");
+        let plain: String = verses.iter().map(|v| v.plain_line() + "\n").collect();
+
+        assert_eq!(plain, "Genesis 1:1\tIn the beginning.\nSong of Solomon 2:1\tI am the rose of Sharon.\n");
+
+        // Without a header, the first lines are verses rather than skipped
+        let reloaded = load_text(&plain);
+        assert_eq!(reloaded.len(), verses.len());
+        for (a, b) in verses.iter().zip(&reloaded) {
+            assert_eq!((&a.book, a.chapter, a.verse, &a.text), (&b.book, b.chapter, b.verse, &b.text));
+        }
+    }
+
+    #[test]
+    fn test_note_brackets() {
+        let brackets = NoteBrackets::default();
+        assert_eq!(
+            brackets.extract("God is love [agape], and he that dwelleth in love [agape] dwelleth in God"),
+            ("God is love, and he that dwelleth in love dwelleth in God".to_string(), vec!["agape".to_string(), "agape".to_string()])
+        );
+        // An unclosed bracket (as in the ASV's "[Selah") is left alone
+        assert_eq!(brackets.extract("And he answereth me. [Selah").0, "And he answereth me. [Selah");
+
+        let braces = NoteBrackets::parse("{}").unwrap();
+        let mut verses = vec![Verse::new("John", 21, 15, "lovest thou me {agapao} more than these?")];
+        extract_notes(&mut verses, braces);
+        assert_eq!((verses[0].text.as_str(), verses[0].notes.as_slice()), ("lovest thou me more than these?", ["agapao".to_string()].as_slice()));
+        // They print in the brackets they were written in, in every style, and only when shown
+        let shown = NoteDisplay { brackets: braces, show: true };
+        assert_eq!(verses[0].delimited_line(Delimiter::default(), shown), "John 21:15\tlovest thou me more than these? {agapao}");
+        assert!(format!("{}", verses[0].shown(shown)).ends_with("{agapao}"));
+        let passage: Vec<&Verse> = verses.iter().collect();
+        assert!(crate::passage::format_prose(&passage, shown, false).ends_with("these? {agapao}"));
+        assert!(crate::passage::format_inline(&passage, shown).ends_with("these? {agapao}"));
+        let hidden = NoteDisplay { brackets: braces, show: false };
+        assert_eq!(verses[0].delimited_line(Delimiter::default(), hidden), "John 21:15\tlovest thou me more than these?");
+        assert!(!format!("{}", verses[0]).contains("agapao"));
+        // --extract writes them back whether or not they are shown
+        let mut hidden = vec![Verse::new("John", 21, 15, "lovest thou me [agapao] more than these?")];
+        extract_notes(&mut hidden, brackets);
+        let mut written = Vec::new();
+        crate::export::write_text_bible(&mut written, &[], &[&hidden[0]], brackets).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), "John 21:15\tlovest thou me more than these? [agapao]\n");
+        assert!(NoteBrackets::parse("[[").is_err() && NoteBrackets::parse("ab").is_err() && NoteBrackets::parse("[").is_err());
+    }
+
+    #[test]
+    fn test_pipe_delimited_round_trip() {
+        let verses = load_text("Genesis 1:1\tIn the beginning.\nSong of Solomon 2:1\tI am the rose of Sharon, | and the lily.\n");
+        let pipe = Delimiter::parse("pipe").unwrap();
+        let lines: String = verses.iter().map(|v| v.delimited_line(pipe, NoteDisplay::default()) + "\n").collect();
+
+        assert_eq!(lines, "Genesis 1:1|In the beginning.\nSong of Solomon 2:1|I am the rose of Sharon, | and the lily.\n");
+        // Only the first delimiter separates; the text keeps any others
+        assert_eq!(load_bible_delimited(io::Cursor::new(&lines), pipe).unwrap(), verses);
+        // Tab-separated lines still load, even when their text has the delimiter in it
+        let tabbed: String = verses.iter().map(|v| v.plain_line() + "\n").collect();
+        assert_eq!(load_bible_delimited(io::Cursor::new(&tabbed), pipe).unwrap(), verses);
+
+        assert_eq!(Delimiter::parse("comma"), Ok(Delimiter(',')));
+        assert_eq!(Delimiter::parse("tab"), Ok(Delimiter::default()));
+        for bad in [":", " ", "a", "7", "||"] {
+            assert!(Delimiter::parse(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    fn small_bible() -> Bible {
+        let verse = |book: &str, chapter, number| Verse::new(book, chapter, number, "");
+        Bible::new(vec![
+            verse("Genesis", 1, 1),
+            verse("Genesis", 1, 2),
+            verse("Genesis", 2, 1),
+            verse("Psalm", 23, 1),
+            verse("Psalm", 23, 2),
+            verse("Genesis", 1, 3),
+        ])
+    }
+
+    #[test]
+    fn test_bible_books_and_chapters() {
+        let bible = small_bible();
+
+        assert_eq!(bible.books().collect::<Vec<_>>(), vec!["Genesis", "Psalm"]);
+        assert_eq!(bible.chapters("Genesis").collect::<Vec<_>>(), vec![1, 2]);
+        // Canonical names and abbreviations resolve to the book as stored
+        assert_eq!(bible.chapters("Psalms").collect::<Vec<_>>(), vec![23]);
+        assert_eq!(bible.chapters("Exodus").count(), 0);
+    }
+
+    #[test]
+    fn test_bible_chapter_verses() {
+        let bible = small_bible();
+
+        // The stray Genesis 1:3 is moved into its chapter
+        let numbers: Vec<u32> = bible.chapter_verses("Gen", 1).iter().map(|v| v.verse).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(bible.chapter_verses("Ps", 23).len(), 2);
+        assert!(bible.chapter_verses("Genesis", 3).is_empty());
+        // Deref gives the whole verse list
+        assert_eq!(bible.len(), 6);
+    }
+
+    #[test]
+    fn test_nearest_verses() {
+        // Mark ends at 16:8 and is missing 9:44, as in translations following the oldest manuscripts
+        let verse = |chapter, verse| Verse::new("Mark", chapter, verse, "");
+        let bible = Bible::new(vec![verse(9, 43), verse(9, 45), verse(15, 47), verse(16, 1), verse(16, 8)]);
+        let positions = |found: Vec<&Verse>| found.iter().map(|v| (v.chapter, v.verse)).collect::<Vec<_>>();
+
+        assert_eq!(positions(bible.nearest_verses("Mark", 16, 19, 19)), vec![(16, 8)]);
+        assert_eq!(positions(bible.nearest_verses("Mk", 9, 44, 44)), vec![(9, 43), (9, 45)]);
+        assert_eq!(positions(bible.nearest_verses("Mark", 16, 9, 20)), vec![(16, 8)]);
+        assert_eq!(positions(bible.nearest_verses("Mark", 10, 0, u32::MAX)), vec![(9, 45), (15, 47)]);
+        assert_eq!(positions(bible.nearest_verses("Mark", 1, 1, 1)), vec![(9, 43)]);
+        assert!(bible.nearest_verses("Luke", 1, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn test_lookup_errors() {
+        let bible = small_bible();
+
+        let error = lookup_verse_cli(&bible, "Genesis 1:1 extra", PassageStyle::Lines, false).unwrap_err();
+        let json: serde_json::Value = serde_json::from_str(&error.to_json().to_string()).unwrap();
+        assert_eq!(json["error"], "InvalidReference");
+        assert_ne!(error.exit_code(), 0);
+
+        let error = lookup_verse_cli(&bible, "Genesis 1:9", PassageStyle::Lines, false).unwrap_err();
+        assert_eq!(error.kind(), "NotFound");
+        assert!(lookup_verse_cli(&bible, "Genesis 1:1", PassageStyle::Lines, false).is_ok());
+    }
+
+    #[test]
+    fn test_canonical_reference_must_exist() {
+        let bible = small_bible();
+
+        assert!(print_canonical_reference(&bible, "gen 1.2").is_ok());
+        assert_eq!(print_canonical_reference(&bible, "Genesis 9:1").unwrap_err().kind(), "NotFound");
+        assert_eq!(print_canonical_reference(&bible, "Hezekiah 1:1").unwrap_err().kind(), "InvalidReference");
+    }
+
+    #[test]
+    fn test_sort_canonical() {
+        let verse = |book: &str, chapter, verse| Verse::new(book, chapter, verse, "");
+        let mut verses = vec![verse("John", 3, 16), verse("Genesis", 2, 1), verse("Genesis", 1, 2), verse("Exodus", 1, 1)];
+
+        sort_canonical(&mut verses);
+
+        let order: Vec<_> = verses.iter().map(|v| (v.book.as_str(), v.chapter, v.verse)).collect();
+        assert_eq!(order, vec![("Genesis", 1, 2), ("Genesis", 2, 1), ("Exodus", 1, 1), ("John", 3, 16)]);
+    }
+
+    #[test]
+    fn test_passage_ranges() {
+        let mut verses = vec![Verse::new("Psalms", 118, 29, "O give thanks unto the LORD;")];
+        verses.extend((1..=176).map(|number| Verse::new("Psalms", 119, number, format!("Verse {}", number))));
+        verses.push(Verse::new("Psalms", 120, 1, "In my distress I cried unto the LORD,"));
+        verses.push(Verse::new("Proverbs", 1, 1, "The proverbs of Solomon"));
+        let bible = Bible::new(verses);
+        let passage = |input: &str| bible.passage(&parse_reference(input).unwrap());
+
+        let whole = passage("Psalms 119:1-176");
+        assert_eq!(whole.len(), 176);
+        assert_eq!((whole[0].chapter, whole[0].verse), (119, 1));
+        assert_eq!((whole[175].chapter, whole[175].verse), (119, 176));
+        assert_eq!(passage("Ps 119:105-112").iter().map(|v| v.verse).collect::<Vec<_>>(), (105..=112).collect::<Vec<_>>());
+        // Ranges past the end stop at the chapter's last verse
+        assert_eq!(passage("Psalms 119:170-200").len(), 7);
+        assert!(passage("Psalms 119:177-180").is_empty());
+
+        // Every form agrees with checking each verse against the reference
+        for input in ["Psalms", "Psalms 119", "Psalms 118-120", "Psalms 119:50", "Psalms 121", "Proverbs 1:1", "Job 1:1"] {
+            let reference = parse_reference(input).unwrap();
+            let scanned: Vec<&Verse> = bible.iter().filter(|v| reference.contains(v)).collect();
+            assert_eq!(passage(input).iter().collect::<Vec<_>>(), scanned, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_has_reference() {
+        let verse = |chapter, number| Verse::new("John", chapter, number, "");
+        // John 3 is missing verse 17, and there is no chapter 4
+        let mut verses: Vec<Verse> = (1..=36).filter(|&n| n != 17).map(|n| verse(3, n)).collect();
+        verses.extend((1..=5).map(|n| verse(5, n)));
+        let bible = Bible::new(verses);
+        let exists = |input: &str, any| bible.has_reference(&parse_reference(input).unwrap(), any);
+
+        assert!(exists("John 3:16", false));
+        assert!(!exists("John 3:17", false));
+        assert!(!exists("Acts 1:1", false));
+        assert!(exists("John", false) && exists("John 5", false) && !exists("John 4", false));
+
+        // A range needs every verse, or with `any` one of them
+        assert!(exists("John 3:14-16", false));
+        assert!(!exists("John 3:16-18", false));
+        assert!(exists("John 3:16-18", true));
+        assert!(!exists("John 3:40-42", true));
+        assert!(!exists("John 3-5", false));
+        assert!(exists("John 3-5", true));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let verse = |book: &str, chapter, number| Verse::new(book, chapter, number, "");
+        let mut verses: Vec<Verse> = (1..=5).map(|number| verse("Jude", 1, number)).collect();
+        verses.insert(0, verse("3 John", 1, 14));
+        verses.push(verse("Revelation", 1, 1));
+        let bible = Bible::new(verses);
+        let numbers = |verses: &[&Verse]| verses.iter().map(|v| v.verse).collect::<Vec<_>>();
+
+        let (before, target, after) = bible.neighbors(&parse_reference("Jude 1:3").unwrap(), 2).unwrap();
+        assert_eq!((numbers(&before), target.verse, numbers(&after)), (vec![1, 2], 3, vec![4, 5]));
+
+        // The first and last verses of a book have neighbors on one side only
+        let (before, target, after) = bible.neighbors(&parse_reference("Jude 1:1").unwrap(), 2).unwrap();
+        assert!(before.is_empty());
+        assert_eq!((target.reference().as_str(), numbers(&after)), ("Jude 1:1", vec![2, 3]));
+        let (before, _, after) = bible.neighbors(&parse_reference("Jude 1:5").unwrap(), 10).unwrap();
+        assert_eq!(numbers(&before), vec![1, 2, 3, 4]);
+        assert!(after.is_empty());
+
+        assert!(bible.neighbors(&parse_reference("Jude 1:6").unwrap(), 1).is_none());
+        assert!(bible.neighbors(&parse_reference("Jude 1").unwrap(), 1).is_none());
+    }
+
+    #[test]
+    fn test_verse_order() {
+
+        // Bible order rather than alphabetical: Genesis before Exodus, John before 1 John
+        assert!(Verse::new("Genesis", 50, 26, "") < Verse::new("Exodus", 1, 1, ""));
+        assert!(Verse::new("John", 21, 25, "") < Verse::new("1 John", 1, 1, ""));
+        assert!(Verse::new("Malachi", 4, 6, "") < Verse::new("Matthew", 1, 1, ""));
+        // Chapters and verses compare as numbers
+        assert!(Verse::new("Psalms", 9, 1, "") < Verse::new("Psalms", 10, 1, ""));
+        assert!(Verse::new("Psalms", 119, 2, "") < Verse::new("Psalms", 119, 10, ""));
+        // Unknown books come last
+        assert!(Verse::new("Revelation", 22, 21, "") < Verse::new("Enoch", 1, 1, ""));
+
+        // Equal only when the text matches too, and ordered consistently with that
+        assert_eq!(Verse::new("John", 11, 35, "Jesus wept."), Verse::new("John", 11, 35, "Jesus wept."));
+        assert_ne!(Verse::new("John", 11, 35, "Jesus wept."), Verse::new("John", 11, 35, "Jesus cried."));
+        assert_ne!(Verse::new("John", 11, 35, "Jesus wept.").cmp(&Verse::new("John", 11, 35, "Jesus cried.")), std::cmp::Ordering::Equal);
+        // Notes count the same for both
+        let plain = Verse::new("John", 3, 16, "For God so loved the world");
+        let noted = Verse { notes: vec!["agapao".to_string()], ..plain.clone() };
+        assert_ne!(plain, noted);
+        assert_ne!(plain.cmp(&noted), std::cmp::Ordering::Equal);
+        assert_eq!((noted == noted.clone(), noted.cmp(&noted.clone())), (true, std::cmp::Ordering::Equal));
+
+        let mut verses = [Verse::new("Romans", 8, 28, ""), Verse::new("Ruth", 1, 16, ""), Verse::new("Acts", 2, 1, ""), Verse::new("Revelation", 1, 1, "")];
+        verses.sort();
+        let books: Vec<&str> = verses.iter().map(|v| v.book.as_str()).collect();
+        assert_eq!(books, vec!["Ruth", "Acts", "Romans", "Revelation"]);
+    }
+
+    #[test]
+    fn test_boundary_verse() {
+        use crate::books::Testament;
+        let verse = |book: &str, chapter, verse| Verse::new(book, chapter, verse, "");
+        let bible = vec![verse("Malachi", 4, 6), verse("Matthew", 1, 1), verse("John", 21, 25), verse("John", 1, 1)];
+        let john = VerseFilter { book: Some("John".to_string()), ..Default::default() };
+        let nt = VerseFilter { testament: Some(Testament::New), ..Default::default() };
+        let missing = VerseFilter { book: Some("Jude".to_string()), ..Default::default() };
+
+        assert_eq!(boundary_verse(&bible, &john, false).map(|v| v.chapter), Some(1));
+        assert_eq!(boundary_verse(&bible, &john, true).map(|v| (v.chapter, v.verse)), Some((21, 25)));
+        assert_eq!(boundary_verse(&bible, &nt, false).map(|v| v.book.as_str()), Some("Matthew"));
+        assert!(boundary_verse(&bible, &missing, true).is_none());
+    }
+
+    #[test]
+    fn test_load_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("psalm.txt");
+        let lines: Vec<String> = (1..=25).map(|v| format!("Psalms 119:{} Blessed are the undefiled", v)).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let mut reported = Vec::new();
+        let verses = load_bible_with_progress(path.to_str().unwrap(), 10, |count| reported.push(count)).unwrap();
+        assert_eq!(verses.len(), 25);
+        assert_eq!(reported, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_switch_translation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("web.txt"), "John 3:16 For God so loved the world\n").unwrap();
+        let registry = TranslationRegistry::parse("[web]\nname = \"World English Bible\"\npath = \"web.txt\"\n", dir.path()).unwrap();
+
+        let (bible, name, file, synonyms) = switch_translation(&registry, "WEB").unwrap();
+        assert_eq!(name, "World English Bible");
+        assert_eq!(file, dir.path().join("web.txt").to_string_lossy());
+        assert_eq!(bible.len(), 1);
+        assert!(synonyms.is_none());
+
+        let error = switch_translation(&registry, "kjv").unwrap_err();
+        assert_eq!(error.kind(), "UnknownTranslation");
+        assert!(error.to_string().contains("Available translations: web"));
+    }
+
+    #[test]
+    fn test_translation_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        for (file, text) in [("web.txt", "John 3:16 For God so loved the world"), ("ylt.txt", "John 3:16 For God did so love the world"), ("dby.txt", "John 3:16 For God so loved the world")] {
+            std::fs::write(dir.path().join(file), format!("{}\n", text)).unwrap();
+        }
+        let registry = TranslationRegistry::parse(
+            "[web]\nname = \"World English Bible\"\npath = \"web.txt\"\n[ylt]\npath = \"ylt.txt\"\n[dby]\npath = \"dby.txt\"\n",
+            dir.path(),
+        )
+        .unwrap();
+        let mut cache = TranslationCache::new(2);
+
+        // Switching back and forth loads each translation once; the files aren't read again
+        let (web, name, cached) = cache.switch(&registry, "web").unwrap();
+        assert_eq!((name.as_str(), cached), ("World English Bible", false));
+        let (ylt, _, cached) = cache.switch(&registry, "ylt").unwrap();
+        assert!(!cached);
+        std::fs::remove_file(dir.path().join("web.txt")).unwrap();
+        let (again, name, cached) = cache.switch(&registry, "WEB").unwrap();
+        assert!(cached && Rc::ptr_eq(&web, &again), "{}", name);
+        assert!(Rc::ptr_eq(&ylt, &cache.switch(&registry, "ylt").unwrap().0));
+
+        assert!(cache.synonyms().is_none());
+
+        // A third translation drops the least recently used, which must then be loaded again;
+        // the synonyms its file embeds are kept with it
+        std::fs::write(dir.path().join("dby.txt"), r#"{"synonyms": {"love": ["love", "charity"]}, "John": {"3": {"16": "For God so loved the world"}}}"#).unwrap();
+        let (_, _, cached) = cache.switch(&registry, "dby").unwrap();
+        assert!(!cached);
+        assert_eq!(cache.synonyms().map(|groups| groups["love"].clone()), Some(vec!["love".to_string(), "charity".to_string()]));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.switch(&registry, "ylt").unwrap().2);
+        assert!(cache.switch(&registry, "web").unwrap_err().to_string().contains("not found"));
+        assert_eq!(cache.switch(&registry, "kjv").unwrap_err().kind(), "UnknownTranslation");
+    }
+
     #[test]
     fn test_verse_display() {
-        let verse = Verse {
-            book: "John".to_string(),
-            chapter: 3,
-            verse: 16,
-            text: "For God so loved the world...".to_string(),
-        };
+        let verse = Verse::new("John", 3, 16, "For God so loved the world...");
         
         let display = format!("{}", verse);
         assert!(display.contains("John"));
@@ -0,0 +1,105 @@
+// bookmarks.rs
+// Saved references kept across interactive sessions (`bookmark <ref>`, `bookmarks`)
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::bible::clean_line;
+use crate::error::BibleError;
+use crate::reference::{canonicalize_reference, invalid_reference_reason};
+
+/// File bookmarks are kept in when no --bookmark-file is given
+pub const DEFAULT_BOOKMARK_FILE: &str = "bookmarks.txt";
+
+/// A user's saved references, in the order they were added. Each is stored in canonical
+/// form (`John 3:16`), one per line of the bookmark file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmarks {
+    file: String,
+    references: Vec<String>,
+}
+
+impl Bookmarks {
+    /// No bookmarks yet, to be saved to `file`
+    pub fn new(file: &str) -> Self {
+        Bookmarks { file: file.to_string(), references: Vec::new() }
+    }
+
+    /// Load bookmarks from `file`; a missing file is an empty list that `save` will create.
+    /// Blank lines, `#` comments and lines that aren't references are skipped.
+    pub fn load(file: &str) -> io::Result<Self> {
+        let mut bookmarks = Bookmarks::new(file);
+        if !Path::new(file).exists() {
+            return Ok(bookmarks);
+        }
+        for (index, line) in fs::read_to_string(file)?.lines().enumerate() {
+            let line = clean_line(line, index).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(reference) = canonicalize_reference(line) {
+                if !bookmarks.references.contains(&reference) {
+                    bookmarks.references.push(reference);
+                }
+            }
+        }
+        Ok(bookmarks)
+    }
+
+    /// Bookmark a reference, however it is typed (`jn 3.16`). Returns its canonical form and
+    /// whether it was new; a reference already bookmarked isn't added twice.
+    pub fn add(&mut self, input: &str) -> Result<(String, bool), BibleError> {
+        let reference = canonicalize_reference(input).ok_or_else(|| BibleError::InvalidReference {
+            reason: invalid_reference_reason(input),
+            expected: "a reference to a known book, like 'John 3:16' or 'Psalm 23'",
+        })?;
+        if self.references.contains(&reference) {
+            return Ok((reference, false));
+        }
+        self.references.push(reference.clone());
+        Ok((reference, true))
+    }
+
+    /// Write the bookmarks back to their file
+    pub fn save(&self) -> io::Result<()> {
+        let mut content = String::new();
+        for reference in &self.references {
+            content.push_str(reference);
+            content.push('\n');
+        }
+        fs::write(&self.file, content)
+    }
+
+    pub fn references(&self) -> &[String] {
+        &self.references
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("bookmarks.txt");
+        let file = file.to_str().unwrap();
+
+        let mut bookmarks = Bookmarks::load(file).unwrap();
+        assert!(bookmarks.references().is_empty());
+        assert_eq!(bookmarks.add("jn 3.16").unwrap(), ("John 3:16".to_string(), true));
+        assert_eq!(bookmarks.add("Ps 23").unwrap(), ("Psalms 23".to_string(), true));
+        // The same reference typed differently is a duplicate
+        assert_eq!(bookmarks.add("John 3 : 16").unwrap(), ("John 3:16".to_string(), false));
+        assert_eq!(bookmarks.add("Hezekiah 1:1").unwrap_err().kind(), "InvalidReference");
+        bookmarks.save().unwrap();
+
+        let reloaded = Bookmarks::load(file).unwrap();
+        assert_eq!(reloaded.references(), ["John 3:16", "Psalms 23"]);
+        assert_eq!(reloaded, bookmarks);
+    }
+}
@@ -0,0 +1,298 @@
+// books.rs
+// Canonical book list and abbreviation table shared by lookup and filtering
+
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+use colored::*;
+
+/// A canonical book together with the abbreviations and alternate names it accepts
+pub struct BookInfo {
+    pub name: &'static str,
+    pub abbreviations: &'static [&'static str],
+}
+
+/// All 66 books in canonical order
+pub const BOOKS: &[BookInfo] = &[
+    BookInfo { name: "Genesis", abbreviations: &["Gen", "Ge", "Gn"] },
+    BookInfo { name: "Exodus", abbreviations: &["Exod", "Exo", "Ex"] },
+    BookInfo { name: "Leviticus", abbreviations: &["Lev", "Le", "Lv"] },
+    BookInfo { name: "Numbers", abbreviations: &["Num", "Nu", "Nm"] },
+    BookInfo { name: "Deuteronomy", abbreviations: &["Deut", "Deu", "Dt"] },
+    BookInfo { name: "Joshua", abbreviations: &["Josh", "Jos", "Jsh"] },
+    BookInfo { name: "Judges", abbreviations: &["Judg", "Jdg", "Jg"] },
+    BookInfo { name: "Ruth", abbreviations: &["Rth", "Ru"] },
+    BookInfo { name: "1 Samuel", abbreviations: &["1 Sam", "1 Sa", "1 Sm"] },
+    BookInfo { name: "2 Samuel", abbreviations: &["2 Sam", "2 Sa", "2 Sm"] },
+    BookInfo { name: "1 Kings", abbreviations: &["1 Kgs", "1 Ki", "1 Kin"] },
+    BookInfo { name: "2 Kings", abbreviations: &["2 Kgs", "2 Ki", "2 Kin"] },
+    BookInfo { name: "1 Chronicles", abbreviations: &["1 Chr", "1 Chron", "1 Ch"] },
+    BookInfo { name: "2 Chronicles", abbreviations: &["2 Chr", "2 Chron", "2 Ch"] },
+    BookInfo { name: "Ezra", abbreviations: &["Ezr"] },
+    BookInfo { name: "Nehemiah", abbreviations: &["Neh", "Ne"] },
+    BookInfo { name: "Esther", abbreviations: &["Esth", "Est", "Es"] },
+    BookInfo { name: "Job", abbreviations: &["Jb"] },
+    BookInfo { name: "Psalms", abbreviations: &["Psalm", "Ps", "Psa", "Pss"] },
+    BookInfo { name: "Proverbs", abbreviations: &["Prov", "Pro", "Prv", "Pr"] },
+    BookInfo { name: "Ecclesiastes", abbreviations: &["Eccl", "Ecc", "Qoh"] },
+    BookInfo { name: "Song of Solomon", abbreviations: &["Song", "Song of Songs", "SOS", "Canticles"] },
+    BookInfo { name: "Isaiah", abbreviations: &["Isa", "Is"] },
+    BookInfo { name: "Jeremiah", abbreviations: &["Jer", "Je", "Jr"] },
+    BookInfo { name: "Lamentations", abbreviations: &["Lam", "La"] },
+    BookInfo { name: "Ezekiel", abbreviations: &["Ezek", "Eze", "Ezk"] },
+    BookInfo { name: "Daniel", abbreviations: &["Dan", "Da", "Dn"] },
+    BookInfo { name: "Hosea", abbreviations: &["Hos", "Ho"] },
+    BookInfo { name: "Joel", abbreviations: &["Joe", "Jl"] },
+    BookInfo { name: "Amos", abbreviations: &["Amo", "Am"] },
+    BookInfo { name: "Obadiah", abbreviations: &["Obad", "Oba", "Ob"] },
+    BookInfo { name: "Jonah", abbreviations: &["Jon", "Jnh"] },
+    BookInfo { name: "Micah", abbreviations: &["Mic", "Mc"] },
+    BookInfo { name: "Nahum", abbreviations: &["Nah", "Na"] },
+    BookInfo { name: "Habakkuk", abbreviations: &["Hab", "Hb"] },
+    BookInfo { name: "Zephaniah", abbreviations: &["Zeph", "Zep", "Zp"] },
+    BookInfo { name: "Haggai", abbreviations: &["Hag", "Hg"] },
+    BookInfo { name: "Zechariah", abbreviations: &["Zech", "Zec", "Zc"] },
+    BookInfo { name: "Malachi", abbreviations: &["Mal", "Ml"] },
+    BookInfo { name: "Matthew", abbreviations: &["Matt", "Mat", "Mt"] },
+    BookInfo { name: "Mark", abbreviations: &["Mrk", "Mar", "Mk", "Mr"] },
+    BookInfo { name: "Luke", abbreviations: &["Luk", "Lk"] },
+    BookInfo { name: "John", abbreviations: &["Jhn", "Joh", "Jn"] },
+    BookInfo { name: "Acts", abbreviations: &["Act", "Ac"] },
+    BookInfo { name: "Romans", abbreviations: &["Rom", "Ro", "Rm"] },
+    BookInfo { name: "1 Corinthians", abbreviations: &["1 Cor", "1 Co"] },
+    BookInfo { name: "2 Corinthians", abbreviations: &["2 Cor", "2 Co"] },
+    BookInfo { name: "Galatians", abbreviations: &["Gal", "Ga"] },
+    BookInfo { name: "Ephesians", abbreviations: &["Eph", "Ephes"] },
+    BookInfo { name: "Philippians", abbreviations: &["Phil", "Php", "Pp"] },
+    BookInfo { name: "Colossians", abbreviations: &["Col", "Co"] },
+    BookInfo { name: "1 Thessalonians", abbreviations: &["1 Thess", "1 Thes", "1 Th"] },
+    BookInfo { name: "2 Thessalonians", abbreviations: &["2 Thess", "2 Thes", "2 Th"] },
+    BookInfo { name: "1 Timothy", abbreviations: &["1 Tim", "1 Ti"] },
+    BookInfo { name: "2 Timothy", abbreviations: &["2 Tim", "2 Ti"] },
+    BookInfo { name: "Titus", abbreviations: &["Tit", "Ti"] },
+    BookInfo { name: "Philemon", abbreviations: &["Phlm", "Philem", "Phm"] },
+    BookInfo { name: "Hebrews", abbreviations: &["Heb"] },
+    BookInfo { name: "James", abbreviations: &["Jas", "Jm"] },
+    BookInfo { name: "1 Peter", abbreviations: &["1 Pet", "1 Pe", "1 Pt"] },
+    BookInfo { name: "2 Peter", abbreviations: &["2 Pet", "2 Pe", "2 Pt"] },
+    BookInfo { name: "1 John", abbreviations: &["1 Jn", "1 Jhn", "1 Jo"] },
+    BookInfo { name: "2 John", abbreviations: &["2 Jn", "2 Jhn", "2 Jo"] },
+    BookInfo { name: "3 John", abbreviations: &["3 Jn", "3 Jhn", "3 Jo"] },
+    BookInfo { name: "Jude", abbreviations: &["Jud", "Jd"] },
+    BookInfo { name: "Revelation", abbreviations: &["Rev", "Re", "Revelations", "Apocalypse"] },
+];
+
+/// A named run of consecutive books (e.g. the Gospels), usable wherever a book filter is
+pub struct BookGroup {
+    pub names: &'static [&'static str],
+    /// Canonical ranks of the first and last book in the group (inclusive)
+    pub first: usize,
+    pub last: usize,
+}
+
+/// Traditional groupings of the books
+pub const BOOK_GROUPS: &[BookGroup] = &[
+    BookGroup { names: &["Law", "Pentateuch", "Torah"], first: 0, last: 4 },
+    BookGroup { names: &["History", "Historical Books"], first: 5, last: 16 },
+    BookGroup { names: &["Poetry", "Wisdom"], first: 17, last: 21 },
+    BookGroup { names: &["Major Prophets"], first: 22, last: 26 },
+    BookGroup { names: &["Minor Prophets"], first: 27, last: 38 },
+    BookGroup { names: &["Gospels"], first: 39, last: 42 },
+    BookGroup { names: &["Pauline Epistles", "Paul"], first: 44, last: 56 },
+    BookGroup { names: &["General Epistles"], first: 57, last: 64 },
+];
+
+lazy_static! {
+    // Every accepted spelling, normalized, mapped to its canonical name
+    static ref BOOK_LOOKUP: HashMap<String, &'static str> = {
+        let mut lookup = HashMap::new();
+        for book in BOOKS {
+            lookup.insert(normalize(book.name), book.name);
+            for abbreviation in book.abbreviations {
+                lookup.insert(normalize(abbreviation), book.name);
+            }
+        }
+        lookup
+    };
+}
+
+// Lowercase, drop periods and make the space after a leading book number optional
+fn normalize(name: &str) -> String {
+    let name = name.to_lowercase().replace('.', " ");
+    let mut words: Vec<&str> = name.split_whitespace().collect();
+    let mut normalized = String::new();
+
+    // "1john" and "1 john" should both resolve
+    if let Some(first) = words.first() {
+        if let Some(rest) = first.strip_prefix(|c: char| c.is_ascii_digit()) {
+            if !rest.is_empty() {
+                normalized.push_str(&first[..1]);
+                normalized.push(' ');
+                normalized.push_str(rest);
+                words.remove(0);
+                if !words.is_empty() {
+                    normalized.push(' ');
+                }
+            }
+        }
+    }
+    normalized.push_str(&words.join(" "));
+    normalized
+}
+
+/// Resolve a book name or abbreviation (e.g. `Jn`, `1 Cor`, `Psalm`) to its canonical name
+pub fn resolve_book(input: &str) -> Option<&'static str> {
+    BOOK_LOOKUP.get(&normalize(input)).copied()
+}
+
+/// Position of a book in canonical order (Genesis = 0), if it is a known book
+pub fn book_rank(name: &str) -> Option<usize> {
+    let canonical = resolve_book(name)?;
+    BOOKS.iter().position(|b| b.name == canonical)
+}
+
+/// The two testaments; the first 39 canonical books form the Old Testament
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Testament {
+    Old,
+    New,
+}
+
+impl Testament {
+    /// Parse `ot`/`old` or `nt`/`new` (case-insensitive)
+    pub fn parse(s: &str) -> Option<Testament> {
+        match s.to_lowercase().as_str() {
+            "ot" | "old" => Some(Testament::Old),
+            "nt" | "new" => Some(Testament::New),
+            _ => None,
+        }
+    }
+}
+
+/// Testament a book belongs to, if it is a known book
+pub fn testament_of(name: &str) -> Option<Testament> {
+    book_rank(name).map(|rank| if rank < 39 { Testament::Old } else { Testament::New })
+}
+
+/// Find the book group called `name` (case-insensitive), e.g. `Gospels`
+pub fn book_group(name: &str) -> Option<&'static BookGroup> {
+    let name = normalize(name);
+    BOOK_GROUPS.iter().find(|group| group.names.iter().any(|n| normalize(n) == name))
+}
+
+/// Check whether a verse's book satisfies a `--book` filter.
+/// Known names, abbreviations and book groups match exactly; anything else falls back to a
+/// substring match.
+pub fn book_matches(filter: &str, book: &str) -> bool {
+    if let Some(canonical) = resolve_book(filter) {
+        return resolve_book(book) == Some(canonical);
+    }
+    if let Some(group) = book_group(filter) {
+        return book_rank(book).is_some_and(|rank| rank >= group.first && rank <= group.last);
+    }
+    book.to_lowercase().contains(&filter.to_lowercase())
+}
+
+// Print the canonical book list with the abbreviations each book accepts
+pub fn print_books_cli(use_color: bool) {
+    for book in BOOKS {
+        let name = format!("{:<16}", book.name);
+        if use_color {
+            println!("{} {}", name.cyan(), book.abbreviations.join(", "));
+        } else {
+            println!("{} {}", name, book.abbreviations.join(", "));
+        }
+    }
+
+    println!();
+    for group in BOOK_GROUPS {
+        let name = format!("{:<16}", group.names[0]);
+        let range = format!("{} - {}", BOOKS[group.first].name, BOOKS[group.last].name);
+        let aliases = if group.names.len() > 1 {
+            format!(" (also: {})", group.names[1..].join(", "))
+        } else {
+            String::new()
+        };
+        if use_color {
+            println!("{} {}{}", name.cyan(), range, aliases);
+        } else {
+            println!("{} {}{}", name, range, aliases);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_abbreviations() {
+        assert_eq!(resolve_book("Gen"), Some("Genesis"));
+        assert_eq!(resolve_book("jn"), Some("John"));
+        assert_eq!(resolve_book("1 Cor"), Some("1 Corinthians"));
+        assert_eq!(resolve_book("1cor"), Some("1 Corinthians"));
+        assert_eq!(resolve_book("1 Jn."), Some("1 John"));
+        assert_eq!(resolve_book("Ps"), Some("Psalms"));
+        assert_eq!(resolve_book("Psalm"), Some("Psalms"));
+        assert_eq!(resolve_book("Song Of Solomon"), Some("Song of Solomon"));
+        assert_eq!(resolve_book("REV"), Some("Revelation"));
+        assert_eq!(resolve_book("Hezekiah"), None);
+    }
+
+    #[test]
+    fn test_every_canonical_name_resolves_to_itself() {
+        for book in BOOKS {
+            assert_eq!(resolve_book(book.name), Some(book.name));
+        }
+        assert_eq!(BOOKS.len(), 66);
+    }
+
+    #[test]
+    fn test_abbreviations_are_unambiguous() {
+        let mut seen = HashMap::new();
+        for book in BOOKS {
+            for name in std::iter::once(&book.name).chain(book.abbreviations) {
+                if let Some(other) = seen.insert(normalize(name), book.name) {
+                    panic!("'{}' is claimed by both {} and {}", name, other, book.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_book_rank() {
+        assert_eq!(book_rank("Genesis"), Some(0));
+        assert_eq!(book_rank("Psalm"), Some(18));
+        assert_eq!(book_rank("Rev"), Some(65));
+        assert_eq!(book_rank("Hezekiah"), None);
+    }
+
+    #[test]
+    fn test_testament_of() {
+        assert_eq!(testament_of("Malachi"), Some(Testament::Old));
+        assert_eq!(testament_of("Matthew"), Some(Testament::New));
+        assert_eq!(testament_of("Hezekiah"), None);
+        assert_eq!(Testament::parse("NT"), Some(Testament::New));
+        assert_eq!(Testament::parse("old"), Some(Testament::Old));
+    }
+
+    #[test]
+    fn test_book_matches() {
+        assert!(book_matches("Jn", "John"));
+        assert!(!book_matches("John", "1 John"));
+        assert!(book_matches("Psalms", "Psalm"));
+        // Unknown filters still match by substring
+        assert!(book_matches("thess", "1 Thessalonians"));
+    }
+
+    #[test]
+    fn test_book_groups() {
+        assert!(book_matches("Gospels", "Mark"));
+        assert!(book_matches("gospels", "John"));
+        assert!(!book_matches("Gospels", "Acts"));
+        assert!(book_matches("Pentateuch", "Deut"));
+        assert!(book_matches("Pauline Epistles", "Philemon"));
+        assert!(!book_matches("Pauline Epistles", "Hebrews"));
+        assert_eq!(BOOKS[BOOK_GROUPS[0].first].name, "Genesis");
+        assert_eq!(BOOKS[BOOK_GROUPS[BOOK_GROUPS.len() - 1].last].name, "Jude");
+    }
+}
@@ -0,0 +1,180 @@
+// config.rs
+// Defaults read from bible_search.toml and the environment, which command line flags override
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use crate::output::ColorChoice;
+
+/// Name of the config file looked up in the current directory and the user's config directory
+pub const CONFIG_FILE: &str = "bible_search.toml";
+
+/// Environment variable naming the Bible file to use when no --file is given
+pub const BIBLE_FILE_VAR: &str = "BIBLE_FILE";
+/// Environment variable naming the synonyms file to use when no --synonyms-file is given
+pub const SYNONYMS_FILE_VAR: &str = "BIBLE_SYNONYMS";
+
+/// Defaults for command line options, loaded from a `bible_search.toml` like:
+///
+/// ```toml
+/// translation = "kjv"
+/// synonyms-file = "synonyms.txt"
+/// color = "auto"
+/// limit = 20
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub file: Option<String>,
+    pub synonyms_file: Option<String>,
+    /// `auto`, `always` or `never`, as for --color
+    pub color: Option<ColorChoice>,
+    /// Deprecated: `no-color = true` is `color = "never"`
+    pub no_color: Option<bool>,
+    pub limit: Option<usize>,
+    /// Translation code used when no --file or translation flag is given
+    pub translation: Option<String>,
+}
+
+impl Config {
+    /// Parse a config from TOML text. Relative paths are resolved against `base_dir`.
+    pub fn parse(content: &str, base_dir: &Path) -> io::Result<Self> {
+        let mut config: Config = toml::from_str(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse config: {}", e)))?;
+
+        for path in [&mut config.file, &mut config.synonyms_file].into_iter().flatten() {
+            if Path::new(path.as_str()).is_relative() && !base_dir.as_os_str().is_empty() {
+                *path = base_dir.join(path.as_str()).to_string_lossy().to_string();
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load_from_file(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Where a config file is looked for, in order: the current directory, then
+    /// `$XDG_CONFIG_HOME/bible_search/` (or `~/.config/bible_search/`)
+    pub fn search_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(CONFIG_FILE)];
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+        if let Some(dir) = config_home {
+            paths.push(dir.join("bible_search").join(CONFIG_FILE));
+        }
+        paths
+    }
+
+    /// Load the first config file that exists, or an empty config when there is none
+    pub fn discover() -> Result<Self, (PathBuf, io::Error)> {
+        match Self::search_paths().into_iter().find(|path| path.is_file()) {
+            Some(path) => Self::load_from_file(&path).map_err(|e| (path, e)),
+            None => Ok(Config::default()),
+        }
+    }
+}
+
+/// Whether the user set an option themselves rather than it coming from clap's default
+pub fn given(matches: &ArgMatches, id: &str) -> bool {
+    matches!(matches.value_source(id), Some(ValueSource::CommandLine))
+}
+
+/// A path from the environment; unset and empty variables count as absent
+pub fn env_path(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Resolve an option: a value given on the command line wins, then `fallback` (the
+/// environment or config file value), then the option's built-in default
+pub fn setting<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str, fallback: Option<T>) -> Option<T> {
+    if given(matches, id) {
+        return matches.get_one::<T>(id).cloned();
+    }
+    fallback.or_else(|| matches.get_one::<T>(id).cloned())
+}
+
+/// A path option that the environment variable `var` can also set: a value given on the
+/// command line wins, then the environment, then the config file's `configured` value, then
+/// the option's built-in default
+pub fn path_setting(matches: &ArgMatches, id: &str, var: &str, configured: Option<String>) -> Option<String> {
+    setting(matches, id, env_path(var).or(configured))
+}
+
+/// Whether the path option of `path_setting` was set on the command line, in the environment
+/// or in the config file, rather than left to its built-in default
+pub fn path_chosen(matches: &ArgMatches, id: &str, var: &str, configured: Option<&String>) -> bool {
+    given(matches, id) || env_path(var).is_some() || configured.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, ArgAction, Command};
+
+    fn cli() -> Command {
+        Command::new("test")
+            .arg(Arg::new("file").long("file").default_value("bibles/bible.txt"))
+            .arg(Arg::new("limit").long("limit").value_parser(clap::value_parser!(usize)))
+            .arg(Arg::new("no-color").long("no-color").action(ArgAction::SetTrue))
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse("file = \"kjv.txt\"\nno-color = true\nlimit = 5\n", Path::new("/etc/bible")).unwrap();
+        assert_eq!(config.file.as_deref(), Some("/etc/bible/kjv.txt"));
+        assert_eq!(config.no_color, Some(true));
+        assert_eq!(config.limit, Some(5));
+        assert_eq!(config.translation, None);
+        assert_eq!(Config::parse("color = \"always\"\n", Path::new("")).unwrap().color, Some(ColorChoice::Always));
+        assert!(Config::parse("color = \"sometimes\"\n", Path::new("")).is_err());
+
+        // Typos are reported rather than silently ignored
+        assert!(Config::parse("colour = false\n", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_flags_override_config() {
+        let config = Config::parse("file = \"kjv.txt\"\nlimit = 5\nno-color = true\n", Path::new("")).unwrap();
+
+        let defaults = cli().get_matches_from(["test"]);
+        assert_eq!(setting(&defaults, "file", config.file.clone()).as_deref(), Some("kjv.txt"));
+        assert_eq!(setting(&defaults, "limit", config.limit), Some(5));
+        assert_eq!(setting(&defaults, "no-color", config.no_color), Some(true));
+        assert_eq!(setting::<String>(&defaults, "file", None).as_deref(), Some("bibles/bible.txt"));
+
+        let flags = cli().get_matches_from(["test", "--file", "asv.txt", "--limit", "2"]);
+        assert_eq!(setting(&flags, "file", config.file.clone()).as_deref(), Some("asv.txt"));
+        assert_eq!(setting(&flags, "limit", config.limit), Some(2));
+    }
+
+    #[test]
+    fn test_environment_precedence() {
+        // A variable of its own, so tests running alongside don't see it
+        const VAR: &str = "BIBLE_SEARCH_TEST_FILE";
+        let config = Config::parse("file = \"kjv.txt\"\n", Path::new("")).unwrap();
+        let flags = cli().get_matches_from(["test", "--file", "flag.txt"]);
+        let defaults = cli().get_matches_from(["test"]);
+
+        // Flag over environment over config over built-in default
+        env::set_var(VAR, "env.txt");
+        assert_eq!(path_setting(&flags, "file", VAR, config.file.clone()).as_deref(), Some("flag.txt"));
+        assert_eq!(path_setting(&defaults, "file", VAR, config.file.clone()).as_deref(), Some("env.txt"));
+        assert!(path_chosen(&defaults, "file", VAR, None));
+        // An empty variable counts as unset
+        env::set_var(VAR, "");
+        assert_eq!(path_setting(&defaults, "file", VAR, config.file.clone()).as_deref(), Some("kjv.txt"));
+        env::remove_var(VAR);
+        assert_eq!(path_setting(&defaults, "file", VAR, None).as_deref(), Some("bibles/bible.txt"));
+        assert!(path_chosen(&defaults, "file", VAR, config.file.as_ref()));
+        assert!(!path_chosen(&defaults, "file", VAR, None));
+        assert!(path_chosen(&flags, "file", VAR, None));
+    }
+}
@@ -0,0 +1,164 @@
+// error.rs
+// Failures reported by the command line, as colored prose or as JSON (--error-format json)
+
+use std::fmt;
+use colored::*;
+use serde_json::json;
+use crate::bible::Verse;
+
+/// How errors are written to stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ErrorFormat {
+    /// Colored messages for people
+    #[default]
+    Human,
+    /// One `{"error": ..., "message": ..., "exit_code": ...}` object per error, for tools
+    Json,
+}
+
+/// A failure worth a non-zero exit code
+#[derive(Debug, Clone)]
+pub enum BibleError {
+    /// A Bible, registry or other input file doesn't exist
+    FileNotFound { file: String },
+    /// A file exists but couldn't be read or parsed
+    LoadFailed { file: String, message: String },
+    /// An output file couldn't be written
+    WriteFailed { file: String, message: String },
+    /// A translation code that isn't registered, or whose file is missing
+    UnknownTranslation(String),
+    /// Input that isn't a reference, with the forms that would have been accepted
+    InvalidReference { reason: String, expected: &'static str },
+    /// A well-formed reference that isn't in the loaded Bible, with the verses either side of it
+    NotFound { message: String, nearest: Vec<Verse> },
+    /// A search that matched nothing
+    NoResults { message: String, hint: Option<String> },
+}
+
+impl BibleError {
+    /// Error for a failed load: missing files are reported as such
+    pub fn load(file: &str, error: &std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            BibleError::FileNotFound { file: file.to_string() }
+        } else {
+            BibleError::LoadFailed { file: file.to_string(), message: error.to_string() }
+        }
+    }
+
+    /// Name of the error in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BibleError::FileNotFound { .. } => "FileNotFound",
+            BibleError::LoadFailed { .. } => "LoadFailed",
+            BibleError::WriteFailed { .. } => "WriteFailed",
+            BibleError::UnknownTranslation(_) => "UnknownTranslation",
+            BibleError::InvalidReference { .. } => "InvalidReference",
+            BibleError::NotFound { .. } => "NotFound",
+            BibleError::NoResults { .. } => "NoResults",
+        }
+    }
+
+    /// Process exit code: 2 for input that couldn't be understood, 1 for everything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            BibleError::InvalidReference { .. } | BibleError::UnknownTranslation(_) => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = json!({
+            "error": self.kind(),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+        });
+        if let BibleError::NotFound { nearest, .. } = self {
+            if !nearest.is_empty() {
+                let references: Vec<String> = nearest.iter().map(|v| v.reference()).collect();
+                value["nearest"] = json!(references);
+            }
+        }
+        value
+    }
+
+    /// Write the error to stderr. Suggested nearest verses go to stdout in human format.
+    pub fn report(&self, format: ErrorFormat) {
+        if format == ErrorFormat::Json {
+            eprintln!("{}", self.to_json());
+            return;
+        }
+
+        match self {
+            BibleError::FileNotFound { .. }
+            | BibleError::LoadFailed { .. }
+            | BibleError::WriteFailed { .. }
+            | BibleError::UnknownTranslation(_) => {
+                eprintln!("🔥 {}", self);
+            }
+            BibleError::InvalidReference { reason, expected } => {
+                eprintln!("{}", format!("Invalid reference: {}.", reason).red());
+                eprintln!("Please use {}.", expected);
+            }
+            BibleError::NotFound { message, nearest } => {
+                eprintln!("{}", message.red());
+                if !nearest.is_empty() {
+                    println!("Nearest verses in this translation:");
+                    for verse in nearest {
+                        println!("{}", verse);
+                    }
+                }
+            }
+            BibleError::NoResults { message, hint } => {
+                eprintln!("{}", message.red());
+                if let Some(hint) = hint {
+                    eprintln!("{}", hint);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for BibleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BibleError::FileNotFound { file } => write!(f, "File not found: {}", file),
+            BibleError::LoadFailed { file, message } => write!(f, "Error loading {}: {}", file, message),
+            BibleError::WriteFailed { file, message } => write!(f, "Error writing {}: {}", file, message),
+            BibleError::UnknownTranslation(message) => write!(f, "{}", message),
+            BibleError::InvalidReference { reason, expected } => write!(f, "Invalid reference: {}. Please use {}.", reason, expected),
+            BibleError::NotFound { message, .. } => write!(f, "{}", message),
+            BibleError::NoResults { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for BibleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_reference_json() {
+        let error = BibleError::InvalidReference { reason: "unexpected 'extra' after 'John 3:16'".to_string(), expected: "'Book Chapter:Verse'" };
+
+        let parsed: serde_json::Value = serde_json::from_str(&error.to_json().to_string()).unwrap();
+
+        assert_eq!(parsed["error"], "InvalidReference");
+        assert_eq!(parsed["message"], "Invalid reference: unexpected 'extra' after 'John 3:16'. Please use 'Book Chapter:Verse'.");
+        assert_ne!(parsed["exit_code"], 0);
+        assert!(parsed.get("nearest").is_none());
+    }
+
+    #[test]
+    fn test_not_found_lists_nearest_verses() {
+        let nearest = vec![Verse::new("Mark", 16, 8, "And they went out quickly.")];
+        let error = BibleError::NotFound { message: "Verse not found.".to_string(), nearest };
+
+        assert_eq!(error.to_json()["nearest"], json!(["Mark 16:8"]));
+        assert_eq!(error.exit_code(), 1);
+
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        assert_eq!(BibleError::load("web.txt", &missing).kind(), "FileNotFound");
+    }
+}
@@ -0,0 +1,214 @@
+// export.rs
+// Writing a selection of verses out as a new Bible file (--extract), in text or JSON format,
+// and a book's cross-references as a JSON dataset (--xref-book)
+
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use serde::ser::Serializer;
+use serde::Serialize;
+use crate::bible::{clean_line, parse_verse_line, Bible, NoteBrackets, Verse};
+use crate::error::BibleError;
+use crate::reference::{invalid_reference_reason, parse_reference};
+use crate::similarity::{book_cross_references, CrossReference, XrefCorpus, XrefOptions};
+
+/// The header of a text Bible: its first two lines when they aren't verses (the translation's
+/// code and name). Empty for JSON files, files without a header and files that can't be read.
+pub fn text_header(filename: &str) -> Vec<String> {
+    let Ok(file) = File::open(filename) else {
+        return Vec::new();
+    };
+    let mut header = Vec::new();
+    for (index, line) in io::BufReader::new(file).lines().take(2).enumerate() {
+        let Ok(line) = line else { break };
+        let line = clean_line(&line, index);
+        if line.trim_start().starts_with('{') || parse_verse_line(line).is_some() {
+            break;
+        }
+        header.push(line.to_string());
+    }
+    header
+}
+
+/// Write verses in the text format the loader reads: the header lines, then one
+/// `Book Chapter:Verse<TAB>Text` line per verse, notes back in `brackets`
+pub fn write_text_bible(mut writer: impl Write, header: &[String], verses: &[&Verse], brackets: NoteBrackets) -> io::Result<()> {
+    for line in header {
+        writeln!(writer, "{}", line)?;
+    }
+    for verse in verses {
+        // A verse is one line, so line breaks inside it (as in some JSON Bibles) become spaces.
+        // Notes taken out of the text go back in, shown or not.
+        let line = format!("{} {}:{}\t{}", verse.book, verse.chapter, verse.verse, verse.text_with_notes(brackets));
+        writeln!(writer, "{}", line.replace(['\r', '\n'], " "))?;
+    }
+    writer.flush()
+}
+
+/// Write verses in the BibleTranslations JSON format (`{"Book": {"1": {"1": "text"}}}`),
+/// books, chapters and verses in the order given, notes back in `brackets`
+pub fn write_json_bible(mut writer: impl Write, verses: &[&Verse], brackets: NoteBrackets) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut writer, &JsonBooks(verses, brackets))?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+// Serialized as maps built from runs of consecutive verses, so the order is kept
+struct JsonBooks<'a>(&'a [&'a Verse], NoteBrackets);
+struct JsonChapters<'a>(&'a [&'a Verse], NoteBrackets);
+struct JsonVerses<'a>(&'a [&'a Verse], NoteBrackets);
+
+impl Serialize for JsonBooks<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.chunk_by(|a, b| a.book == b.book).map(|book| (&book[0].book, JsonChapters(book, self.1))))
+    }
+}
+
+impl Serialize for JsonChapters<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.chunk_by(|a, b| a.chapter == b.chapter).map(|chapter| (chapter[0].chapter.to_string(), JsonVerses(chapter, self.1))))
+    }
+}
+
+impl Serialize for JsonVerses<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|verse| (verse.verse.to_string(), verse.text_with_notes(self.1))))
+    }
+}
+
+/// Write the verses of `reference` (a book, chapter, chapter range, verse or verse range) to
+/// `output`: JSON when it ends in `.json`, otherwise text keeping `source`'s header lines.
+/// Returns the number of verses written.
+pub fn extract_to_file(bible: &Bible, source: &str, reference: &str, output: &str) -> Result<usize, BibleError> {
+    let parsed = parse_reference(reference).ok_or_else(|| BibleError::InvalidReference {
+        reason: invalid_reference_reason(reference),
+        expected: "'Book', 'Book Chapter', 'Book Chapter-Chapter', 'Book Chapter:Verse' or 'Book Chapter:Verse-Verse'",
+    })?;
+    let verses: Vec<&Verse> = bible.passage(&parsed).iter().collect();
+    if verses.is_empty() {
+        return Err(BibleError::NotFound { message: format!("{} not found.", parsed), nearest: Vec::new() });
+    }
+
+    let write_failed = |e: io::Error| BibleError::WriteFailed { file: output.to_string(), message: e.to_string() };
+    let writer = BufWriter::new(File::create(output).map_err(write_failed)?);
+    if output.ends_with(".json") {
+        write_json_bible(writer, &verses, bible.note_display().brackets).map_err(write_failed)?;
+    } else {
+        write_text_bible(writer, &text_header(source), &verses, bible.note_display().brackets).map_err(write_failed)?;
+    }
+    Ok(verses.len())
+}
+
+#[derive(Serialize)]
+struct XrefDataset<'a> {
+    book: &'a str,
+    metric: String,
+    verses: Vec<XrefEntry>,
+}
+
+#[derive(Serialize)]
+struct XrefEntry {
+    reference: String,
+    cross_references: Vec<XrefLink>,
+}
+
+#[derive(Serialize)]
+struct XrefLink {
+    reference: String,
+    score: f32,
+}
+
+/// Write a book's cross-references as one JSON document: `{"book", "metric", "verses": [{
+/// "reference", "cross_references": [{"reference", "score"}]}]}`, verses in Bible order
+pub fn write_xref_dataset(mut writer: impl Write, book: &str, metric: String, results: &[(&Verse, Vec<CrossReference>)]) -> io::Result<()> {
+    let verses = results
+        .iter()
+        .map(|(verse, xrefs)| XrefEntry {
+            reference: verse.reference(),
+            cross_references: xrefs.iter().map(|xref| XrefLink { reference: xref.verse.reference(), score: xref.score }).collect(),
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut writer, &XrefDataset { book, metric, verses })?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+/// Compute the cross-references of every verse in `book` (--xref-book) and write them to
+/// `output` as JSON. Returns the number of verses written; `progress` is as for
+/// `book_cross_references`.
+pub fn xref_book_to_file(corpus: &XrefCorpus, book: &str, options: &XrefOptions, output: &str, progress: impl FnMut(usize, usize)) -> Result<usize, BibleError> {
+    let results = book_cross_references(corpus, book, options, progress);
+    if results.is_empty() {
+        return Err(BibleError::NotFound { message: format!("{} not found.", book), nearest: Vec::new() });
+    }
+
+    let write_failed = |e: io::Error| BibleError::WriteFailed { file: output.to_string(), message: e.to_string() };
+    let writer = BufWriter::new(File::create(output).map_err(write_failed)?);
+    write_xref_dataset(writer, &results[0].0.book, corpus.description(), &results).map_err(write_failed)?;
+    Ok(results.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_parser::load_bible_auto;
+    use crate::synonyms::SynonymMapper;
+
+    #[test]
+    fn test_extract_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("kjv.txt");
+        let verses = vec![
+            Verse::new("Matthew", 4, 25, "And there followed him great multitudes of people."),
+            Verse::new("Matthew", 5, 1, "And seeing the multitudes, he went up into a mountain:"),
+            Verse::new("Matthew", 6, 9, "After this manner therefore pray ye: Our Father which art in heaven,"),
+            Verse::new("Matthew", 7, 29, "For he taught them as one having authority, and not as the scribes."),
+            Verse::new("Matthew", 8, 1, "When he was come down from the mountain, great multitudes followed him."),
+        ];
+        let mut content = String::from("KJV\nKing James Version\n");
+        for v in &verses {
+            content.push_str(&format!("{}\n", v.plain_line()));
+        }
+        std::fs::write(&source, content).unwrap();
+        let source = source.to_str().unwrap();
+        let bible = Bible::new(load_bible_auto(source).unwrap());
+
+        for name in ["sermon.txt", "sermon.json"] {
+            let output = dir.path().join(name);
+            let output = output.to_str().unwrap();
+
+            assert_eq!(extract_to_file(&bible, source, "Matthew 5-7", output).unwrap(), 3);
+
+            let extracted: Vec<String> = load_bible_auto(output).unwrap().iter().map(|v| v.plain_line()).collect();
+            let expected: Vec<String> = verses[1..4].iter().map(|v| v.plain_line()).collect();
+            assert_eq!(extracted, expected, "{}", name);
+        }
+        assert_eq!(text_header(dir.path().join("sermon.txt").to_str().unwrap()), vec!["KJV", "King James Version"]);
+
+        assert!(matches!(extract_to_file(&bible, source, "Matthew 9", "unused.txt"), Err(BibleError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_xref_dataset() {
+        let verses = vec![
+            Verse::new("John", 10, 11, "I am the good shepherd: the good shepherd giveth his life for the sheep."),
+            Verse::new("John", 10, 14, "I am the good shepherd, and know my sheep, and am known of mine."),
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+        ];
+        let mapper = SynonymMapper::new();
+        let options = XrefOptions { similarity: "0.1".to_string(), ..Default::default() };
+        let corpus = XrefCorpus::new(&verses, &mapper, &options);
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("john_xrefs.json");
+        let output = output.to_str().unwrap();
+
+        assert_eq!(xref_book_to_file(&corpus, "jn", &options, output, |_, _| {}).unwrap(), 2);
+        let dataset: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+
+        assert_eq!(dataset["book"], "John");
+        assert_eq!(dataset["metric"], "similarity >= 10.0%");
+        assert_eq!(dataset["verses"][0]["reference"], "John 10:11");
+        assert_eq!(dataset["verses"][0]["cross_references"][0]["reference"], "John 10:14");
+        assert_eq!(dataset["verses"].as_array().unwrap().len(), 2);
+        assert!(matches!(xref_book_to_file(&corpus, "Mark", &options, output, |_, _| {}), Err(BibleError::NotFound { .. })));
+    }
+}
@@ -0,0 +1,204 @@
+// filter.rs
+// Restricting which verses a command considers (--book, --book-glob, --testament, --ref-pattern
+// and exclusions)
+
+use regex::{Regex, RegexBuilder};
+use crate::bible::Verse;
+use crate::books::{book_matches, resolve_book, testament_of, Testament};
+
+/// Candidate-verse restrictions shared by search, navigation and random selection
+#[derive(Debug, Clone, Default)]
+pub struct VerseFilter {
+    pub book: Option<String>,
+    /// Only verses whose canonical book name matches (see `book_pattern`)
+    pub book_pattern: Option<Regex>,
+    pub testament: Option<Testament>,
+    /// Books (or book groups) removed after the inclusions above are applied
+    pub exclude_books: Vec<String>,
+    pub exclude_testament: Option<Testament>,
+    /// Only verses whose "Book Chapter:Verse" reference matches (see `reference_pattern`)
+    pub reference_pattern: Option<Regex>,
+}
+
+/// Compile a `--ref-pattern`, matched case-insensitively against "Book Chapter:Verse".
+/// A pattern starting with `^` is a regular expression (`^1 ` is every verse of the numbered
+/// books); anything else is a glob over the whole reference, where `*` matches any run of
+/// characters and `?` a single one (`Psalm*`, `* 1:*` for every chapter 1).
+pub fn reference_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    let expression = if pattern.starts_with('^') {
+        pattern.to_string()
+    } else {
+        glob_expression(pattern)
+    };
+    RegexBuilder::new(&expression).case_insensitive(true).build()
+}
+
+/// Compile a `--book` given with `--book-glob`: a glob over canonical book names, `*` for any
+/// run of characters and `?` for one, ignoring case. `1*` is the numbered-one books (1 Samuel,
+/// 1 Kings, 1 John, ...) and `*John` is John and the three epistles.
+pub fn book_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&glob_expression(pattern.trim())).case_insensitive(true).build()
+}
+
+// A glob as a regular expression over the whole text
+fn glob_expression(pattern: &str) -> String {
+    let glob: String = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect();
+    format!("^{}$", glob)
+}
+
+impl VerseFilter {
+    /// Does a verse pass every restriction?
+    pub fn matches(&self, verse: &Verse) -> bool {
+        if let Some(book) = &self.book {
+            if !book_matches(book, &verse.book) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.book_pattern {
+            // "Psalm" in a Bible is matched as the canonical "Psalms"
+            if !pattern.is_match(resolve_book(&verse.book).unwrap_or(&verse.book)) {
+                return false;
+            }
+        }
+        if let Some(testament) = self.testament {
+            if testament_of(&verse.book) != Some(testament) {
+                return false;
+            }
+        }
+        if self.exclude_books.iter().any(|book| book_matches(book, &verse.book)) {
+            return false;
+        }
+        if self.exclude_testament.is_some() && testament_of(&verse.book) == self.exclude_testament {
+            return false;
+        }
+        if let Some(pattern) = &self.reference_pattern {
+            if !pattern.is_match(&verse.reference()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Human-readable description of the restrictions, for messages
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(book) = &self.book {
+            parts.push(format!("book '{}'", book));
+        }
+        if let Some(pattern) = &self.book_pattern {
+            parts.push(format!("books matching '{}'", pattern));
+        }
+        match self.testament {
+            Some(Testament::Old) => parts.push("the Old Testament".to_string()),
+            Some(Testament::New) => parts.push("the New Testament".to_string()),
+            None => {}
+        }
+        if let Some(pattern) = &self.reference_pattern {
+            parts.insert(0, format!("references matching '{}'", pattern));
+        }
+        let mut description = if parts.is_empty() {
+            "the Bible".to_string()
+        } else {
+            parts.join(" in ")
+        };
+
+        let mut excluded: Vec<String> = self.exclude_books.iter().map(|b| format!("'{}'", b)).collect();
+        match self.exclude_testament {
+            Some(Testament::Old) => excluded.push("the Old Testament".to_string()),
+            Some(Testament::New) => excluded.push("the New Testament".to_string()),
+            None => {}
+        }
+        if !excluded.is_empty() {
+            description.push_str(&format!(" excluding {}", excluded.join(", ")));
+        }
+        description
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verse(book: &str) -> Verse {
+        Verse::new(book, 1, 1, "")
+    }
+
+    #[test]
+    fn test_book_and_testament() {
+        let nt = VerseFilter { testament: Some(Testament::New), ..Default::default() };
+        assert!(nt.matches(&verse("Matthew")));
+        assert!(!nt.matches(&verse("Genesis")));
+
+        let john_in_ot = VerseFilter { book: Some("John".to_string()), testament: Some(Testament::Old), ..Default::default() };
+        assert!(!john_in_ot.matches(&verse("John")));
+
+        assert!(VerseFilter::default().matches(&verse("Genesis")));
+    }
+
+    #[test]
+    fn test_exclusions_apply_after_inclusions() {
+        let gospels_but_john = VerseFilter {
+            book: Some("Gospels".to_string()),
+            exclude_books: vec!["Jn".to_string()],
+            ..Default::default()
+        };
+        assert!(gospels_but_john.matches(&verse("Luke")));
+        assert!(!gospels_but_john.matches(&verse("John")));
+        assert!(!gospels_but_john.matches(&verse("Acts")));
+        assert_eq!(gospels_but_john.describe(), "book 'Gospels' excluding 'Jn'");
+
+        let no_psalms_or_nt = VerseFilter {
+            exclude_books: vec!["Psalm".to_string()],
+            exclude_testament: Some(Testament::New),
+            ..Default::default()
+        };
+        assert!(no_psalms_or_nt.matches(&verse("Genesis")));
+        assert!(!no_psalms_or_nt.matches(&verse("Psalms")));
+        assert!(!no_psalms_or_nt.matches(&verse("Romans")));
+    }
+
+    #[test]
+    fn test_reference_pattern() {
+        let verse = |book: &str, chapter: u32, number: u32| Verse::new(book, chapter, number, "");
+        let filter = |pattern: &str| VerseFilter { reference_pattern: Some(reference_pattern(pattern).unwrap()), ..Default::default() };
+
+        let psalms = filter("psalm*");
+        assert!(psalms.matches(&verse("Psalms", 23, 1)));
+        assert!(!psalms.matches(&verse("Proverbs", 1, 1)));
+
+        let first_chapters = filter("* 1:*");
+        assert!(first_chapters.matches(&verse("Genesis", 1, 31)));
+        assert!(first_chapters.matches(&verse("1 John", 1, 9)));
+        assert!(!first_chapters.matches(&verse("Genesis", 11, 1)));
+
+        let numbered = filter("^1 ");
+        assert!(numbered.matches(&verse("1 John", 4, 8)));
+        assert!(!numbered.matches(&verse("John", 1, 1)));
+
+        // Glob characters are literal apart from * and ?
+        assert!(filter("John 3:1?").matches(&verse("John", 3, 16)));
+        assert!(!filter("John 3.1?").matches(&verse("John", 3, 16)));
+        assert!(reference_pattern("^(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_book_pattern() {
+        let filter = |pattern: &str| VerseFilter { book_pattern: Some(book_pattern(pattern).unwrap()), ..Default::default() };
+        let books = ["Genesis", "1 Samuel", "2 Samuel", "1 Kings", "Psalm", "John", "1 John", "2 John", "3 John", "Jude"];
+        let matching = |pattern: &str| -> Vec<&str> { books.into_iter().filter(|book| filter(pattern).matches(&verse(book))).collect() };
+
+        assert_eq!(matching("1*"), ["1 Samuel", "1 Kings", "1 John"]);
+        assert_eq!(matching("*John"), ["John", "1 John", "2 John", "3 John"]);
+        assert_eq!(matching("? samuel"), ["1 Samuel", "2 Samuel"]);
+        // Books are matched by their canonical names, whatever the Bible calls them
+        assert_eq!(matching("psalms"), ["Psalm"]);
+        assert!(matching("Ju?e").contains(&"Jude"));
+    }
+}
@@ -0,0 +1,263 @@
+// highlight.rs
+// Token-aware highlighting of search terms inside verse text
+
+use regex::{Regex, RegexBuilder};
+use colored::*;
+
+// Is this a character that belongs to a word token?
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+// Expand a match to the boundaries of the word token(s) it touches
+fn token_bounds(text: &str, start: usize, end: usize) -> (usize, usize) {
+    let token_start = text[..start]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(start);
+    let token_end = text[end..]
+        .char_indices()
+        .find(|(_, c)| !is_word_char(*c))
+        .map(|(i, _)| end + i)
+        .unwrap_or(text.len());
+    (token_start, token_end)
+}
+
+// A phrase term's words may be separated by any whitespace in the verse
+fn term_regex(term: &str, case_sensitive: bool) -> Option<Regex> {
+    let words: Vec<String> = term.split_whitespace().map(regex::escape).collect();
+    RegexBuilder::new(&words.join(r"\s+"))
+        .case_insensitive(!case_sensitive)
+        .build()
+        .ok()
+}
+
+/// Find the byte spans of `text` that should be highlighted for the given search terms.
+///
+/// A match that covers a whole word is always highlighted. A match buried inside a larger
+/// word (`god` in "ungodly") is skipped, unless `highlight_words` is set, in which case the
+/// whole surrounding word is highlighted instead. Spans are sorted and never overlap: where
+/// terms overlap, their spans are merged.
+pub fn find_match_spans(text: &str, terms: &[String], case_sensitive: bool, highlight_words: bool) -> Vec<(usize, usize)> {
+    let terms: Vec<(String, bool)> = terms.iter().map(|term| (term.clone(), case_sensitive)).collect();
+    find_case_match_spans(text, &terms, highlight_words)
+}
+
+/// Like `find_match_spans`, with each term saying whether it matches case sensitively
+pub fn find_case_match_spans(text: &str, terms: &[(String, bool)], highlight_words: bool) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+
+    for (term, case_sensitive) in terms.iter().filter(|(t, _)| !t.is_empty()) {
+        let re = match term_regex(term, *case_sensitive) {
+            Some(re) => re,
+            None => continue,
+        };
+        for m in re.find_iter(text) {
+            let (token_start, token_end) = token_bounds(text, m.start(), m.end());
+            if token_start == m.start() && token_end == m.end() {
+                spans.push((m.start(), m.end()));
+            } else if highlight_words {
+                spans.push((token_start, token_end));
+            }
+        }
+    }
+
+    merge_spans(spans)
+}
+
+/// Sort spans and merge those that overlap, so every byte is covered at most once: terms that
+/// overlap ("son of" and "of man" in "Son of man") become one span rather than one hiding the
+/// other. Spans that only touch are kept apart.
+pub fn merge_spans(mut spans: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    spans.sort();
+    let mut result: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in spans {
+        match result.last_mut() {
+            Some(last) if start < last.1 => last.1 = last.1.max(end),
+            _ => result.push((start, end)),
+        }
+    }
+    result
+}
+
+/// Highlight colors for the words of a query (unless --no-term-colors), in the order the words come:
+/// the first is the usual yellow, and a seventh word starts over
+pub const TERM_PALETTE: [Color; 6] = [Color::Yellow, Color::Green, Color::Cyan, Color::Magenta, Color::Blue, Color::Red];
+
+/// The highlight color of the query word at `index`
+pub fn term_color(index: usize) -> Color {
+    TERM_PALETTE[index % TERM_PALETTE.len()]
+}
+
+/// Like `find_case_match_spans`, for query groups (a word and its synonyms): each span also
+/// gives the index of the group it matched, so each word can have its own color. Where spans of
+/// different groups overlap they are merged, taking the earlier span's group.
+pub fn find_group_spans(text: &str, groups: &[Vec<(String, bool)>], highlight_words: bool) -> Vec<(usize, usize, usize)> {
+    let spans: Vec<(usize, usize, usize)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, terms)| find_case_match_spans(text, terms, highlight_words).into_iter().map(move |(start, end)| (start, end, index)))
+        .collect();
+    merge_group_spans(spans)
+}
+
+/// `merge_spans` for spans that carry a group index: merged spans keep the earlier one's group
+pub fn merge_group_spans(mut spans: Vec<(usize, usize, usize)>) -> Vec<(usize, usize, usize)> {
+    spans.sort();
+    let mut result: Vec<(usize, usize, usize)> = Vec::new();
+    for (start, end, index) in spans {
+        match result.last_mut() {
+            Some(last) if start < last.1 => last.1 = last.1.max(end),
+            _ => result.push((start, end, index)),
+        }
+    }
+    result
+}
+
+/// Wrap each span of `text` in the highlight color, once: overlapping spans are merged first
+pub fn apply_highlights(text: &str, spans: &[(usize, usize)]) -> String {
+    let spans: Vec<(usize, usize, usize)> = merge_spans(spans.to_vec()).into_iter().map(|(start, end)| (start, end, 0)).collect();
+    apply_term_highlights(text, &spans)
+}
+
+/// Wrap each span of `text` in the color of its query word (see `term_color`); the spans must
+/// be sorted and must not overlap, as `find_group_spans` gives them
+pub fn apply_term_highlights(text: &str, spans: &[(usize, usize, usize)]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut position = 0;
+    for &(start, end, index) in spans {
+        output.push_str(&text[position..start]);
+        output.push_str(&text[start..end].black().on_color(term_color(index)).to_string());
+        position = end;
+    }
+    output.push_str(&text[position..]);
+    output
+}
+
+/// A legend for the term colors: each query word in its highlight color
+pub fn format_legend(words: &[String]) -> String {
+    let words: Vec<String> = words.iter().enumerate().map(|(index, word)| word.black().on_color(term_color(index)).to_string()).collect();
+    format!("Colors: {}", words.join("  "))
+}
+
+/// Run `f` with colors forced on, as on a terminal, one caller at a time since the setting is
+/// global (for tests of highlighted text)
+#[cfg(test)]
+pub(crate) fn with_colors<T>(f: impl FnOnce() -> T) -> T {
+    lazy_static::lazy_static! {
+        static ref COLORS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    }
+    let _guard = COLORS.lock().unwrap_or_else(|e| e.into_inner());
+    colored::control::set_override(true);
+    let result = f();
+    colored::control::unset_override();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_whole_word_match_is_highlighted() {
+        let text = "For God so loved the world";
+        assert_eq!(find_match_spans(text, &terms(&["god"]), false, false), vec![(4, 7)]);
+    }
+
+    #[test]
+    fn test_substring_in_token_is_skipped_by_default() {
+        let text = "The way of the ungodly shall perish";
+        assert!(find_match_spans(text, &terms(&["god"]), false, false).is_empty());
+    }
+
+    #[test]
+    fn test_substring_in_token_highlights_whole_word() {
+        let text = "The way of the ungodly shall perish";
+        assert_eq!(find_match_spans(text, &terms(&["god"]), false, true), vec![(15, 22)]);
+    }
+
+    #[test]
+    fn test_mixed_whole_and_partial_matches() {
+        let text = "Godliness is of God";
+        // Only the standalone "God" is highlighted
+        assert_eq!(find_match_spans(text, &terms(&["god"]), false, false), vec![(16, 19)]);
+        // With whole-word highlighting both tokens are
+        assert_eq!(find_match_spans(text, &terms(&["god"]), false, true), vec![(0, 9), (16, 19)]);
+    }
+
+    #[test]
+    fn test_case_sensitive_spans() {
+        let text = "God is god";
+        assert_eq!(find_match_spans(text, &terms(&["God"]), true, false), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_punctuation_is_a_boundary() {
+        let text = "love, loved; beloved.";
+        assert_eq!(find_match_spans(text, &terms(&["love"]), false, false), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_overlapping_terms_are_merged() {
+        let text = "the Son of man is come to seek";
+        // Neither phrase hides the other; "of" is highlighted once
+        assert_eq!(find_match_spans(text, &terms(&["son of", "of man"]), false, false), vec![(4, 14)]);
+
+        // A term inside another's match, whole-word highlighting on
+        let text = "my beloved son, with reason";
+        let spans = find_match_spans(text, &terms(&["love", "beloved", "son", "reason"]), false, true);
+        assert_eq!(spans, vec![(3, 10), (11, 14), (21, 27)]);
+        assert!(spans.windows(2).all(|w| w[0].1 <= w[1].0), "each character is highlighted at most once");
+
+        assert_eq!(merge_spans(vec![(5, 9), (0, 3), (2, 6), (9, 12)]), vec![(0, 9), (9, 12)]);
+        // Overlapping spans are wrapped once, never nested
+        let (highlighted, whole) = with_colors(|| (apply_highlights("abcdef", &[(0, 4), (2, 6)]), "abcdef".black().on_yellow().to_string()));
+        assert_eq!(highlighted, whole);
+        assert_eq!(highlighted.matches("\x1b[0m").count(), 1);
+        assert_eq!(highlighted.matches("cd").count(), 1);
+    }
+
+    #[test]
+    fn test_term_colors_follow_the_query() {
+        let groups = |words: &[&[&str]]| -> Vec<Vec<(String, bool)>> {
+            words.iter().map(|terms| terms.iter().map(|t| (t.to_string(), false)).collect()).collect()
+        };
+        let love_mercy = groups(&[&["love", "charity"], &["mercy"]]);
+
+        // Every match of a word, synonyms included, takes that word's color, in every verse
+        assert_eq!(find_group_spans("mercy and love, and charity", &love_mercy, false), vec![(0, 5, 1), (10, 14, 0), (20, 27, 0)]);
+        assert_eq!(find_group_spans("Love mercy", &love_mercy, false), vec![(0, 4, 0), (5, 10, 1)]);
+        // Overlapping words are merged into the earlier span's color
+        assert_eq!(find_group_spans("son of man", &groups(&[&["of man"], &["son of"]]), false), vec![(0, 10, 1)]);
+        assert_eq!((term_color(0), term_color(1), term_color(TERM_PALETTE.len())), (Color::Yellow, Color::Green, Color::Yellow));
+        // The usual single-color highlighting is the first word's color
+        assert_eq!(apply_highlights("love mercy", &[(0, 4)]), apply_term_highlights("love mercy", &[(0, 4, 0)]));
+
+        let legend = format_legend(&terms(&["love", "mercy"]));
+        assert!(legend.starts_with("Colors: "));
+        assert!(legend.find("love").unwrap() < legend.find("mercy").unwrap());
+    }
+
+    #[test]
+    fn test_legend_colors_match_the_verse() {
+        let groups: Vec<Vec<(String, bool)>> = vec![vec![("love".to_string(), false), ("charity".to_string(), false)], vec![("mercy".to_string(), false)]];
+        let text = "mercy and love, and charity";
+        let (legend, verse, love, mercy) = with_colors(|| {
+            let colored = |word: &str, index| word.black().on_color(term_color(index)).to_string();
+            (format_legend(&terms(&["love", "mercy"])), apply_term_highlights(text, &find_group_spans(text, &groups, false)), colored("love", 0), colored("mercy", 1))
+        });
+
+        // Each word is drawn the same in the legend and the verse, its synonyms in its color
+        assert_eq!(legend, format!("Colors: {}  {}", love, mercy));
+        assert!(verse.starts_with(&mercy) && verse.contains(&love), "{:?}", verse);
+        assert!(verse.ends_with(&love.replace("love", "charity")));
+        assert_ne!(love.replace("love", ""), mercy.replace("mercy", ""));
+    }
+}
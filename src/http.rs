@@ -0,0 +1,171 @@
+// http.rs
+// An HTTP JSON API (--http, with the `server` feature): the requests of --serve as GET URLs,
+// for web frontends
+
+use std::net::SocketAddr;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+use crate::bible::Bible;
+use crate::error::BibleError;
+use crate::random::Rng;
+use crate::serve::{handle_request, Request};
+use crate::synonyms::SynonymMapper;
+
+// Undo URL encoding: `%20` and `+` are spaces in "1%20John" and "q=living+water"
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// An error response body, shaped like `BibleError::to_json`
+fn error_body(kind: &str, message: String) -> Value {
+    json!({ "error": kind, "message": message, "exit_code": 2 })
+}
+
+/// The request a URL asks for:
+///
+/// * `/search?q=living+water&limit=10&synonyms=true`
+/// * `/verse/John/3/16`, `/verse/John/3` or `/verse/John`, and `/verse/John/3/16-18`
+/// * `/random?book=Psalms&seed=7`
+/// * `/xref/John/3/16?similarity=2-gram&limit=5`
+///
+/// A path the API doesn't have is a 404 and bad parameters a 400, each with a JSON body.
+pub fn parse_url(url: &str) -> Result<Request, (u16, Value)> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+    let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, value)| value.clone());
+    let number = |key: &str| -> Result<Option<u64>, (u16, Value)> {
+        match param(key) {
+            Some(value) => match value.parse() {
+                Ok(n) => Ok(Some(n)),
+                Err(_) => Err((400, error_body("InvalidRequest", format!("'{}' is not a valid {}", value, key)))),
+            },
+            None => Ok(None),
+        }
+    };
+    let segments: Vec<String> = path.split('/').filter(|segment| !segment.is_empty()).map(decode).collect();
+    // "John/3/16" → "John 3:16"; a chapter or a book alone works too
+    let reference = |parts: &[String]| match parts {
+        [book, chapter, verse] => Some(format!("{} {}:{}", book, chapter, verse)),
+        [book, chapter] => Some(format!("{} {}", book, chapter)),
+        [book] => Some(book.clone()),
+        _ => None,
+    };
+
+    let request = match segments.first().map(String::as_str) {
+        Some("search") => match param("q") {
+            Some(query) => Request::Search {
+                query,
+                limit: number("limit")?.map(|n| n as usize),
+                synonyms: param("synonyms").is_some_and(|value| value == "true" || value == "1"),
+            },
+            None => return Err((400, error_body("InvalidRequest", "/search needs a query, e.g. /search?q=faith".to_string()))),
+        },
+        Some("verse") if segments.len() > 1 => match reference(&segments[1..]) {
+            Some(reference) => Request::Reference { reference },
+            None => return Err((404, error_body("NotFound", format!("No such path: {}", path)))),
+        },
+        Some("random") if segments.len() == 1 => Request::Random { book: param("book"), seed: number("seed")? },
+        Some("xref") if segments.len() == 4 => Request::CrossReferences {
+            reference: reference(&segments[1..]).unwrap_or_default(),
+            similarity: param("similarity"),
+            limit: number("limit")?.map(|n| n as usize),
+        },
+        _ => return Err((404, error_body("NotFound", format!("No such path: {}", path)))),
+    };
+    Ok(request)
+}
+
+// The HTTP status for a failed request: 400 for input that couldn't be understood, 404 for
+// what isn't there
+fn status_of(error: &BibleError) -> u16 {
+    match error {
+        BibleError::NotFound { .. } | BibleError::NoResults { .. } => 404,
+        _ if error.exit_code() == 2 => 400,
+        _ => 500,
+    }
+}
+
+/// The status and JSON body answering a GET of `url`
+pub fn respond_to_url(bible: &Bible, synonym_mapper: &SynonymMapper, url: &str, rng: &mut Rng) -> (u16, Value) {
+    match parse_url(url) {
+        Ok(request) => match handle_request(bible, synonym_mapper, &request, rng) {
+            Ok(body) => (200, body),
+            Err(e) => (status_of(&e), e.to_json()),
+        },
+        Err(response) => response,
+    }
+}
+
+/// Serve the API on `address` (e.g. `127.0.0.1:8080`; port 0 picks a free one) until the
+/// process ends, one request at a time. `on_ready` is called with the address bound.
+pub fn serve_http(bible: &Bible, synonym_mapper: &SynonymMapper, address: &str, rng: &mut Rng, on_ready: impl FnOnce(Option<SocketAddr>)) -> Result<(), BibleError> {
+    let server = Server::http(address).map_err(|e| BibleError::LoadFailed { file: address.to_string(), message: e.to_string() })?;
+    on_ready(server.server_addr().to_ip());
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    for request in server.incoming_requests() {
+        let (status, body) = if *request.method() == Method::Get {
+            respond_to_url(bible, synonym_mapper, request.url(), rng)
+        } else {
+            (405, error_body("InvalidRequest", format!("{} is not supported; use GET", request.method())))
+        };
+        let response = Response::from_string(body.to_string()).with_status_code(status).with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("⚠️ Error answering a request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(parse_url("/verse/John/3/16").unwrap(), Request::Reference { reference: "John 3:16".to_string() });
+        assert_eq!(parse_url("/verse/1%20John/4/7-8").unwrap(), Request::Reference { reference: "1 John 4:7-8".to_string() });
+        assert_eq!(parse_url("/verse/Ruth").unwrap(), Request::Reference { reference: "Ruth".to_string() });
+        assert_eq!(
+            parse_url("/search?q=living+water&limit=3&synonyms=true").unwrap(),
+            Request::Search { query: "living water".to_string(), limit: Some(3), synonyms: true }
+        );
+        assert_eq!(parse_url("/random?seed=7").unwrap(), Request::Random { book: None, seed: Some(7) });
+        assert_eq!(
+            parse_url("/xref/John/3/16?similarity=2-gram").unwrap(),
+            Request::CrossReferences { reference: "John 3:16".to_string(), similarity: Some("2-gram".to_string()), limit: None }
+        );
+
+        assert_eq!(parse_url("/search").unwrap_err().0, 400);
+        assert_eq!(parse_url("/random?seed=soon").unwrap_err().0, 400);
+        assert_eq!(parse_url("/xref/John/3").unwrap_err().0, 404);
+        assert_eq!(parse_url("/").unwrap_err().0, 404);
+        assert_eq!(decode("100%"), "100%");
+    }
+}
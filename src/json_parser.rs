@@ -1,28 +1,75 @@
 // json_parser.rs
 // Parser for BibleTranslations JSON format (https://github.com/jadenzaleski/BibleTranslations)
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufReader, Read};
-use serde::{Deserialize, Serialize};
-use crate::bible::Verse;
+use std::io::{self, BufRead, BufReader, Read};
+use std::marker::PhantomData;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+use crate::bible::{load_bible_report_delimited, sort_canonical, Delimiter, TextLoad, Verse};
+use crate::books::book_rank;
 
-// JSON structure for the BibleTranslations format
+/// Synonym groups embedded in a JSON Bible: each key word with its synonyms
+pub type EmbeddedSynonyms = HashMap<String, Vec<String>>;
+
+// JSON structure for the BibleTranslations format, optionally with a top-level `synonyms`
+// object so a translation can ship with its own lexicon
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonBible {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    synonyms: Option<EmbeddedSynonyms>,
     #[serde(flatten)]
-    books: std::collections::HashMap<String, JsonBook>,
+    books: Entries<JsonBook>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonBook {
     #[serde(flatten)]
-    chapters: std::collections::HashMap<String, JsonChapter>,
+    chapters: Entries<JsonChapter>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonChapter {
     #[serde(flatten)]
-    verses: std::collections::HashMap<String, String>,
+    verses: Entries<String>,
+}
+
+// A JSON object's entries in the order the file lists them, which a map would lose
+// (--check-integrity reports verses stored out of order)
+#[derive(Debug)]
+struct Entries<T>(Vec<(String, T)>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Entries<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for EntriesVisitor<T> {
+            type Value = Entries<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor(PhantomData))
+    }
+}
+
+impl<T: Serialize> Serialize for Entries<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+    }
 }
 
 /// Load a Bible from JSON format (BibleTranslations repository format)
@@ -39,9 +86,48 @@ struct JsonChapter {
 /// }
 /// ```
 pub fn load_bible_json(filename: &str) -> io::Result<Vec<Verse>> {
-    let file = File::open(filename)?;
-    let reader = BufReader::new(file);
+    load_bible_json_reader(BufReader::new(File::open(filename)?))
+}
+
+/// Load a JSON Bible like `load_bible_json`, with the synonym groups of its top-level
+/// `synonyms` object when it has one:
+///
+/// ```json
+/// {
+///   "synonyms": { "love": ["charity", "beloved"] },
+///   "Genesis": { "1": { "1": "In the beginning God created the heaven and the earth." } }
+/// }
+/// ```
+pub fn load_bible_json_with_synonyms(filename: &str) -> io::Result<(Vec<Verse>, Option<EmbeddedSynonyms>)> {
+    read_json_bible(BufReader::new(File::open(filename)?), 0, |_| {})
+}
+
+/// Load a JSON Bible like `load_bible_json`, calling `progress` with the number of verses
+/// converted so far after every `every` verses (never when `every` is 0)
+pub fn load_bible_json_with_progress(filename: &str, every: usize, progress: impl FnMut(usize)) -> io::Result<Vec<Verse>> {
+    read_json_bible(BufReader::new(File::open(filename)?), every, progress).map(|(verses, _)| verses)
+}
+
+/// Load a JSON Bible from any reader, in the same format as `load_bible_json`
+pub fn load_bible_json_reader(reader: impl Read) -> io::Result<Vec<Verse>> {
+    read_json_bible(reader, 0, |_| {}).map(|(verses, _)| verses)
+}
+
+fn read_json_bible(reader: impl Read, every: usize, progress: impl FnMut(usize)) -> io::Result<(Vec<Verse>, Option<EmbeddedSynonyms>)> {
+    let (verses, synonyms) = read_json_stored(reader, every, progress)?;
+
+    // A reference repeated in an object keeps its last text, as when the objects were maps
+    let mut seen = HashSet::new();
+    let mut verses: Vec<Verse> = verses.into_iter().rev().filter(|v| seen.insert((v.book.clone(), v.chapter, v.verse))).collect();
+
+    // Sort verses into canonical book, chapter and verse order
+    sort_canonical(&mut verses);
     
+    Ok((verses, synonyms))
+}
+
+// Read a JSON Bible's verses in the order the file lists them
+fn read_json_stored(reader: impl Read, every: usize, mut progress: impl FnMut(usize)) -> io::Result<(Vec<Verse>, Option<EmbeddedSynonyms>)> {
     // Parse JSON
     let json_bible: JsonBible = serde_json::from_reader(reader)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, 
@@ -50,35 +136,31 @@ pub fn load_bible_json(filename: &str) -> io::Result<Vec<Verse>> {
     let mut verses = Vec::new();
     
     // Convert JSON structure to Verse objects
-    for (book_name, book) in json_bible.books {
-        for (chapter_str, chapter) in book.chapters {
+    for (book_name, book) in json_bible.books.0 {
+        for (chapter_str, chapter) in book.chapters.0 {
             let chapter_num: u32 = chapter_str.parse()
                 .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, 
                     format!("Invalid chapter number '{}': {}", chapter_str, e)))?;
             
-            for (verse_str, text) in chapter.verses {
+            for (verse_str, text) in chapter.verses.0 {
                 let verse_num: u32 = verse_str.parse()
                     .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, 
                         format!("Invalid verse number '{}': {}", verse_str, e)))?;
                 
-                verses.push(Verse {
-                    book: book_name.clone(),
-                    chapter: chapter_num,
-                    verse: verse_num,
-                    text: text.trim().to_string(),
-                });
+                verses.push(Verse::new(book_name.clone(), chapter_num, verse_num, text.trim()));
+                if every > 0 && verses.len().is_multiple_of(every) {
+                    progress(verses.len());
+                }
             }
         }
     }
     
-    // Sort verses by book, chapter, and verse for consistent ordering
-    verses.sort_by(|a, b| {
-        a.book.cmp(&b.book)
-            .then(a.chapter.cmp(&b.chapter))
-            .then(a.verse.cmp(&b.verse))
-    });
-    
-    Ok(verses)
+    Ok((verses, json_bible.synonyms))
+}
+
+// A JSON Bible as a load report: its verses and any synonyms it embeds
+fn json_load((verses, synonyms): (Vec<Verse>, Option<EmbeddedSynonyms>)) -> TextLoad {
+    TextLoad { synonyms, ..TextLoad::from(verses) }
 }
 
 /// Detect if a file is in JSON format by checking the first non-whitespace character
@@ -105,6 +187,10 @@ pub fn is_json_format(filename: &str) -> bool {
 
 /// Auto-detect format and load Bible accordingly
 pub fn load_bible_auto(filename: &str) -> io::Result<Vec<Verse>> {
+    if Path::new(filename).is_dir() {
+        return load_bible_dir(filename).map(|load| load.verses);
+    }
+
     // Check file extension first
     if filename.ends_with(".json") {
         return load_bible_json(filename);
@@ -119,10 +205,147 @@ pub fn load_bible_auto(filename: &str) -> io::Result<Vec<Verse>> {
     }
 }
 
+/// Load a Bible file or directory like `load_bible_auto`, but with the verses in the order
+/// they are stored rather than canonical order, for checking that order. A directory's files
+/// follow one another in the order of their first verses.
+pub fn load_bible_stored(filename: &str) -> io::Result<Vec<Verse>> {
+    if Path::new(filename).is_dir() {
+        let (mut files, _) = read_bible_dir(filename, load_bible_stored)?;
+        files.sort_by_key(|verses| verses.first().map(|v| (book_rank(&v.book).unwrap_or(usize::MAX), v.chapter, v.verse)));
+        return Ok(files.concat());
+    }
+    if filename.ends_with(".json") || is_json_format(filename) {
+        read_json_stored(BufReader::new(File::open(filename)?), 0, |_| {}).map(|(verses, _)| verses)
+    } else {
+        crate::bible::load_bible(filename)
+    }
+}
+
+/// Load a Bible from a reader (`--file -` reads stdin), detecting the format from the content:
+/// JSON when the first non-whitespace character is `{`, text otherwise. The whole input is
+/// read before parsing, since the format can't be told from the first bytes alone without
+/// consuming them.
+pub fn load_bible_reader_auto(reader: impl BufRead) -> io::Result<Vec<Verse>> {
+    load_bible_report_reader_auto(reader, Delimiter::default()).map(|load| load.verses)
+}
+
+/// Load a Bible from a reader like `load_bible_reader_auto`, keeping the text loader's report
+/// of malformed lines and duplicate references; text lines are split at `delimiter`. A JSON
+/// Bible can't list a reference twice, so its report is always empty, apart from any synonyms
+/// it embeds.
+pub fn load_bible_report_reader_auto(mut reader: impl BufRead, delimiter: Delimiter) -> io::Result<TextLoad> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+    if content.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+        read_json_bible(content.as_slice(), 0, |_| {}).map(json_load)
+    } else {
+        load_bible_report_delimited(content.as_slice(), delimiter)
+    }
+}
+
+/// Load a Bible file or directory like `load_bible_auto`, with the report of
+/// `load_bible_report_reader_auto` for a text file and the embedded synonyms of a JSON file
+pub fn load_bible_report_auto(filename: &str, delimiter: Delimiter) -> io::Result<TextLoad> {
+    if Path::new(filename).is_dir() {
+        load_bible_dir(filename).map(TextLoad::from)
+    } else if filename.ends_with(".json") || is_json_format(filename) {
+        load_bible_json_with_synonyms(filename).map(json_load)
+    } else {
+        load_bible_report_delimited(BufReader::new(File::open(filename)?), delimiter)
+    }
+}
+
+/// Result of loading every bible file found in a directory
+pub struct DirectoryLoad {
+    pub verses: Vec<Verse>,
+    /// Files that loaded, with the number of verses each contributed
+    pub loaded: Vec<(String, usize)>,
+    /// Files that were skipped, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+// A directory's verses as a load report, with the files loaded and skipped
+impl From<DirectoryLoad> for TextLoad {
+    fn from(load: DirectoryLoad) -> Self {
+        TextLoad { loaded_files: load.loaded, skipped_files: load.skipped, ..TextLoad::from(load.verses) }
+    }
+}
+
+/// Load a Bible split across a directory of files (e.g. one `Genesis.txt` per book).
+///
+/// Every `.txt` and `.json` file is parsed with `load_bible_auto`; other files, and files
+/// that fail to parse or contain no verses, are skipped. Verses are returned in canonical order.
+pub fn load_bible_dir(dir: &str) -> io::Result<DirectoryLoad> {
+    let (files, mut result) = read_bible_dir(dir, load_bible_auto)?;
+    result.verses = files.concat();
+    sort_canonical(&mut result.verses);
+    Ok(result)
+}
+
+// The verses of each file of a directory that `load_bible_dir` reads, loaded with `load`, in
+// file name order, with the report of the files loaded and skipped (and no verses)
+fn read_bible_dir(dir: &str, load: fn(&str) -> io::Result<Vec<Verse>>) -> io::Result<(Vec<Vec<Verse>>, DirectoryLoad)> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    let mut result = DirectoryLoad { verses: Vec::new(), loaded: Vec::new(), skipped: Vec::new() };
+    let mut files = Vec::new();
+
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let recognized = matches!(path.extension().and_then(|e| e.to_str()), Some("txt") | Some("json"));
+        if !recognized {
+            result.skipped.push((name, "not a .txt or .json file".to_string()));
+            continue;
+        }
+
+        match load(&path.to_string_lossy()) {
+            Ok(verses) if verses.is_empty() => result.skipped.push((name, "no verses found".to_string())),
+            Ok(verses) => {
+                result.loaded.push((name, verses.len()));
+                files.push(verses);
+            }
+            Err(e) => result.skipped.push((name, e.to_string())),
+        }
+    }
+
+    Ok((files, result))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_embedded_synonyms() {
+        let dir = tempfile::tempdir().unwrap();
+        let with = dir.path().join("with.json");
+        let without = dir.path().join("without.json");
+        std::fs::write(&with, r#"{
+            "synonyms": { "Love": ["charity", " Beloved "], "empty": [] },
+            "John": { "3": { "16": "For God so loved the world." } }
+        }"#).unwrap();
+        std::fs::write(&without, r#"{ "John": { "3": { "16": "For God so loved the world." } } }"#).unwrap();
+
+        // The synonyms block is not a book
+        let (verses, synonyms) = load_bible_json_with_synonyms(with.to_str().unwrap()).unwrap();
+        assert_eq!(verses.iter().map(|v| v.reference()).collect::<Vec<_>>(), ["John 3:16"]);
+        let synonyms = synonyms.unwrap();
+        assert_eq!(synonyms["Love"], ["charity", " Beloved "]);
+        let mapper = crate::synonyms::SynonymMapper::from_groups(synonyms);
+        assert_eq!(mapper.expanded_terms("love"), ["beloved", "charity", "love"]);
+        assert_eq!(mapper.get_synonym_count(), 1);
+        assert_eq!(load_bible_report_auto(with.to_str().unwrap(), Delimiter::default()).unwrap().synonyms.map(|s| s.len()), Some(2));
+
+        // Files without one load as before
+        let (verses, synonyms) = load_bible_json_with_synonyms(without.to_str().unwrap()).unwrap();
+        assert_eq!((verses.len(), synonyms), (1, None));
+        assert!(load_bible_report_auto(without.to_str().unwrap(), Delimiter::default()).unwrap().synonyms.is_none());
+    }
+
     #[test]
     fn test_json_parsing() {
         use std::io::Write;
@@ -164,5 +387,60 @@ mod tests {
         assert_eq!(verses[3].book, "John");
         assert_eq!(verses[3].chapter, 3);
         assert_eq!(verses[3].verse, 16);
+
+        let mut calls = 0;
+        load_bible_json_with_progress(path, 2, |_| calls += 1).unwrap();
+        assert_eq!(calls, 2);
+
+        // A verse listed twice keeps its last text; as stored, both are there
+        let repeated = r#"{"John": {"11": {"35": "Jesus cried.", "35": "Jesus wept."}}}"#;
+        let verses = load_bible_json_reader(repeated.as_bytes()).unwrap();
+        assert_eq!(verses.iter().map(|v| v.text.as_str()).collect::<Vec<_>>(), ["Jesus wept."]);
+        assert_eq!(read_json_stored(repeated.as_bytes(), 0, |_| {}).unwrap().0.len(), 2);
+    }
+
+    #[test]
+    fn test_load_from_reader() {
+        let json = r#"
+            {"John": {"11": {"36": "Then said the Jews, Behold how he loved him!", "35": "Jesus wept."}}}"#;
+        let verses = load_bible_json_reader(io::Cursor::new(json)).unwrap();
+        assert_eq!(verses.len(), 2);
+        assert_eq!(verses[0].reference(), "John 11:35");
+        assert_eq!(load_bible_reader_auto(io::Cursor::new(json)).unwrap(), verses);
+
+        let verses = load_bible_reader_auto(io::Cursor::new("TST\nTest Version\nJohn 11:35\tJesus wept.\n")).unwrap();
+        assert_eq!(verses.len(), 1);
+        assert_eq!(verses[0].text, "Jesus wept.");
+
+        assert_eq!(load_bible_json_reader(io::Cursor::new("{ not json")).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(load_bible_reader_auto(io::Cursor::new("{ not json")).unwrap_err().kind(), io::ErrorKind::InvalidData);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_load_bible_dir() {
+        use std::fs;
+        use tempfile::tempdir;
+
+        let dir = tempdir().unwrap();
+        // Written out of canonical order to check sorting
+        fs::write(dir.path().join("Exodus.txt"),
+            "TST\nTest Version\nExodus 1:1\tNow these are the names.\nExodus 1:2\tReuben, Simeon, Levi.\n").unwrap();
+        fs::write(dir.path().join("Genesis.json"),
+            r#"{"Genesis": {"1": {"1": "In the beginning."}}}"#).unwrap();
+        fs::write(dir.path().join("README.md"), "not a bible").unwrap();
+        fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+
+        let load = load_bible_dir(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(load.verses.len(), 3);
+        assert_eq!(load.verses[0].book, "Genesis");
+        assert_eq!(load.verses[1].book, "Exodus");
+        assert_eq!(load.loaded, vec![("Exodus.txt".to_string(), 2), ("Genesis.json".to_string(), 1)]);
+        let skipped: Vec<_> = load.skipped.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(skipped, vec!["README.md", "broken.json"]);
+
+        // The report load of a directory keeps the files it loaded and skipped
+        let report = load_bible_report_auto(dir.path().to_str().unwrap(), Delimiter::default()).unwrap();
+        assert_eq!((report.verses.len(), report.loaded_files, report.skipped_files.len()), (3, load.loaded, 2));
+    }
+}
@@ -0,0 +1,135 @@
+// lemma.rs
+// A small rule-based English lemmatizer (--lemmatize): "loved", "loving" and "loveth" all
+// compare as "love"
+
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // Irregular forms no suffix rule recovers, the archaic ones included
+    static ref IRREGULAR: HashMap<&'static str, &'static str> = [
+        ("am", "be"), ("is", "be"), ("are", "be"), ("art", "be"), ("was", "be"), ("wast", "be"),
+        ("were", "be"), ("wert", "be"), ("been", "be"), ("being", "be"),
+        ("has", "have"), ("hast", "have"), ("hath", "have"), ("had", "have"), ("hadst", "have"), ("having", "have"),
+        ("does", "do"), ("doth", "do"), ("doeth", "do"), ("dost", "do"), ("did", "do"), ("didst", "do"), ("done", "do"),
+        ("said", "say"), ("saith", "say"), ("sayest", "say"),
+        ("went", "go"), ("gone", "go"), ("goes", "go"), ("goeth", "go"),
+        ("came", "come"), ("cometh", "come"),
+        ("saw", "see"), ("seen", "see"), ("seeth", "see"),
+        ("spake", "speak"), ("spoke", "speak"), ("spoken", "speak"),
+        ("gave", "give"), ("given", "give"),
+        ("took", "take"), ("taken", "take"),
+        ("knew", "know"), ("known", "know"),
+        ("made", "make"), ("sat", "sit"), ("ate", "eat"), ("eaten", "eat"),
+        ("began", "begin"), ("begun", "begin"), ("begat", "beget"), ("begotten", "beget"),
+        ("brought", "bring"), ("thought", "think"), ("taught", "teach"), ("sought", "seek"), ("bought", "buy"),
+        ("wrote", "write"), ("written", "write"), ("found", "find"), ("heard", "hear"), ("told", "tell"), ("sold", "sell"),
+        ("stood", "stand"), ("kept", "keep"), ("slept", "sleep"), ("wept", "weep"), ("fled", "flee"), ("fed", "feed"),
+        ("slew", "slay"), ("slain", "slay"), ("arose", "arise"), ("arisen", "arise"), ("rose", "rise"), ("risen", "rise"),
+        ("fell", "fall"), ("fallen", "fall"), ("forgave", "forgive"), ("forgiven", "forgive"),
+        ("bare", "bear"), ("born", "bear"), ("borne", "bear"), ("ran", "run"), ("sent", "send"), ("built", "build"),
+        ("dwelt", "dwell"), ("spent", "spend"), ("meant", "mean"), ("held", "hold"), ("drank", "drink"), ("drunk", "drink"),
+        ("sang", "sing"), ("sung", "sing"), ("chose", "choose"), ("chosen", "choose"), ("drew", "draw"), ("drawn", "draw"),
+        ("men", "man"), ("women", "woman"), ("children", "child"), ("brethren", "brother"), ("feet", "foot"), ("teeth", "tooth"),
+    ]
+    .into_iter()
+    .collect();
+}
+
+// Words with an ending a rule would strip but that are already roots
+const UNCHANGED: &[&str] = &[
+    "priest", "harvest", "request", "conquest", "interest", "manifest", "modest", "honest", "earnest",
+    "behest", "forest", "guest", "tempest", "contest", "arrest", "protest", "lest", "nest", "rest",
+    "test", "chest", "best", "west", "quest", "anything", "nothing", "something", "everything",
+    "evening", "morning", "wedding", "hundred", "kindred", "naked", "wicked", "sacred", "moses",
+    "alas", "always", "perhaps",
+];
+
+// Does the word end in a consonant, a vowel, then a consonant other than w, x or y?
+fn ends_cvc(word: &[u8]) -> bool {
+    let vowel = |c: u8| b"aeiou".contains(&c);
+    match word {
+        [.., a, b, c] => !vowel(*a) && vowel(*b) && !vowel(*c) && !b"wxy".contains(c),
+        _ => false,
+    }
+}
+
+// Put back what an -ed, -ing, -eth or -est ending took: "lov" → "love", "stopp" → "stop"
+fn restore_stem(stem: &str) -> String {
+    let bytes = stem.as_bytes();
+    let vowel_groups = stem.split(|c: char| !"aeiouy".contains(c)).filter(|group| !group.is_empty()).count();
+    match bytes {
+        [.., a, b] if a == b && !b"lsz".contains(b) => stem[..stem.len() - 1].to_string(),
+        // English words don't end in v, c or u: "believ", "danc", "continu"
+        [.., b'v' | b'c' | b'u'] => format!("{}e", stem),
+        // "rais", "pleas", but not "bless"
+        [.., a, b's'] if b"aeiou".contains(a) => format!("{}e", stem),
+        _ if vowel_groups == 1 && ends_cvc(bytes) => format!("{}e", stem),
+        _ => stem.to_string(),
+    }
+}
+
+/// The dictionary form of a lowercase English word: verbs lose their tense endings, KJV ones
+/// (`-eth`, `-est`) included, nouns their plural endings, and common irregular forms map to
+/// their root. Rule-based, so it is only approximate; short words and words it doesn't
+/// recognize are returned as they are.
+pub fn lemmatize(word: &str) -> String {
+    if let Some(root) = IRREGULAR.get(word) {
+        return root.to_string();
+    }
+    if word.len() <= 3 || UNCHANGED.contains(&word) || !word.is_ascii() {
+        return word.to_string();
+    }
+    let stem = |suffix: &str| word.strip_suffix(suffix).filter(|stem| stem.len() >= 2 && stem.contains(|c: char| "aeiouy".contains(c)));
+
+    // "carried", "cities", "holiest"; "died" and "lies" keep their "ie"
+    for suffix in ["ied", "ies", "iest", "ieth"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            return if stem.len() >= 2 { format!("{}y", stem) } else { format!("{}ie", stem) };
+        }
+    }
+    // "seeth", "fleeth": the -th goes, the doubled e stays; "agreed" and "indeed" are left be
+    if let Some(stem) = word.strip_suffix("eeth") {
+        return format!("{}ee", stem);
+    }
+    if word.ends_with("eed") {
+        return word.to_string();
+    }
+    for suffix in ["ing", "eth", "est", "ed"] {
+        if let Some(stem) = stem(suffix) {
+            return restore_stem(stem);
+        }
+    }
+    // "churches", "wishes", "boxes"; other plurals just lose the s
+    if let Some(stem) = word.strip_suffix("es").filter(|stem| ["ch", "sh", "ss", "x", "z"].iter().any(|end| stem.ends_with(end))) {
+        return stem.to_string();
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if !stem.ends_with(['s', 'u', 'i']) => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lemmatize() {
+        for word in ["love", "loved", "loving", "loveth", "lovest", "loves"] {
+            assert_eq!(lemmatize(word), "love", "{}", word);
+        }
+        let cases = [
+            ("walked", "walk"), ("walketh", "walk"), ("believed", "believe"), ("stopped", "stop"),
+            ("blessed", "bless"), ("carried", "carry"), ("cities", "city"), ("died", "die"),
+            ("churches", "church"), ("seeth", "see"), ("opened", "open"), ("raised", "raise"),
+            ("holiest", "holy"), ("houses", "house"), ("indeed", "indeed"),
+            ("was", "be"), ("children", "child"), ("spake", "speak"),
+            // Roots with an ending that looks like a suffix stay as they are
+            ("priest", "priest"), ("king", "king"), ("jesus", "jesus"), ("glass", "glass"), ("god", "god"),
+        ];
+        for (word, lemma) in cases {
+            assert_eq!(lemmatize(word), lemma, "{}", word);
+        }
+    }
+}
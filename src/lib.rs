@@ -0,0 +1,35 @@
+// lib.rs
+// Library interface: the modules behind the bible_tool command line, usable from other crates
+
+pub mod bible;
+pub mod bookmarks;
+pub mod config;
+pub mod error;
+pub mod synonyms;
+pub mod names;
+pub mod numbers;
+pub mod outline;
+pub mod json_parser;
+pub mod lemma;
+pub mod export;
+pub mod reference;
+pub mod books;
+pub mod highlight;
+pub mod search;
+pub mod serve;
+#[cfg(feature = "server")]
+pub mod http;
+pub mod similarity;
+pub mod output;
+pub mod pager;
+pub mod passage;
+pub mod filter;
+pub mod random;
+pub mod reading;
+pub mod topic;
+pub mod translations;
+pub mod validate;
+pub mod vocab;
+pub mod versification;
+
+pub use bible::{Bible, Verse};
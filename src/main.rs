@@ -1,17 +1,29 @@
+use std::io::IsTerminal;
 use colored::*;
-use clap::{Arg, Command};
+use clap::builder::TypedValueParser;
+use clap::{Arg, ArgGroup, ArgMatches, Command};
 
-// Declare the new modules
-mod bible;
-mod synonyms;
-mod json_parser;
-
-// Use the structs and functions from the new modules
-use bible::{search_bible_cli, lookup_verse_cli, get_random_verse, find_cross_references, interactive_mode};
-use synonyms::SynonymMapper;
+// The modules live in the library crate (src/lib.rs)
+use bible_tool::{bookmarks, books, export, json_parser, names, outline, reading, serve, topic, translations, validate, vocab};
+use bible_tool::config::{self, Config};
+use bible_tool::error::{BibleError, ErrorFormat};
+use bible_tool::versification::Versification;
+use bible_tool::bible::{print_boundary_verse, print_canonical_reference, lookup_verse_cli, get_random_verse, print_random_passage, print_shuffled_verses, find_cross_references, find_chapter_cross_references, print_metric_comparison, find_like_cli, interactive_mode, extract_notes, Bible, TranslationCache, TRANSLATION_CACHE_SIZE, Delimiter, NoteBrackets, NoteDisplay, XrefDisplay};
+use bible_tool::books::Testament;
+use bible_tool::bookmarks::Bookmarks;
+use bible_tool::filter::{self, VerseFilter};
+use bible_tool::output::{BookListDetail, ColorChoice, OutputFormat};
+use bible_tool::passage::PassageStyle;
+use bible_tool::random::Rng;
+use bible_tool::reference::{invalid_reference_reason, parse_reference};
+use bible_tool::search::{chapter_search_cli, number_search_cli, search_bible_cli, starts_with_cli, SearchOptions, SortOrder};
+use bible_tool::similarity::{similarity_bar, WordFilter, XrefCorpus, XrefOptions, XrefScope};
+use bible_tool::names::NameEquivalents;
+use bible_tool::synonyms::SynonymMapper;
+use bible_tool::translations::TranslationRegistry;
 
 fn create_cli() -> Command {
-    Command::new("bible_tool")
+    let cli = Command::new("bible_tool")
         .version("2.0.2")
         .author("Your Name")
         .about("Enhanced Bible search tool with synonym support")
@@ -19,33 +31,44 @@ fn create_cli() -> Command {
             .short('f')
             .long("file")
             .value_name("FILE")
-            .help("Path to Bible text file")
+            .help("Path to Bible text file, or a directory of per-book files; '-' reads the Bible from stdin")
             .default_value("bibles/bible.txt"))
+        .arg(Arg::new("translation")
+            .short('t')
+            .long("translation")
+            .value_name("CODE")
+            .help("Use a translation from the registry (e.g., 'kjv', or your own code)")
+            .conflicts_with_all(["file", "kjv", "erv", "asv", "esv", "nasb"]))
+        .arg(Arg::new("translations-file")
+            .long("translations-file")
+            .value_name("FILE")
+            .help("Path to the translation registry")
+            .default_value(translations::DEFAULT_REGISTRY_FILE))
         .arg(Arg::new("kjv")
             .long("kjv")
-            .help("Use the King James Version (bibles/kjv.txt)")
+            .help("Use the King James Version (alias for --translation kjv)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["file", "erv", "asv", "esv", "nasb"]))
+            .conflicts_with_all(["file", "erv", "asv", "esv", "nasb"]))
         .arg(Arg::new("erv")
             .long("erv")
-            .help("Use the English Revised Version (bibles/erv.txt)")
+            .help("Use the English Revised Version (alias for --translation erv)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["file", "kjv", "asv", "esv", "nasb"]))
+            .conflicts_with_all(["file", "kjv", "asv", "esv", "nasb"]))
         .arg(Arg::new("esv")
             .long("esv")
-            .help("Use the English Revised Version (bibles/ESV.json)")
+            .help("Use the English Standard Version (alias for --translation esv)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["file", "kjv", "asv", "erv", "nasb"]))
+            .conflicts_with_all(["file", "kjv", "asv", "erv", "nasb"]))
         .arg(Arg::new("nasb")
             .long("nasb")
-            .help("Use the English Revised Version (bibles/NASB.json)")
+            .help("Use the New American Standard Bible (alias for --translation nasb)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["file", "kjv", "asv", "erv", "esv"]))
+            .conflicts_with_all(["file", "kjv", "asv", "erv", "esv"]))
         .arg(Arg::new("asv")
             .long("asv")
-            .help("Use the American Standard Version (bibles/asv.txt)")
+            .help("Use the American Standard Version (alias for --translation asv)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["file", "kjv", "erv", "esv", "nasb"]))
+            .conflicts_with_all(["file", "kjv", "erv", "esv", "nasb"]))
         .arg(Arg::new("synonyms-file")
             .long("synonyms-file")
             .value_name("FILE")
@@ -55,82 +78,514 @@ fn create_cli() -> Command {
             .long("create-synonyms")
             .help("Create default synonyms file and exit")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("force")
+            .long("force")
+            .help("Let --create-synonyms replace an existing synonyms file")
+            .action(clap::ArgAction::SetTrue)
+            .requires("create-synonyms"))
         .arg(Arg::new("search")
             .short('s')
             .long("search")
             .value_name("QUERY")
             .help("Search for text in verses")
-            .conflicts_with_all(&["reference", "random"]))
+            .conflicts_with_all(["reference", "random"]))
+        .arg(Arg::new("chapter-search")
+            .long("chapter-search")
+            .value_name("QUERY")
+            .help("Find chapters that contain every query word somewhere in their verses")
+            .conflicts_with_all(["search", "reference", "random", "cross-references"]))
+        .arg(Arg::new("starts-with")
+            .long("starts-with")
+            .value_name("PREFIX")
+            .help("Find verses whose text begins with PREFIX (e.g., 'In the beginning'), ignoring case")
+            .conflicts_with_all(["search", "chapter-search", "topic-profile", "reference", "random", "cross-references"]))
+        .arg(Arg::new("number")
+            .long("number")
+            .value_name("N")
+            .help("Find verses mentioning the number N, as digits or in words ('7', 'seven', 'two and forty')")
+            .value_parser(clap::value_parser!(u64))
+            .conflicts_with_all(["search", "chapter-search", "starts-with", "topic-profile", "reference", "random", "cross-references"]))
+        .arg(Arg::new("number-range")
+            .long("number-range")
+            .value_names(["LOW", "HIGH"])
+            .help("Find verses mentioning a number from LOW to HIGH, as digits or in words")
+            .num_args(2)
+            .value_parser(clap::value_parser!(u64))
+            .conflicts_with_all(["number", "search", "chapter-search", "starts-with", "topic-profile", "reference", "random", "cross-references"]))
+        .arg(Arg::new("topic-profile")
+            .long("topic-profile")
+            .value_name("KEYWORD")
+            .help("List the words most often used alongside a topic (the keyword and its synonyms); use --limit for the top N")
+            .conflicts_with_all(["search", "chapter-search", "reference", "random", "cross-references"]))
+        .arg(Arg::new("memorize")
+            .long("memorize")
+            .value_name("TOPIC")
+            .help("List the shortest verses on a topic (the keyword or its synonyms), for memorizing; use --limit for more than 5")
+            .conflicts_with_all(["search", "chapter-search", "reference", "random", "cross-references", "topic-profile"]))
+        .arg(Arg::new("outline")
+            .long("outline")
+            .value_name("BOOK")
+            .help("Outline a book: each chapter with its first verse as a teaser (--format json for the full verses)")
+            .conflicts_with_all(["search", "chapter-search", "reference", "random", "cross-references", "topic-profile"]))
         .arg(Arg::new("reference")
             .short('r')
             .long("reference")
             .value_name("REFERENCE")
-            .help("Look up verse by reference (e.g., 'John 3:16')")
-            .conflicts_with_all(&["search", "random"]))
+            .help("Look up a verse, range, chapter or book (e.g., 'John 3:16', 'John 3:16-18', 'John 3', 'Matthew 5-7', 'John')")
+            .conflicts_with_all(["search", "random"]))
+        .arg(Arg::new("extract")
+            .long("extract")
+            .value_name("REFERENCE")
+            .help("Write a book, chapters or verses (e.g., 'Matthew 5-7') to a new Bible file given by --output")
+            .requires("output")
+            .conflicts_with_all(["search", "reference", "random", "cross-references", "interactive"]))
+        .arg(Arg::new("output")
+            .short('o')
+            .long("output")
+            .value_name("FILE")
+            .help("File written by --extract (JSON when it ends in .json, otherwise text with the source's header lines) or --xref-book (JSON)")
+            .requires("writes-file"))
+        .arg(Arg::new("prose")
+            .long("prose")
+            .help("Print a looked-up passage as flowing paragraphs with superscript verse numbers")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("exists")
+            .long("exists")
+            .value_name("REFERENCE")
+            .help("Print nothing; exit 0 if the reference is in the Bible (every verse of a range), 1 if not, 2 if it isn't a reference")
+            .conflicts_with_all(["search", "reference", "random"]))
+        .arg(Arg::new("exists-any")
+            .long("exists-any")
+            .help("With --exists, a range exists when any of its verses (or chapters) do")
+            .action(clap::ArgAction::SetTrue)
+            .requires("exists"))
+        .arg(Arg::new("canonical-ref")
+            .long("canonical-ref")
+            .help("Print the looked-up reference in canonical form (e.g., 'jn 3.16' -> 'John 3:16') instead of its text")
+            .action(clap::ArgAction::SetTrue)
+            .requires("reference"))
+        .arg(Arg::new("inline-numbers")
+            .long("inline-numbers")
+            .value_name("WHEN")
+            .help("Print a looked-up passage as one block with bracketed verse numbers: [16] For God... [17] ... ('always' numbers a single verse too)")
+            .num_args(0..=1)
+            .default_missing_value("auto")
+            .value_parser(["auto", "always"])
+            .conflicts_with_all(["prose", "plain"]))
+        .arg(Arg::new("refs-only")
+            .long("refs-only")
+            .help("Print only the references of the results ('Book Chapter:Verse'), with no text; --format json for a JSON array")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["plain", "prose", "inline-numbers", "canonical-ref"]))
+        .arg(Arg::new("only-book-list")
+            .long("only-book-list")
+            .value_name("DETAIL")
+            .help("With --search, print only the books containing matches and their verse counts; 'chapters' lists each chapter")
+            .value_parser(["books", "chapters"])
+            .num_args(0..=1)
+            .default_missing_value("books")
+            .requires("search")
+            .conflicts_with_all(["refs-only", "plain", "show-score"]))
+        .arg(Arg::new("error-format")
+            .long("error-format")
+            .value_name("FORMAT")
+            .help("How failures are written to stderr: 'human' or 'json' ({\"error\": ..., \"message\": ..., \"exit_code\": ...})")
+            .value_parser(["human", "json"])
+            .default_value("human"))
+        .arg(Arg::new("plain")
+            .long("plain")
+            .help("Print results as uncolored 'Book Chapter:Verse<TAB>Text' lines with no status messages")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["prose", "show-score", "interactive", "cross-references"]))
+        .arg(Arg::new("allow-duplicate-refs")
+            .long("allow-duplicate-refs")
+            .help("Keep every copy of a reference a text Bible lists more than once (by default the first is kept, with a warning)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("delimiter")
+            .long("delimiter")
+            .value_name("CHAR")
+            .help("Separator between reference and text for --plain output and for loading text Bibles: 'tab' (default), 'comma', 'pipe' or one character")
+            .value_parser(Delimiter::parse))
+        .arg(Arg::new("search-notes")
+            .long("search-notes")
+            .help("Search the bracketed study notes of a Bible (like '[agape]') instead of its text")
+            .action(clap::ArgAction::SetTrue)
+            .requires("search"))
+        .arg(Arg::new("show-notes")
+            .long("show-notes")
+            .help("Take bracketed study notes out of the text and show them after each verse")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("note-brackets")
+            .long("note-brackets")
+            .value_name("PAIR")
+            .help("The brackets study notes are written in, for --search-notes and --show-notes [default: []]")
+            .value_parser(NoteBrackets::parse))
         .arg(Arg::new("random")
             .long("random")
-            .help("Get a random verse")
+            .help("Get a random verse (of the --book or --testament, if given)")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["search", "reference", "random-chapter", "random-passage"]))
+        .arg(Arg::new("random-chapter")
+            .long("random-chapter")
+            .help("Print a random whole chapter (of the --book or --testament, if given)")
             .action(clap::ArgAction::SetTrue)
-            .conflicts_with_all(&["search", "reference"]))
+            .conflicts_with_all(["search", "reference", "random-passage"]))
+        .arg(Arg::new("random-passage")
+            .long("random-passage")
+            .value_name("N")
+            .help("Print N consecutive verses from a random starting verse, within its chapter")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .conflicts_with_all(["search", "reference"]))
+        .arg(Arg::new("shuffle")
+            .long("shuffle")
+            .value_name("N")
+            .help("Print N different random verses (of the --book or --testament, if given), in random order")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .conflicts_with_all(["search", "reference", "random", "random-chapter", "random-passage"]))
+        .arg(Arg::new("resume")
+            .long("resume")
+            .help("Print the chapter after the last one read, across books, and remember it as read")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["search", "reference", "random", "random-chapter", "random-passage"]))
+        .arg(Arg::new("set-position")
+            .long("set-position")
+            .value_name("CHAPTER")
+            .help("Record a chapter (like \"John 3\") as the last one read, for --resume to read on from")
+            .conflicts_with_all(["search", "reference", "random", "random-chapter", "random-passage"]))
+        .arg(Arg::new("position-file")
+            .long("position-file")
+            .value_name("FILE")
+            .help("File --resume and --set-position keep the reading position in")
+            .default_value(reading::DEFAULT_POSITION_FILE))
+        .arg(Arg::new("seed")
+            .long("seed")
+            .value_name("N")
+            .help("Seed for --random, --random-chapter, --random-passage and --shuffle, so the same pick is made every time")
+            .value_parser(clap::value_parser!(u64)))
         .arg(Arg::new("synonyms")
             .long("synonyms")
             .help("Include synonyms in search")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("name-equiv")
+            .long("name-equiv")
+            .help("Also search for other spellings of names (Elijah/Elias, Isaiah/Esaias)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("names-file")
+            .long("names-file")
+            .value_name("FILE")
+            .help("Name equivalents file for --name-equiv (built-in list when missing)")
+            .default_value(names::DEFAULT_NAMES_FILE))
         .arg(Arg::new("case-sensitive")
             .short('c')
             .long("case-sensitive")
             .help("Case sensitive search")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("match-case-exact")
+            .long("match-case-exact")
+            .help("Match query words written with capitals in exactly that case ('LORD' but not 'Lord'); other words ignore case")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("case-sensitive"))
         .arg(Arg::new("book")
             .short('b')
             .long("book")
             .value_name("BOOK")
-            .help("Filter results to specific book"))
+            .help("Filter results to specific book or book group (name or abbreviation, see --books)"))
+        .arg(Arg::new("book-glob")
+            .long("book-glob")
+            .help("Read --book as a glob over book names: '1*' for 1 Samuel, 1 Kings, 1 John..., '*John' for John and 1-3 John")
+            .action(clap::ArgAction::SetTrue)
+            .requires("book"))
+        .arg(Arg::new("books")
+            .long("books")
+            .help("List the books of the Bible with their accepted abbreviations and exit")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("testament")
+            .long("testament")
+            .value_name("TESTAMENT")
+            .help("Restrict to the Old ('ot') or New ('nt') Testament")
+            .value_parser(["ot", "nt", "old", "new"]))
+        .arg(Arg::new("exclude-book")
+            .long("exclude-book")
+            .value_name("BOOK")
+            .help("Leave out a book or book group, applied after --book (repeatable)")
+            .action(clap::ArgAction::Append))
+        .arg(Arg::new("exclude-testament")
+            .long("exclude-testament")
+            .value_name("TESTAMENT")
+            .help("Leave out the Old ('ot') or New ('nt') Testament")
+            .value_parser(["ot", "nt", "old", "new"]))
+        .arg(Arg::new("ref-pattern")
+            .long("ref-pattern")
+            .value_name("PATTERN")
+            .help("Only consider verses whose reference matches a glob ('Psalm*', '* 1:*') or a regex starting with '^'"))
+        .arg(Arg::new("first")
+            .long("first")
+            .help("Print the first verse of the selected --book or --testament")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["last", "search", "reference", "random", "cross-references"]))
+        .arg(Arg::new("last")
+            .long("last")
+            .help("Print the last verse of the selected --book or --testament")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["first", "search", "reference", "random", "cross-references"]))
         .arg(Arg::new("limit")
             .short('l')
             .long("limit")
             .value_name("NUMBER")
-            .help("Limit number of results")
+            .help("Limit number of results (0 for no limit)")
             .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("min-terms")
+            .long("min-terms")
+            .value_name("K")
+            .help("Only match verses containing at least K of the distinct query words (synonyms count for their word)")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("no-synonyms-for")
+            .long("no-synonyms-for")
+            .value_name("WORD")
+            .help("Don't expand WORD to its synonyms, while other words still are (repeatable)")
+            .action(clap::ArgAction::Append))
+        .arg(Arg::new("synonyms-first")
+            .long("synonyms-first")
+            .help("Search with synonyms for the first query word that has any (the topic), the rest as typed; implies --synonyms")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("no-exclude-synonyms")
+            .long("no-exclude-synonyms")
+            .help("With --synonyms, exclude only the -word itself from a search, not its synonyms")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("all")
+            .long("all")
+            .visible_alias("contains-all")
+            .help("Only match verses containing every query word (synonyms count for their word)")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("min-terms"))
+        .arg(Arg::new("nth")
+            .long("nth")
+            .value_name("N")
+            .help("Print only the Nth result (1-based) of a verse search, after sorting; an error when there are fewer")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .requires("verse-search")
+            .conflicts_with("only-book-list"))
+        .arg(Arg::new("max-results-per-chapter")
+            .long("max-results-per-chapter")
+            .value_name("N")
+            .help("Show at most N matching verses from any one chapter (before --limit); the footer still counts them all")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .requires("search"))
+        .arg(Arg::new("compact")
+            .long("compact")
+            .help("Print each result of a verse search on one line, references in a column and the text cut to the terminal width")
+            .requires("verse-search")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("sort")
+            .long("sort")
+            .value_name("ORDER")
+            .help("Order of search results: 'canonical' (Bible order) or 'relevance'")
+            .value_parser(["canonical", "relevance"])
+            .default_value("canonical"))
+        .arg(Arg::new("show-score")
+            .long("show-score")
+            .help("Show each search result's relevance score (share of query words found)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("highlight-words")
+            .long("highlight-words")
+            .help("Highlight the whole word when a search term matches only part of it")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("no-term-colors")
+            .long("no-term-colors")
+            .help("Highlight every query word in the same color, with no legend, instead of each word (with its synonyms) in its own")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("validate")
+            .long("validate")
+            .visible_alias("dry-run")
+            .help("Check that the Bible file parses cleanly (malformed lines, duplicates, gaps) and exit")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("check-integrity")
+            .long("check-integrity")
+            .help("Check that every book's chapters and verses are complete and in order, then exit")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("validate"))
+        .arg(Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .help("Output format for search results, --reference, --cross-references, --validate, --check-integrity, --topic-profile and --refs-only: 'text', 'json', or 'jsonl' for one JSON object per result line; 'csv' with --only-book-list")
+            .value_parser(["text", "json", "jsonl", "csv"])
+            .default_value("text"))
+        .arg(Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Leave out status messages and the match summary")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("color")
+            .long("color")
+            .value_name("WHEN")
+            .help("Color output: auto (when writing to a terminal), always or never [default: auto]")
+            .value_parser(clap::builder::PossibleValuesParser::new(["auto", "always", "never"])
+                .map(|s| ColorChoice::parse(&s).unwrap_or_default())))
         .arg(Arg::new("no-color")
             .long("no-color")
-            .help("Disable colored output")
+            .help("Deprecated alias for --color never")
+            .conflicts_with("color")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("bookmark-file")
+            .long("bookmark-file")
+            .value_name("FILE")
+            .help("File the interactive 'bookmark' and 'bookmarks' commands save to and read from")
+            .default_value(bookmarks::DEFAULT_BOOKMARK_FILE))
         .arg(Arg::new("interactive")
             .short('i')
             .long("interactive")
             .help("Start in interactive mode")
             .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("serve")
+            .long("serve")
+            .help("Load the Bible once, then answer JSON requests read one per line from stdin with one JSON line each on stdout (see README)")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["interactive", "search", "reference", "random", "cross-references"]))
         .arg(Arg::new("cross-references")
             .short('x')
             .long("cross-references")
             .value_name("REFERENCE")
             .help("Find cross-references for a verse (e.g., 'John 3:16')")
-            .conflicts_with_all(&["search", "random"]))
+            .conflicts_with_all(["search", "random"]))
+        .arg(Arg::new("cross-references-chapter")
+            .long("cross-references-chapter")
+            .value_name("CHAPTER")
+            .help("Find the chapters most like a chapter (e.g., '2 Chronicles 9'), comparing their whole text; use --limit for more than 10")
+            .conflicts_with_all(["search", "random", "reference"]))
+        .arg(Arg::new("xref-compare")
+            .long("xref-compare")
+            .value_name("REFERENCE")
+            .help("Show a verse's top cross-references by Jaccard, n-gram and names side by side (top 5 of each unless --top or --limit is given)")
+            .conflicts_with_all(["search", "random", "reference", "cross-references", "cross-references-chapter", "find-like"]))
+        .arg(Arg::new("find-like")
+            .long("find-like")
+            .value_name("TEXT")
+            .help("Find the verses worded most like TEXT, for a verse you only roughly remember (top 5 unless --top or --limit is given)")
+            .conflicts_with_all(["search", "chapter-search", "starts-with", "number", "number-range", "topic-profile", "reference", "random", "cross-references"]))
+        .arg(Arg::new("xref-book")
+            .long("xref-book")
+            .value_name("BOOK")
+            .help("Write the cross-references of every verse in BOOK to --output as JSON (top 10 per verse unless --top or --limit is given)")
+            .requires("output")
+            .conflicts_with_all(["search", "reference", "random", "cross-references", "find-like", "extract", "interactive"]))
+        .arg(Arg::new("versification")
+            .long("versification")
+            .value_name("FILE")
+            .help("Map --reference and --cross-references input from another verse numbering using this file"))
         .arg(Arg::new("similarity")
             .long("similarity")
             .value_name("METRIC")
-            .help("Similarity metric: 0.0-1.0 for Jaccard, or '2-gram', '3-gram', etc. for phrase matching")
+            .help("Similarity metric: 0.0-1.0 for Jaccard, '2-gram', '3-gram', etc. for phrase matching, or 'names' for shared proper nouns")
             .default_value("0.3"))
+        .arg(Arg::new("top")
+            .long("top")
+            .value_name("K")
+            .help("Show the K most similar cross-references (or --find-like matches), whatever their score (ignores the --similarity threshold)")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .conflicts_with("limit"))
+        .arg(Arg::new("xref-scope")
+            .long("xref-scope")
+            .value_name("SCOPE")
+            .help("Only look for cross-references in the source verse's 'book' or 'testament', or in 'all' verses")
+            .value_parser(["book", "testament", "all"])
+            .default_value("all"))
+        .arg(Arg::new("bars")
+            .long("bars")
+            .help("Draw each cross-reference's (or --find-like match's) similarity as a bar (██████░░░░ 60.0%)")
+            .action(clap::ArgAction::SetTrue)
+            .requires("similar"))
+        .arg(Arg::new("show-keywords")
+            .long("show-keywords")
+            .help("Print the words the source verse is compared by (after stop words and synonyms) before its cross-references")
+            .action(clap::ArgAction::SetTrue)
+            .requires("cross-references"))
+        .arg(Arg::new("explain-ranking")
+            .long("explain-ranking")
+            .help("Print under each cross-reference what its score is made of: the shared and total words for Jaccard and names, the shared phrases for n-grams")
+            .action(clap::ArgAction::SetTrue)
+            .requires("cross-references"))
+        .arg(Arg::new("vocab")
+            .long("vocab")
+            .help("Count the distinct words (leaving out stop words) of the Bible, or of the --book/--testament chosen")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("by-book")
+            .long("by-book")
+            .help("With --vocab, count each book's vocabulary too")
+            .action(clap::ArgAction::SetTrue)
+            .requires("vocab"))
+        .arg(Arg::new("no-stopwords")
+            .long("no-stopwords")
+            .help("Compare common words ('the', 'unto', 'ye') too when finding cross-references, and count them in --vocab")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("min-word-len")
+            .long("min-word-len")
+            .value_name("N")
+            .help("Shortest word compared when finding cross-references or counted by --vocab, in letters (default 3)")
+            .value_parser(clap::value_parser!(usize)))
+        .arg(Arg::new("lemmatize")
+            .long("lemmatize")
+            .help("Compare words by their dictionary form ('loved', 'loving' → 'love') when searching, finding cross-references or counting --vocab")
+            .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("use-synonyms-xref")
             .long("use-synonyms-xref")
             .help("Use synonyms when calculating cross-reference similarity")
             .action(clap::ArgAction::SetTrue))
+        // The modes that write --output
+        .group(ArgGroup::new("writes-file").args(["extract", "xref-book"]).multiple(false))
+        // The modes that score verses by similarity
+        .group(ArgGroup::new("similar").args(["cross-references", "cross-references-chapter", "find-like"]).multiple(false))
+        // The searches listing matching verses, which --nth picks from
+        .group(ArgGroup::new("verse-search").args(["search", "starts-with", "number", "number-range"]).multiple(false));
+
+    // The HTTP API is only built with the `server` feature
+    #[cfg(feature = "server")]
+    let cli = cli.arg(Arg::new("http")
+        .long("http")
+        .value_name("ADDRESS")
+        .help("Serve a JSON API (/search, /verse, /random, /xref) over HTTP on ADDRESS, e.g. 127.0.0.1:8080 (see README)")
+        .conflicts_with_all(["interactive", "serve", "search", "reference", "random", "cross-references"]));
+    cli
 }
 
 fn main() {
     let matches = create_cli().get_matches();
-    
-    let synonyms_file = matches.get_one::<String>("synonyms-file").unwrap();
+
+    let error_format = match matches.get_one::<String>("error-format").map(|f| f.as_str()) {
+        Some("json") => ErrorFormat::Json,
+        _ => ErrorFormat::Human,
+    };
+
+    // Defaults from bible_search.toml; flags given on the command line take precedence
+    let config = match Config::discover() {
+        Ok(config) => config,
+        Err((path, e)) => exit_with(BibleError::load(&path.to_string_lossy(), &e), error_format),
+    };
+    // BIBLE_FILE and BIBLE_SYNONYMS sit between the flags and the config file
+    // Synonyms embedded in a JSON Bible are used only when no synonyms file was asked for
+    let synonyms_file_chosen = config::path_chosen(&matches, "synonyms-file", config::SYNONYMS_FILE_VAR, config.synonyms_file.as_ref());
+    let synonyms_file = &config::path_setting(&matches, "synonyms-file", config::SYNONYMS_FILE_VAR, config.synonyms_file.clone()).unwrap();
+    // A deprecated no-color = true in the config, or --no-color, means never
+    let config_color = config.color.or(config.no_color.filter(|&never| never).map(|_| ColorChoice::Never));
+    let color = if matches.get_flag("no-color") {
+        ColorChoice::Never
+    } else {
+        config::setting(&matches, "color", config_color).unwrap_or_default()
+    };
+    let limit = config::setting(&matches, "limit", config.limit);
     
     // Handle --create-synonyms flag
     if matches.get_flag("create-synonyms") {
-        match SynonymMapper::create_default_file(synonyms_file) {
+        match SynonymMapper::create_default_file(synonyms_file, matches.get_flag("force")) {
             Ok(_) => {
                 println!("{} Created default synonyms file: {}", "✅".green(), synonyms_file);
                 println!("You can now edit this file to customize your synonyms.");
                 return;
             }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                eprintln!("⚠️  {} already exists, so it was left as it is.", synonyms_file);
+                eprintln!("   Run with --create-synonyms --force to replace it with the defaults.");
+                std::process::exit(1);
+            }
             Err(e) => {
                 eprintln!("{} Error creating synonyms file: {}", "🔥".red(), e);
                 return;
@@ -138,83 +593,498 @@ fn main() {
         }
     }
     
-    // Bible selection with version flags
-    let bible_file = if matches.get_flag("kjv") {
-        "bibles/kjv.txt"
-    } else if matches.get_flag("erv") {
-        "bibles/erv.txt"
-    } else if matches.get_flag("asv") {
-        "bibles/asv.txt"
-    } else if matches.get_flag("esv") {
-        "bibles/ESV.json"
-    } else if matches.get_flag("nasb") {
-        "bibles/NASB.json"
-    } else {
-        // Fallback to the --file argument if no version flag is used
-        matches.get_one::<String>("file").unwrap()
+    // Handle --books flag; the table doesn't need a loaded Bible
+    if matches.get_flag("books") {
+        books::print_books_cli(color.apply());
+        return;
+    }
+
+    // Bible selection: a translation code (or one of its alias flags) goes through the registry
+    let translation_code = matches.get_one::<String>("translation").cloned().or_else(|| {
+        ["kjv", "erv", "asv", "esv", "nasb"]
+            .into_iter()
+            .find(|code| matches.get_flag(code))
+            .map(|code| code.to_string())
+    }).or_else(|| {
+        // The config's translation only applies when no file was named either
+        config.translation.clone().filter(|_| !config::path_chosen(&matches, "file", config::BIBLE_FILE_VAR, None))
+    });
+
+    // The title shown in interactive mode: the translation's name, or the file it came from
+    let (bible_file, title) = match translation_code {
+        Some(code) => {
+            let registry_file = matches.get_one::<String>("translations-file").unwrap();
+            let registry = match TranslationRegistry::load_or_builtin(registry_file) {
+                Ok(registry) => registry,
+                Err(e) => exit_with(BibleError::load(registry_file, &e), error_format),
+            };
+            match registry.resolve(&code) {
+                Ok(translation) => (translation.path.clone(), translation.name.clone().unwrap_or_else(|| translation.code.clone())),
+                Err(message) => exit_with(BibleError::UnknownTranslation(message), error_format),
+            }
+        }
+        // Fallback to the --file argument if no translation is selected
+        None => {
+            let file = config::path_setting(&matches, "file", config::BIBLE_FILE_VAR, config.file.clone()).unwrap();
+            (file.clone(), file)
+        }
     };
+    let bible_file = bible_file.as_str();
+
+    // --plain output is meant for pipes: no color and no status messages
+    let plain = matches.get_flag("plain");
+    let delimiter = matches.get_one::<Delimiter>("delimiter").copied().unwrap_or_default();
+    let use_color = if plain { ColorChoice::Never } else { color }.apply();
+
+    let format = matches.get_one::<String>("format").and_then(|f| OutputFormat::parse(f)).unwrap_or_default();
+    let book_list = matches.get_one::<String>("only-book-list").and_then(|detail| BookListDetail::parse(detail));
+    if format == OutputFormat::Csv && book_list.is_none() {
+        create_cli().error(clap::error::ErrorKind::ArgumentConflict, "--format csv is only available with --only-book-list").exit();
+    }
+    // Reports (--validate, --topic-profile, ...) are single documents, so jsonl means JSON there too
+    let json = format.is_json();
+    let refs_only = matches.get_flag("refs-only");
+    // Status messages would corrupt piped or JSON output, and --serve's responses
+    let quiet = plain || json || refs_only || book_list.is_some() || matches.get_flag("quiet") || matches.contains_id("exists") || matches.get_flag("serve");
 
-    let use_color = !matches.get_flag("no-color");
+    // Handle --validate; exits non-zero when the file has problems
+    if matches.get_flag("validate") {
+        match validate::validate_file(bible_file) {
+            Ok(report) => {
+                validate::print_report(&report, json);
+                if report.has_problems() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => exit_with(BibleError::load(bible_file, &e), error_format),
+        }
+        return;
+    }
+
+    // Handle --check-integrity; known textual omissions are reported but aren't errors
+    if matches.get_flag("check-integrity") {
+        match validate::check_file_integrity(bible_file) {
+            Ok(report) => {
+                validate::print_integrity_report(&report, json);
+                if report.has_errors() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => exit_with(BibleError::load(bible_file, &e), error_format),
+        }
+        return;
+    }
     
-    println!("Loading Bible from {}...", bible_file);
+    // `--file -` reads the Bible from stdin
+    let from_stdin = bible_file == "-";
+    if !quiet {
+        println!("Loading Bible from {}...", if from_stdin { "standard input" } else { bible_file });
+    }
     
+    // A directory is treated as one Bible split across several files
+    let loaded = if from_stdin {
+        json_parser::load_bible_report_reader_auto(std::io::stdin().lock(), delimiter)
+    } else {
+        json_parser::load_bible_report_auto(bible_file, delimiter)
+    };
+
     // Load all verses from the file into memory.
-    let bible = match json_parser::load_bible_auto(bible_file) {
-        Ok(verses) => {
-            println!("✅ Bible loaded successfully ({} verses).", verses.len());
-            verses
+    let (bible, embedded_synonyms) = match loaded {
+        Ok(mut load) => {
+            if !quiet {
+                for (name, count) in &load.loaded_files {
+                    println!("   Loaded {} ({} verses)", name, count);
+                }
+                for (name, reason) in &load.skipped_files {
+                    println!("   Skipped {}: {}", name, reason);
+                }
+            }
+            // A reference listed twice keeps its first text unless --allow-duplicate-refs
+            let allow_duplicates = matches.get_flag("allow-duplicate-refs");
+            for duplicate in &load.duplicates {
+                eprintln!("⚠️  Duplicate reference {} on line {} (first on line {}){}", duplicate.reference, duplicate.line,
+                    duplicate.first_line, if allow_duplicates { "" } else { ", skipped" });
+            }
+            let duplicates = load.duplicates.len();
+            if !allow_duplicates {
+                load.drop_duplicates();
+            }
+            if !quiet && duplicates > 0 {
+                println!("✅ Bible loaded successfully ({} verses, {} duplicate reference(s) {}).", load.verses.len(), duplicates,
+                    if allow_duplicates { "kept" } else { "skipped" });
+            } else if !quiet {
+                println!("✅ Bible loaded successfully ({} verses).", load.verses.len());
+            }
+            // Bracketed study notes stay part of the text unless they are asked for, and are
+            // only printed with --show-notes
+            let notes = NoteDisplay {
+                brackets: matches.get_one::<NoteBrackets>("note-brackets").copied().unwrap_or_default(),
+                show: matches.get_flag("show-notes"),
+            };
+            if matches.get_flag("search-notes") || notes.show {
+                extract_notes(&mut load.verses, notes.brackets);
+            }
+            (Bible::new(load.verses).with_note_display(notes), load.synonyms)
         }
         Err(e) => {
-            eprintln!("🔥 Error loading {}: {}", bible_file, e);
-            eprintln!("Please ensure the file exists and has the correct format.");
-            eprintln!("Supported formats: TAB-delimited text (.txt) or JSON (.json)");
-            return;
+            let error = BibleError::load(bible_file, &e);
+            error.report(error_format);
+            if error_format == ErrorFormat::Human {
+                eprintln!("Please ensure the file exists and has the correct format.");
+                eprintln!("Supported formats: tab/space-delimited text (.txt) or JSON (.json)");
+            }
+            std::process::exit(error.exit_code());
         }
     };
     
-    // Load synonyms from file
-    let synonym_mapper = match SynonymMapper::load_from_file(synonyms_file) {
-        Ok(mapper) => {
-            if mapper.get_synonym_count() > 0 {
-                println!("✅ Loaded {} synonym groups from {}", mapper.get_synonym_count(), synonyms_file);
-            } else {
-                println!("⚠️  No synonyms loaded from {}. Using exact word matching only.", synonyms_file);
+    // Load synonyms from file, unless the Bible brought its own
+    let mut synonym_mapper = match embedded_synonyms.as_ref().filter(|_| !synonyms_file_chosen) {
+        Some(groups) => {
+            let mapper = SynonymMapper::from_groups(groups.clone());
+            if !quiet {
+                println!("✅ Loaded {} synonym groups embedded in {}", mapper.get_synonym_count(), bible_file);
             }
             mapper
         }
-        Err(e) => {
-            println!("⚠️  Could not load synonyms file ({}): {}", synonyms_file, e);
-            println!("   Using exact word matching only.");
-            println!("   Run with --create-synonyms to create a default synonyms file.");
-            SynonymMapper::new()
+        None => load_synonyms_file(synonyms_file, quiet),
+    };
+
+    for word in matches.get_many::<String>("no-synonyms-for").into_iter().flatten() {
+        synonym_mapper.suppress(word);
+    }
+
+    // Other spellings of names, only loaded when asked for
+    let name_equivalents = if matches.get_flag("name-equiv") {
+        let names_file = matches.get_one::<String>("names-file").unwrap();
+        match NameEquivalents::load_or_builtin(names_file) {
+            Ok(names) => {
+                if !quiet {
+                    println!("✅ Loaded {} groups of equivalent names", names.len());
+                }
+                Some(names)
+            }
+            Err(e) => {
+                if !quiet {
+                    println!("⚠️  Could not load names file ({}): {}", names_file, e);
+                    println!("   Using the built-in name equivalents.");
+                }
+                Some(NameEquivalents::builtin())
+            }
         }
+    } else {
+        None
+    };
+
+    // Optional map from the numbering references are typed in to the Bible's own
+    let versification = match matches.get_one::<String>("versification") {
+        Some(file) => match Versification::load_from_file(file) {
+            Ok(map) => {
+                if !quiet {
+                    println!("✅ Loaded {} versification mappings from {}", map.len(), file);
+                }
+                Some(map)
+            }
+            Err(e) => exit_with(BibleError::load(file, &e), error_format),
+        },
+        None => None,
+    };
+    let map_reference = |reference: &String| -> String {
+        match versification.as_ref().and_then(|map| map.map_input(reference)) {
+            Some(mapped) => {
+                if !quiet {
+                    println!("🔁 {} is {} in this Bible's numbering", reference.trim(), mapped);
+                }
+                mapped
+            }
+            None => reference.clone(),
+        }
+    };
+
+    // With --book-glob the --book value is a pattern over book names rather than one book
+    let book_glob = matches.get_flag("book-glob");
+    let verse_filter = VerseFilter {
+        book: matches.get_one::<String>("book").filter(|_| !book_glob).cloned(),
+        book_pattern: matches.get_one::<String>("book").filter(|_| book_glob).map(|pattern| {
+            filter::book_pattern(pattern).unwrap_or_else(|e| {
+                let reason = format!("'{}' is not a valid --book glob ({})", pattern, e);
+                exit_with(BibleError::InvalidReference { reason, expected: "a glob like '1*' or '*John'" }, error_format)
+            })
+        }),
+        testament: matches.get_one::<String>("testament").and_then(|t| Testament::parse(t)),
+        exclude_books: matches.get_many::<String>("exclude-book").map(|b| b.cloned().collect()).unwrap_or_default(),
+        exclude_testament: matches.get_one::<String>("exclude-testament").and_then(|t| Testament::parse(t)),
+        reference_pattern: matches.get_one::<String>("ref-pattern").map(|pattern| {
+            filter::reference_pattern(pattern).unwrap_or_else(|e| {
+                let reason = format!("'{}' is not a valid --ref-pattern ({})", pattern, e);
+                exit_with(BibleError::InvalidReference { reason, expected: "a glob like 'Psalm*' or a regex starting with '^'" }, error_format)
+            })
+        }),
     };
 
     // Check if interactive mode is requested or no arguments provided
     if matches.get_flag("interactive") || 
-       (!matches.contains_id("search") && !matches.contains_id("reference") && 
-        !matches.get_flag("random") && !matches.contains_id("cross-references")) {
-        interactive_mode(&bible, &synonym_mapper);
+       (!matches.contains_id("search") && !matches.contains_id("chapter-search") &&
+        !matches.contains_id("starts-with") && !matches.contains_id("topic-profile") && !matches.contains_id("outline") && !matches.contains_id("memorize") && !matches.get_flag("vocab") &&
+        !matches.contains_id("number") && !matches.contains_id("number-range") &&
+        !matches.contains_id("reference") && !matches.contains_id("extract") && !matches.contains_id("exists") &&
+        !matches.get_flag("random") && !matches.get_flag("random-chapter") && !matches.contains_id("random-passage") &&
+        !matches.contains_id("shuffle") && !matches.get_flag("resume") && !matches.contains_id("set-position") &&
+        !matches.contains_id("cross-references") && !matches.contains_id("cross-references-chapter") && !matches.contains_id("xref-compare") && !matches.contains_id("find-like") &&
+        !matches.contains_id("xref-book") && !matches.get_flag("serve") && !matches.try_contains_id("http").unwrap_or(false) &&
+        !matches.get_flag("first") && !matches.get_flag("last")) {
+        // The menu reads its commands from stdin, which has already been read to the end
+        if from_stdin {
+            eprintln!("{} Interactive mode can't be used when the Bible is read from standard input (--file -).", "🔥".red());
+            std::process::exit(1);
+        }
+        // Registry for switching translations with `use <code>`
+        let registry_file = matches.get_one::<String>("translations-file").unwrap();
+        let registry = TranslationRegistry::load_or_builtin(registry_file).unwrap_or_else(|e| {
+            println!("⚠️  Could not load {}: {}. Using the built-in translations.", registry_file, e);
+            TranslationRegistry::builtin()
+        });
+        let bookmark_file = matches.get_one::<String>("bookmark-file").unwrap();
+        let mut bookmarks = Bookmarks::load(bookmark_file).unwrap_or_else(|e| {
+            println!("⚠️  Could not load bookmarks from {}: {}. Starting with none.", bookmark_file, e);
+            Bookmarks::new(bookmark_file)
+        });
+        // The translations `use` switches between, the Bible loaded already among them
+        let mut translations = TranslationCache::new(TRANSLATION_CACHE_SIZE);
+        let bible = translations.insert(bible_file, &title, bible, embedded_synonyms);
+        let synonyms_file = (!synonyms_file_chosen).then_some(synonyms_file.as_str());
+        interactive_mode(bible, translations, &mut synonym_mapper, synonyms_file, &registry, &mut bookmarks, &title);
         return;
     }
 
+    let search_options = SearchOptions {
+        use_synonyms: matches.get_flag("synonyms") || matches.get_flag("synonyms-first"),
+        synonyms_first: matches.get_flag("synonyms-first"),
+        case_sensitive: matches.get_flag("case-sensitive"),
+        match_case_exact: matches.get_flag("match-case-exact"),
+        filter: verse_filter.clone(),
+        limit,
+        use_color,
+        highlight_words: matches.get_flag("highlight-words"),
+        sort: SortOrder::parse(matches.get_one::<String>("sort").unwrap()).unwrap_or_default(),
+        show_score: matches.get_flag("show-score"),
+        min_terms: matches.get_one::<usize>("min-terms").copied(),
+        match_all: matches.get_flag("all"),
+        literal_exclusions: matches.get_flag("no-exclude-synonyms"),
+        plain,
+        delimiter,
+        refs_only,
+        format,
+        quiet,
+        names: name_equivalents,
+        book_list,
+        nth: matches.get_one::<usize>("nth").copied(),
+        compact: matches.get_flag("compact").then(|| bible_tool::pager::terminal_dimensions().1),
+        search_notes: matches.get_flag("search-notes"),
+        term_colors: !matches.get_flag("no-term-colors"),
+        max_per_chapter: matches.get_one::<usize>("max-results-per-chapter").copied(),
+        lemmatize: matches.get_flag("lemmatize"),
+        ..Default::default()
+    };
+
+    // Words compared by cross-references
+    let word_filter = WordFilter {
+        drop_stopwords: !matches.get_flag("no-stopwords"),
+        min_len: matches.get_one::<usize>("min-word-len").copied().unwrap_or(WordFilter::default().min_len),
+        lemmatize: matches.get_flag("lemmatize"),
+    };
+
     // Handle different command modes
-    if matches.get_flag("random") {
-        get_random_verse(&bible);
+    // How passages are laid out (--reference, --random-chapter, --random-passage, --shuffle)
+    let passage_style = if refs_only {
+        PassageStyle::References { format }
+    } else if json {
+        PassageStyle::Json { format }
+    } else if plain {
+        PassageStyle::Plain { delimiter }
+    } else if matches.get_flag("prose") {
+        PassageStyle::Prose
+    } else if let Some(when) = matches.get_one::<String>("inline-numbers") {
+        PassageStyle::Inline { always: when == "always" }
+    } else {
+        PassageStyle::Lines
+    };
+    // The same --seed always picks the same verse, chapter or passage
+    let mut rng = Rng::seeded(matches.get_one::<u64>("seed").copied());
+
+    #[cfg(feature = "server")]
+    if let Some(address) = matches.get_one::<String>("http") {
+        let served = bible_tool::http::serve_http(&bible, &synonym_mapper, address, &mut rng, |bound| {
+            let bound = bound.map_or(address.clone(), |bound| bound.to_string());
+            println!("{} Serving the API on http://{}", "✅".green(), bound);
+        });
+        if let Err(error) = served {
+            exit_with(error, error_format);
+        }
+        return;
+    }
+
+    let result = if matches.get_flag("serve") {
+        // Requests come on stdin, so the Bible can't
+        if from_stdin {
+            eprintln!("🔥 --serve reads its requests from standard input; give the Bible with --file");
+            std::process::exit(2);
+        }
+        serve::serve(&bible, &synonym_mapper, std::io::stdin().lock(), std::io::stdout().lock(), &mut rng)
+            .map(|_| ())
+            .map_err(|e| BibleError::WriteFailed { file: "standard output".to_string(), message: e.to_string() })
+    } else if matches.get_flag("random") {
+        get_random_verse(&bible, &verse_filter, &mut rng, plain.then_some(delimiter))
+    } else if matches.get_flag("random-chapter") {
+        print_random_passage(&bible, &verse_filter, None, &mut rng, passage_style, use_color)
+    } else if let Some(length) = matches.get_one::<usize>("random-passage") {
+        print_random_passage(&bible, &verse_filter, Some(*length), &mut rng, passage_style, use_color)
+    } else if let Some(n) = matches.get_one::<usize>("shuffle") {
+        print_shuffled_verses(&bible, &verse_filter, *n, &mut rng, passage_style, use_color)
+    } else if matches.get_flag("resume") || matches.contains_id("set-position") {
+        let file = matches.get_one::<String>("position-file").unwrap();
+        let set = matches.get_one::<String>("set-position").map(&map_reference);
+        reading::print_reading_cli(&bible, file, set.as_deref(), matches.get_flag("resume"), passage_style, use_color, quiet)
+    } else if matches.get_flag("first") || matches.get_flag("last") {
+        print_boundary_verse(&bible, &verse_filter, matches.get_flag("last"), plain.then_some(delimiter))
+    } else if let Some(reference) = matches.get_one::<String>("extract") {
+        let output = matches.get_one::<String>("output").unwrap();
+        export::extract_to_file(&bible, bible_file, &map_reference(reference), output).map(|count| {
+            println!("{} Wrote {} verses of {} to {}", "✅".green(), count, reference, output);
+        })
     } else if let Some(query) = matches.get_one::<String>("search") {
-        let use_synonyms = matches.get_flag("synonyms");
-        let case_sensitive = matches.get_flag("case-sensitive");
-        let book_filter = matches.get_one::<String>("book").map(|s| s.as_str());
-        let limit = matches.get_one::<usize>("limit").copied();
-        
-        search_bible_cli(&bible, &synonym_mapper, query, use_synonyms, case_sensitive, book_filter, limit, use_color);
+        search_bible_cli(&bible, &synonym_mapper, query, &search_options)
+    } else if let Some(query) = matches.get_one::<String>("chapter-search") {
+        chapter_search_cli(&bible, &synonym_mapper, query, &search_options)
+    } else if let Some(prefix) = matches.get_one::<String>("starts-with") {
+        starts_with_cli(&bible, prefix, &search_options)
+    } else if let Some(number) = matches.get_one::<u64>("number") {
+        number_search_cli(&bible, *number..=*number, &search_options)
+    } else if let Some(mut bounds) = matches.get_many::<u64>("number-range") {
+        let (low, high) = (*bounds.next().unwrap(), *bounds.next().unwrap());
+        number_search_cli(&bible, low.min(high)..=low.max(high), &search_options)
+    } else if matches.get_flag("vocab") {
+        vocab::print_vocabulary(&vocab::vocabulary(&bible, &verse_filter, word_filter, matches.get_flag("by-book")), json);
+        Ok(())
+    } else if let Some(book) = matches.get_one::<String>("outline") {
+        outline::print_outline_cli(&bible, book, json, bible_tool::pager::terminal_dimensions().1, use_color)
+    } else if let Some(keyword) = matches.get_one::<String>("topic-profile") {
+        // 20 words unless a limit is given; --limit 0 lists every word
+        let limit = match limit {
+            Some(0) => usize::MAX,
+            Some(limit) => limit,
+            None => 20,
+        };
+        let profile = topic::topic_profile(&bible, &synonym_mapper, keyword, &verse_filter, limit);
+        topic::print_topic_profile(&profile, json, use_color);
+        Ok(())
+    } else if let Some(keyword) = matches.get_one::<String>("memorize") {
+        // A handful unless a limit is given; --limit 0 lists every matching verse
+        let limit = match limit {
+            Some(0) => usize::MAX,
+            Some(limit) => limit,
+            None => 5,
+        };
+        let verses = topic::memory_verses(&bible, &synonym_mapper, keyword, &verse_filter, limit);
+        topic::print_memory_verses(&verses, keyword, format, bible.note_display(), use_color)
+    } else if let Some(reference) = matches.get_one::<String>("exists") {
+        // A scripting check, so only the exit status answers; a malformed reference is still an error
+        match parse_reference(&map_reference(reference)) {
+            Some(parsed) if bible.has_reference(&parsed, matches.get_flag("exists-any")) => Ok(()),
+            Some(_) => std::process::exit(1),
+            None => Err(BibleError::InvalidReference {
+                reason: invalid_reference_reason(reference),
+                expected: "'Book', 'Book Chapter', 'Book Chapter-Chapter', 'Book Chapter:Verse' or 'Book Chapter:Verse-Verse'",
+            }),
+        }
+    } else if let Some(reference) = matches.get_one::<String>("reference").filter(|_| matches.get_flag("canonical-ref")) {
+        print_canonical_reference(&bible, &map_reference(reference))
     } else if let Some(reference) = matches.get_one::<String>("reference") {
-        lookup_verse_cli(&bible, reference);
+        lookup_verse_cli(&bible, &map_reference(reference), passage_style, use_color)
     } else if let Some(reference) = matches.get_one::<String>("cross-references") {
-        let similarity_str = matches.get_one::<String>("similarity").unwrap();
-        let use_synonyms = matches.get_flag("use-synonyms-xref");
-        let limit = matches.get_one::<usize>("limit").copied();
-        
-        find_cross_references(&bible, &synonym_mapper, reference, similarity_str, use_synonyms, limit, use_color);
+        let options = xref_options(&matches, limit, word_filter);
+        let display = XrefDisplay { format, bars: matches.get_flag("bars"), use_color, show_keywords: matches.get_flag("show-keywords"), explain: matches.get_flag("explain-ranking") };
+        find_cross_references(&bible, &synonym_mapper, &map_reference(reference), &options, display)
+    } else if let Some(reference) = matches.get_one::<String>("cross-references-chapter") {
+        // Ten chapters unless asked otherwise; --limit 0 lists every chapter sharing anything
+        let options = XrefOptions { limit: limit.or(Some(10)), ..xref_options(&matches, limit, word_filter) };
+        let display = XrefDisplay { format, bars: matches.get_flag("bars"), use_color, ..Default::default() };
+        find_chapter_cross_references(&bible, &synonym_mapper, &map_reference(reference), &options, display)
+    } else if let Some(reference) = matches.get_one::<String>("xref-compare") {
+        let options = xref_options(&matches, limit, word_filter);
+        print_metric_comparison(&bible, &synonym_mapper, &map_reference(reference), &options, format, use_color)
+    } else if let Some(book) = matches.get_one::<String>("xref-book") {
+        // Ten per verse unless asked otherwise; --limit 0 keeps every match
+        let options = XrefOptions { limit: limit.or(Some(10)), ..xref_options(&matches, limit, word_filter) };
+        let output = matches.get_one::<String>("output").unwrap();
+        let corpus = XrefCorpus::new(bible.verses(), &synonym_mapper, &options);
+        let show_progress = std::io::stderr().is_terminal();
+        let written = export::xref_book_to_file(&corpus, book, &options, output, |done, total| {
+            if show_progress {
+                eprint!("\r{} {}/{} verses", similarity_bar(done as f32 / total as f32, 30, use_color), done, total);
+                if done == total {
+                    eprintln!();
+                }
+            }
+        });
+        written.map(|count| {
+            println!("{} Wrote cross-references for {} verses of {} to {}", "✅".green(), count, book, output);
+        })
+    } else if let Some(text) = matches.get_one::<String>("find-like") {
+        // Five matches unless asked for more; --limit 0 lists every verse that shares anything
+        let top = match matches.get_one::<usize>("top").copied().or(limit) {
+            Some(0) => None,
+            Some(top) => Some(top),
+            None => Some(5),
+        };
+        find_like_cli(&bible, text, top, format, matches.get_flag("bars"), use_color)
+    } else {
+        Ok(())
+    };
+
+    if let Err(error) = result {
+        exit_with(error, error_format);
     }
+}
+
+// The cross-reference options the command line gives (--similarity, --use-synonyms-xref,
+// --xref-scope, --top), with `limit` and `words` as already worked out
+fn xref_options(matches: &ArgMatches, limit: Option<usize>, words: WordFilter) -> XrefOptions {
+    XrefOptions {
+        similarity: matches.get_one::<String>("similarity").unwrap().clone(),
+        use_synonyms: matches.get_flag("use-synonyms-xref"),
+        scope: matches.get_one::<String>("xref-scope").and_then(|s| XrefScope::parse(s)).unwrap_or_default(),
+        limit,
+        top: matches.get_one::<usize>("top").copied(),
+        words,
+    }
+}
+
+// Load the synonyms file, falling back to exact word matching when it can't be read
+fn load_synonyms_file(synonyms_file: &str, quiet: bool) -> SynonymMapper {
+    match SynonymMapper::load_from_file(synonyms_file) {
+        Ok(mapper) => {
+            if quiet {
+                // Status messages are suppressed
+            } else if mapper.get_synonym_count() > 0 {
+                println!("✅ Loaded {} synonym groups from {}", mapper.get_synonym_count(), synonyms_file);
+            } else {
+                println!("⚠️  No synonyms loaded from {}. Using exact word matching only.", synonyms_file);
+            }
+            mapper
+        }
+        Err(e) => {
+            if !quiet {
+                println!("⚠️  Could not load synonyms file ({}): {}", synonyms_file, e);
+                println!("   Using exact word matching only.");
+                println!("   Run with --create-synonyms to create a default synonyms file.");
+            }
+            SynonymMapper::new()
+        }
+    }
+}
+
+// Report a failure in the requested format and exit with its code
+fn exit_with(error: BibleError, format: ErrorFormat) -> ! {
+    error.report(format);
+    std::process::exit(error.exit_code());
 }
\ No newline at end of file
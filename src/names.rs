@@ -0,0 +1,162 @@
+// names.rs
+// Equivalent spellings of names across books and translations (Elijah/Elias), for --name-equiv
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::bible::clean_line;
+
+/// File the name equivalents are loaded from when no --names-file is given
+pub const DEFAULT_NAMES_FILE: &str = "names.txt";
+
+/// The equivalents used when there is no names file, the same as the repository's `names.txt`
+pub const DEFAULT_NAMES: &str = include_str!("../names.txt");
+
+/// Groups of names that are spellings of the same name, loaded from lines like
+/// `elijah, elias`. Kept apart from synonyms: a name is expanded with `--name-equiv`
+/// whether or not `--synonyms` is on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NameEquivalents {
+    groups: Vec<Vec<String>>,
+}
+
+impl NameEquivalents {
+    /// Parse comma-separated groups, one per line. `#` starts a comment; names are
+    /// case-insensitive and a name listed in two groups joins them.
+    pub fn parse(content: &str) -> Self {
+        let mut names = NameEquivalents::default();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = clean_line(line, line_number);
+            let line = line.split('#').next().unwrap_or("");
+            let group: Vec<String> = line
+                .split(',')
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect();
+            if group.len() > 1 {
+                names.add_group(group);
+            }
+        }
+        names
+    }
+
+    pub fn load_from_file(filename: &str) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(filename)?))
+    }
+
+    /// The equivalents shipped with the tool
+    pub fn builtin() -> Self {
+        Self::parse(DEFAULT_NAMES)
+    }
+
+    /// Load a names file, falling back to the built-in equivalents when it doesn't exist
+    pub fn load_or_builtin(filename: &str) -> io::Result<Self> {
+        if Path::new(filename).exists() {
+            Self::load_from_file(filename)
+        } else {
+            Ok(Self::builtin())
+        }
+    }
+
+    /// Number of groups of equivalent names
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    // Add a group, merging it into any group that already shares a name
+    fn add_group(&mut self, group: Vec<String>) {
+        let mut merged = group;
+        self.groups.retain(|existing| {
+            if existing.iter().any(|name| merged.contains(name)) {
+                merged.extend(existing.iter().cloned());
+                false
+            } else {
+                true
+            }
+        });
+        merged.sort();
+        merged.dedup();
+        self.groups.push(merged);
+    }
+
+    /// The other spellings of `word`, empty when it isn't a listed name. They take the
+    /// capitalization of `word`, so case-sensitive searches still find them.
+    pub fn equivalents(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let Some(group) = self.groups.iter().find(|group| group.contains(&lower)) else {
+            return Vec::new();
+        };
+        let capitalized = word.chars().next().is_some_and(|c| c.is_uppercase());
+        group
+            .iter()
+            .filter(|name| **name != lower)
+            .map(|name| if capitalized { capitalize(name) } else { name.clone() })
+            .collect()
+    }
+
+    /// Add the equivalents of each query word to its group of alternatives
+    pub fn expand_groups(&self, groups: &mut [Vec<String>]) {
+        for group in groups.iter_mut() {
+            let mut added = Vec::new();
+            for term in group.iter() {
+                added.extend(self.equivalents(term));
+            }
+            for name in added {
+                if !group.contains(&name) {
+                    group.push(name);
+                }
+            }
+        }
+    }
+
+    /// Describe the names a query is widened with, like `elijah → elias`; `None` when the
+    /// query has no listed names
+    pub fn describe(&self, query: &str) -> Option<String> {
+        let expansions: Vec<String> = query
+            .split_whitespace()
+            .filter_map(|word| {
+                let names = self.equivalents(word);
+                (!names.is_empty()).then(|| format!("{} → {}", word, names.join(", ")))
+            })
+            .collect();
+        (!expansions.is_empty()).then(|| expansions.join("; "))
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_names() {
+        let names = NameEquivalents::parse("# comment\nElijah, Elias\nisaiah, esaias # Greek form\nnoah\njonah, jonas\njonas, iona\n");
+
+        assert_eq!(names.len(), 3);
+        assert_eq!(names.equivalents("elijah"), vec!["elias"]);
+        assert_eq!(names.equivalents("Esaias"), vec!["Isaiah"]);
+        // Groups sharing a name are merged
+        assert_eq!(names.equivalents("jonah"), vec!["iona", "jonas"]);
+        assert!(names.equivalents("noah").is_empty());
+        assert_eq!(names.describe("elijah fire"), Some("elijah → elias".to_string()));
+        assert_eq!(names.describe("fire"), None);
+    }
+
+    #[test]
+    fn test_builtin_names() {
+        let names = NameEquivalents::builtin();
+        assert!(names.equivalents("elijah").contains(&"elias".to_string()));
+        assert!(names.equivalents("jesus").is_empty());
+    }
+}
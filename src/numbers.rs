@@ -0,0 +1,169 @@
+// numbers.rs
+// Finding the numbers a verse mentions, as digits ("40") or in words ("forty", "two and forty",
+// "threescore and ten", "an hundred and twenty"), for --number and --number-range
+
+/// A number mentioned in a text, with its byte span
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberMention {
+    pub value: u64,
+    pub start: usize,
+    pub end: usize,
+}
+
+const ONES: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: &[&str] = &["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+// A word that is a number by itself: one to nineteen, the tens, and the KJV's scores
+// ("threescore" is sixty)
+fn word_value(word: &str) -> Option<u64> {
+    if let Some(index) = ONES.iter().position(|w| *w == word) {
+        return Some(index as u64);
+    }
+    if let Some(index) = TENS.iter().position(|w| !w.is_empty() && *w == word) {
+        return Some(index as u64 * 10);
+    }
+    match word {
+        "score" => Some(20),
+        "twoscore" => Some(40),
+        "threescore" => Some(60),
+        "fourscore" => Some(80),
+        _ => None,
+    }
+}
+
+fn multiplier(word: &str) -> Option<u64> {
+    match word {
+        "hundred" => Some(100),
+        "thousand" => Some(1_000),
+        _ => None,
+    }
+}
+
+// Words of a text with their byte spans; hyphens separate words ("forty-two")
+fn words(text: &str) -> Vec<(usize, usize, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(first)) => {
+                words.push((first, index, text[first..index].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Every number a text mentions, in order. Spelled-out numbers are read as whole phrases, so
+/// "seven hundred" is 700 (not 7) and "two and forty" is 42. Ordinals ("seventh") don't count.
+pub fn find_numbers(text: &str) -> Vec<NumberMention> {
+    let words = words(text);
+    let mut mentions = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let (start, _, ref word) = words[i];
+        if let Ok(value) = word.parse::<u64>() {
+            mentions.push(NumberMention { value, start, end: words[i].1 });
+            i += 1;
+            continue;
+        }
+        match read_phrase(&words[i..]) {
+            Some((value, used)) => {
+                mentions.push(NumberMention { value, start, end: words[i + used - 1].1 });
+                i += used;
+            }
+            None => i += 1,
+        }
+    }
+    mentions
+}
+
+// Read a spelled-out number at the start of `words`: its value and how many words it used
+fn read_phrase(words: &[(usize, usize, String)]) -> Option<(u64, usize)> {
+    let mut total = 0; // thousands already complete
+    let mut current = 0; // the part below a thousand
+    let mut used = 0;
+    let mut found = false;
+    let mut i = 0;
+
+    while i < words.len() {
+        let word = words[i].2.as_str();
+        let next = words.get(i + 1).map(|w| w.2.as_str());
+        if let Some(value) = word_value(word) {
+            let below_hundred = current % 100;
+            // "forty two" / "forty and two", "threescore and twelve", "two and forty", or a
+            // fresh start after a hundred
+            let fits = !found
+                || below_hundred == 0
+                || (value < 20 && below_hundred >= 20 && below_hundred % 10 == 0)
+                || (value >= 20 && value % 10 == 0 && below_hundred > 0 && below_hundred < 10);
+            if !fits {
+                break;
+            }
+            current += value;
+            found = true;
+            i += 1;
+            used = i;
+        } else if let Some(factor) = multiplier(word) {
+            // "an hundred", "a thousand" and a bare "hundred" all count one
+            let count = if current == 0 { 1 } else { current };
+            if factor == 1_000 {
+                total += count * 1_000;
+                current = 0;
+            } else if current < 100 {
+                current = count * 100;
+            } else {
+                break;
+            }
+            found = true;
+            i += 1;
+            used = i;
+        } else {
+            // "a"/"an" before a hundred or thousand, and "and" between number words, join a
+            // phrase without adding to it
+            let article = (word == "a" || word == "an") && !found && next.and_then(multiplier).is_some();
+            let joiner = word == "and" && found && next.is_some_and(|w| word_value(w).is_some() || multiplier(w).is_some());
+            if !article && !joiner {
+                break;
+            }
+            i += 1;
+        }
+    }
+    found.then_some((total + current, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<u64> {
+        find_numbers(text).iter().map(|m| m.value).collect()
+    }
+
+    #[test]
+    fn test_number_phrases() {
+        assert_eq!(values("And the rain was upon the earth forty days and forty nights."), vec![40, 40]);
+        assert_eq!(values("they shall tread under foot forty and two months"), vec![42]);
+        assert_eq!(values("Ahaziah was two and forty years old"), vec![42]);
+        assert_eq!(values("The days of our years are threescore years and ten"), vec![60, 10]);
+        assert_eq!(values("threescore and ten"), vec![70]);
+        assert_eq!(values("threescore and twelve thousand beeves"), vec![72_000]);
+        assert_eq!(values("an hundred and twenty years"), vec![120]);
+        assert_eq!(values("his number is Six hundred threescore and six."), vec![666]);
+        assert_eq!(values("of the tribe of Juda were sealed twelve thousand"), vec![12_000]);
+        assert_eq!(values("ten thousand times ten thousand"), vec![10_000, 10_000]);
+        assert_eq!(values("five loaves, and two fishes"), vec![5, 2]);
+        assert_eq!(values("seventy-seven fold, in 7 days"), vec![77, 7]);
+        // Ordinals and words containing numbers aren't numbers
+        assert!(values("on the seventh day, someone often came").is_empty());
+
+        let text = "about four thousand men";
+        let mention = find_numbers(text)[0];
+        assert_eq!(&text[mention.start..mention.end], "four thousand");
+    }
+}
@@ -0,0 +1,106 @@
+// outline.rs
+// A book's outline (--outline): each chapter with its opening verse as a teaser
+
+use colored::*;
+use serde::Serialize;
+use crate::bible::Bible;
+use crate::error::BibleError;
+use crate::output::fit_width;
+
+/// One chapter of an outline
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChapterOutline<'a> {
+    pub chapter: u32,
+    /// Number of verses in the chapter
+    pub verses: usize,
+    /// Reference of the chapter's first verse
+    pub reference: String,
+    /// Text of the chapter's first verse, in full
+    pub text: &'a str,
+}
+
+/// The chapters of a book, in order, with their first verses
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BookOutline<'a> {
+    pub book: String,
+    pub chapters: Vec<ChapterOutline<'a>>,
+}
+
+/// Outline a book (name or abbreviation) from the per-chapter index; `None` when the Bible
+/// doesn't have it
+pub fn book_outline<'a>(bible: &'a Bible, book: &str) -> Option<BookOutline<'a>> {
+    let mut numbers: Vec<u32> = bible.chapters(book).collect();
+    numbers.sort_unstable();
+    let chapters: Vec<ChapterOutline> = numbers
+        .into_iter()
+        .filter_map(|chapter| {
+            let verses = bible.chapter_verses(book, chapter);
+            let first = verses.first()?;
+            Some(ChapterOutline { chapter, verses: verses.len(), reference: first.reference(), text: &first.text })
+        })
+        .collect();
+    // The name as the Bible spells it, whatever abbreviation was asked for
+    let book = bible.chapter_verses(book, chapters.first()?.chapter)[0].book.clone();
+    Some(BookOutline { book, chapters })
+}
+
+/// The outline as text: a heading, then one line per chapter with its number and teaser, the
+/// teaser cut with `…` so each line fits `columns`
+pub fn format_outline(outline: &BookOutline, columns: usize, use_color: bool) -> String {
+    let count = outline.chapters.len();
+    let heading = format!("{} ({} chapter{})", outline.book, count, if count == 1 { "" } else { "s" });
+    let mut text = if use_color { heading.cyan().bold().to_string() } else { heading };
+    let number_width = outline.chapters.last().map_or(1, |c| c.chapter.to_string().len());
+    for chapter in &outline.chapters {
+        let number = format!("{:>width$}", chapter.chapter, width = number_width);
+        // Two spaces before the number and two after it
+        let (teaser, cut) = fit_width(chapter.text, columns.saturating_sub(number_width + 4));
+        let number = if use_color { number.cyan().to_string() } else { number };
+        text.push_str(&format!("\n  {}  {}{}", number, teaser, if cut { "…" } else { "" }));
+    }
+    text
+}
+
+// Print a book's outline (--outline), as text or JSON
+pub fn print_outline_cli(bible: &Bible, book: &str, json: bool, columns: usize, use_color: bool) -> Result<(), BibleError> {
+    let outline = book_outline(bible, book).ok_or_else(|| BibleError::NotFound { message: format!("{} not found.", book), nearest: Vec::new() })?;
+    if json {
+        match serde_json::to_string_pretty(&outline) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("🔥 Error serializing outline: {}", e),
+        }
+    } else {
+        println!("{}", format_outline(&outline, columns, use_color));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bible::Verse;
+
+    #[test]
+    fn test_outline_lists_chapters_in_order() {
+        let verse = |book: &str, chapter, number| Verse::new(book, chapter, number, format!("{} {} opens here, and goes on for a while.", book, chapter));
+        let mut verses = Vec::new();
+        for chapter in 1..=12 {
+            verses.extend((1..=3).map(|number| verse("Ecclesiastes", chapter, number)));
+        }
+        verses.push(verse("Song of Solomon", 1, 1));
+        let bible = Bible::new(verses);
+
+        let outline = book_outline(&bible, "eccl").unwrap();
+        assert_eq!(outline.book, "Ecclesiastes");
+        assert_eq!(outline.chapters.iter().map(|c| c.chapter).collect::<Vec<_>>(), (1..=12).collect::<Vec<_>>());
+        assert_eq!((outline.chapters[9].verses, outline.chapters[9].reference.as_str()), (3, "Ecclesiastes 10:1"));
+        assert!(book_outline(&bible, "Job").is_none());
+
+        let text = format_outline(&outline, 40, false);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Ecclesiastes (12 chapters)");
+        assert_eq!(lines[1], "   1  Ecclesiastes 1 opens here, and go…");
+        assert_eq!(lines[12], "  12  Ecclesiastes 12 opens here, and g…");
+        assert!(lines.iter().all(|line| line.chars().count() <= 40));
+    }
+}
@@ -0,0 +1,395 @@
+// output.rs
+// Machine-readable results (--format json / jsonl / csv) shared by searches, lookups and cross-references
+
+use std::collections::HashSet;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::bible::{NoteDisplay, Verse};
+
+/// How results are written (--format)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document for the whole result set
+    Json,
+    /// One compact JSON object per result, one per line (JSON Lines)
+    JsonLines,
+    /// Comma-separated values with a header row, for spreadsheets (--only-book-list)
+    Csv,
+}
+
+impl OutputFormat {
+    /// Parse a `--format` value: `text`, `json`, `jsonl` or `csv`
+    pub fn parse(s: &str) -> Option<OutputFormat> {
+        match s.to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "jsonl" => Some(OutputFormat::JsonLines),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// JSON of either kind, for output meant for other programs
+    pub fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::JsonLines)
+    }
+}
+
+/// When output is colored (--color)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color when writing to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Parse a `--color` value: `auto`, `always` or `never`
+    pub fn parse(s: &str) -> Option<ColorChoice> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// Whether to color, given what `Auto` would decide
+    pub fn resolve(self, auto: bool) -> bool {
+        match self {
+            ColorChoice::Auto => auto,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Resolve the choice (`Auto` by the `colored` crate's terminal and `NO_COLOR` detection) and
+    /// override the crate to match, so everything printed follows it. Returns whether color is on.
+    pub fn apply(self) -> bool {
+        let enabled = self.resolve(colored::control::SHOULD_COLORIZE.should_colorize());
+        colored::control::set_override(enabled);
+        enabled
+    }
+}
+
+/// A verse as written in JSON output
+#[derive(Debug, Clone, Serialize)]
+pub struct VerseRecord<'a> {
+    pub reference: String,
+    pub book: &'a str,
+    pub chapter: u32,
+    pub verse: u32,
+    pub text: &'a str,
+    /// Similarity to the source verse, for cross-references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// Study notes taken out of the text, when they are shown (--show-notes)
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub notes: &'a [String],
+    /// Where the search terms matched in the text, for search results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<MatchSpan>,
+}
+
+impl<'a> VerseRecord<'a> {
+    /// The record of a verse, with its notes when `notes` shows them
+    pub fn new(verse: &'a Verse, notes: NoteDisplay) -> Self {
+        VerseRecord {
+            reference: verse.reference(),
+            book: &verse.book,
+            chapter: verse.chapter,
+            verse: verse.verse,
+            text: &verse.text,
+            score: None,
+            notes: if notes.show { &verse.notes } else { &[] },
+            matches: Vec::new(),
+        }
+    }
+}
+
+/// A search term's match in a verse's text. Offsets count characters, not bytes, so they index
+/// the text the same way in any language; `end` is exclusive.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MatchSpan {
+    pub term: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The start of `text` that fits in `width` characters once a `…` is added, and whether it
+/// had to be cut; text that fits is returned whole
+pub fn fit_width(text: &str, width: usize) -> (&str, bool) {
+    let width = width.max(1);
+    match text.char_indices().nth(width - 1) {
+        Some((end, _)) if text.chars().count() > width => (text[..end].trim_end(), true),
+        _ => (text, false),
+    }
+}
+
+/// How widely a result set is spread through the Bible
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResultSummary {
+    pub verses: usize,
+    /// Distinct books
+    pub books: usize,
+    /// Distinct chapters, counting e.g. John 1 and 1 John 1 separately
+    pub chapters: usize,
+}
+
+impl ResultSummary {
+    pub fn of<'a>(verses: impl IntoIterator<Item = &'a Verse>) -> Self {
+        Self::from_chapters(verses.into_iter().map(|verse| (verse.book.as_str(), verse.chapter)))
+    }
+
+    // Summarize from the book and chapter of each verse
+    fn from_chapters<'a>(chapters_of_verses: impl Iterator<Item = (&'a str, u32)>) -> Self {
+        let mut books = HashSet::new();
+        let mut chapters = HashSet::new();
+        let mut count = 0;
+        for (book, chapter) in chapters_of_verses {
+            books.insert(book);
+            chapters.insert((book, chapter));
+            count += 1;
+        }
+        ResultSummary { verses: count, books: books.len(), chapters: chapters.len() }
+    }
+}
+
+impl fmt::Display for ResultSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+        write!(f, "Matched {} across {} and {}.", plural(self.verses, "verse"), plural(self.books, "book"), plural(self.chapters, "chapter"))
+    }
+}
+
+// The JSON document for a result set; a struct rather than `json!` keeps the fields in order
+#[derive(Serialize)]
+struct Results<'a> {
+    count: usize,
+    // Every verse a search matched, however many of them are shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
+    summary: ResultSummary,
+    results: Vec<VerseRecord<'a>>,
+}
+
+/// Write records in a JSON format: one document, `{"count": 2, "results": [{...}, {...}]}`,
+/// that can be parsed in one go, or with `JsonLines` one compact object per line
+pub fn format_records(records: Vec<VerseRecord>, format: OutputFormat) -> String {
+    if format == OutputFormat::JsonLines {
+        return records
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    write_results(records, None)
+}
+
+/// `format_records` for a search that matched `total` verses, which --limit and
+/// --max-results-per-chapter can make more than the records: the JSON document also carries
+/// `"total"` (JSON Lines has nowhere to put it)
+pub fn format_records_with_total(records: Vec<VerseRecord>, total: usize, format: OutputFormat) -> String {
+    if format == OutputFormat::JsonLines {
+        return format_records(records, format);
+    }
+    write_results(records, Some(total))
+}
+
+fn write_results(records: Vec<VerseRecord>, total: Option<usize>) -> String {
+    let summary = ResultSummary::from_chapters(records.iter().map(|record| (record.book, record.chapter)));
+    serde_json::to_string_pretty(&Results { count: records.len(), total, summary, results: records }).unwrap_or_default()
+}
+
+/// `format_records` for plain verses, with their notes when `notes` shows them
+pub fn format_results(verses: &[&Verse], format: OutputFormat, notes: NoteDisplay) -> String {
+    format_records(verses.iter().map(|verse| VerseRecord::new(verse, notes)).collect(), format)
+}
+
+/// How much --only-book-list breaks the matches down
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BookListDetail {
+    /// One line per book
+    #[default]
+    Books,
+    /// One line per chapter
+    Chapters,
+}
+
+impl BookListDetail {
+    pub fn parse(s: &str) -> Option<BookListDetail> {
+        match s.to_lowercase().as_str() {
+            "books" => Some(BookListDetail::Books),
+            "chapters" => Some(BookListDetail::Chapters),
+            _ => None,
+        }
+    }
+}
+
+/// Matched verses in one chapter of a book list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChapterCount {
+    pub chapter: u32,
+    pub verses: usize,
+}
+
+/// Matched verses in one book, with the chapters they are in when listing chapters
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookCount<'a> {
+    pub book: &'a str,
+    pub verses: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<ChapterCount>,
+}
+
+/// Count matched verses per book (and per chapter with `BookListDetail::Chapters`), books and
+/// chapters in the order they are first matched, which is Bible order for search results
+pub fn book_counts<'a>(verses: impl IntoIterator<Item = &'a Verse>, detail: BookListDetail) -> Vec<BookCount<'a>> {
+    let mut counts: Vec<BookCount> = Vec::new();
+    for verse in verses {
+        let index = match counts.iter().position(|count| count.book == verse.book) {
+            Some(index) => index,
+            None => {
+                counts.push(BookCount { book: &verse.book, verses: 0, chapters: Vec::new() });
+                counts.len() - 1
+            }
+        };
+        let count = &mut counts[index];
+        count.verses += 1;
+        if detail == BookListDetail::Chapters {
+            match count.chapters.iter_mut().find(|c| c.chapter == verse.chapter) {
+                Some(chapter) => chapter.verses += 1,
+                None => count.chapters.push(ChapterCount { chapter: verse.chapter, verses: 1 }),
+            }
+        }
+    }
+    counts
+}
+
+/// Write a book list: `Book<TAB>count` lines (`Book Chapter<TAB>count` when it lists chapters),
+/// CSV with a header row, or JSON like the other results (`{"count": 2, "books": [...]}`)
+pub fn format_book_list(counts: &[BookCount], format: OutputFormat) -> String {
+    let with_chapters = counts.iter().any(|count| !count.chapters.is_empty());
+    let rows = || {
+        counts.iter().flat_map(move |count| {
+            let chapters: Vec<Option<&ChapterCount>> = if with_chapters { count.chapters.iter().map(Some).collect() } else { vec![None] };
+            chapters.into_iter().map(move |chapter| (count, chapter))
+        })
+    };
+
+    match format {
+        OutputFormat::Text => rows()
+            .map(|(count, chapter)| match chapter {
+                Some(chapter) => format!("{} {}\t{}", count.book, chapter.chapter, chapter.verses),
+                None => format!("{}\t{}", count.book, count.verses),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Csv => {
+            let header = if with_chapters { "book,chapter,verses" } else { "book,verses" };
+            let lines = rows().map(|(count, chapter)| match chapter {
+                Some(chapter) => format!("{},{},{}", csv_field(count.book), chapter.chapter, chapter.verses),
+                None => format!("{},{}", csv_field(count.book), count.verses),
+            });
+            std::iter::once(header.to_string()).chain(lines).collect::<Vec<_>>().join("\n")
+        }
+        OutputFormat::JsonLines => counts
+            .iter()
+            .map(|count| serde_json::to_string(count).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Json => {
+            #[derive(Serialize)]
+            struct BookList<'a> {
+                count: usize,
+                books: &'a [BookCount<'a>],
+            }
+            serde_json::to_string_pretty(&BookList { count: counts.len(), books: counts }).unwrap_or_default()
+        }
+    }
+}
+
+// Quote a CSV field when it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_results_are_one_json_value() {
+        let verses = [
+            Verse::new("John", 11, 35, "Jesus wept."),
+            Verse::new("1 Thessalonians", 5, 16, "Rejoice evermore."),
+        ];
+        let refs: Vec<&Verse> = verses.iter().collect();
+
+        let parsed: serde_json::Value = serde_json::from_str(&format_results(&refs, OutputFormat::Json, NoteDisplay::default())).unwrap();
+
+        assert_eq!(parsed["count"], 2);
+        assert_eq!(parsed["results"][0]["reference"], "John 11:35");
+        assert_eq!(parsed["results"][1]["book"], "1 Thessalonians");
+        assert_eq!(parsed["results"][1]["text"], "Rejoice evermore.");
+        assert!(parsed["results"][0].get("score").is_none());
+        assert_eq!(parsed["summary"], serde_json::json!({"verses": 2, "books": 2, "chapters": 2}));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&format_results(&[], OutputFormat::Json, NoteDisplay::default())).unwrap()["count"], 0);
+    }
+
+    #[test]
+    fn test_json_lines_parse_one_by_one() {
+        let verses = [
+            Verse::new("John", 11, 35, "Jesus wept."),
+            Verse::new("Luke", 19, 41, "He beheld the city, and wept over it,"),
+        ];
+        let mut records: Vec<VerseRecord> = verses.iter().map(|verse| VerseRecord::new(verse, NoteDisplay::default())).collect();
+        records[1].score = Some(0.25);
+
+        let output = format_records(records, OutputFormat::JsonLines);
+        let lines: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["reference"], "John 11:35");
+        assert_eq!(lines[1]["score"], 0.25);
+    }
+
+    #[test]
+    fn test_result_summary() {
+        let verse = |book: &str, chapter: u32, number: u32| Verse::new(book, chapter, number, "");
+        let verses = [verse("John", 1, 1), verse("John", 1, 14), verse("John", 3, 16), verse("1 John", 1, 1)];
+
+        let summary = ResultSummary::of(&verses);
+
+        assert_eq!(summary, ResultSummary { verses: 4, books: 2, chapters: 3 });
+        assert_eq!(summary.to_string(), "Matched 4 verses across 2 books and 3 chapters.");
+        assert_eq!(ResultSummary::of(&verses[..1]).to_string(), "Matched 1 verse across 1 book and 1 chapter.");
+    }
+
+    #[test]
+    fn test_book_list() {
+        let verse = |book: &str, chapter: u32, number: u32| Verse::new(book, chapter, number, "");
+        let verses = [verse("Genesis", 1, 1), verse("Psalms", 19, 1), verse("Psalms", 19, 6), verse("Psalms", 33, 6), verse("Song of Solomon", 2, 1)];
+
+        let books = book_counts(&verses, BookListDetail::Books);
+        assert_eq!(format_book_list(&books, OutputFormat::Text), "Genesis\t1\nPsalms\t3\nSong of Solomon\t1");
+        assert_eq!(format_book_list(&books, OutputFormat::Csv), "book,verses\nGenesis,1\nPsalms,3\nSong of Solomon,1");
+
+        let chapters = book_counts(&verses, BookListDetail::Chapters);
+        assert_eq!(format_book_list(&chapters, OutputFormat::Csv).lines().nth(2), Some("Psalms,19,2"));
+        let parsed: serde_json::Value = serde_json::from_str(&format_book_list(&chapters, OutputFormat::Json)).unwrap();
+        assert_eq!(parsed["count"], 3);
+        assert_eq!(parsed["books"][1], serde_json::json!({"book": "Psalms", "verses": 3, "chapters": [{"chapter": 19, "verses": 2}, {"chapter": 33, "verses": 1}]}));
+        assert_eq!(csv_field("Book, The"), "\"Book, The\"");
+    }
+}
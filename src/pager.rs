@@ -0,0 +1,113 @@
+// pager.rs
+// Simple built-in pager for long result lists in interactive mode
+
+use std::io::{self, Write};
+use regex::Regex;
+use lazy_static::lazy_static;
+
+// Fallback size when the terminal can't be queried (e.g. output is piped)
+const DEFAULT_ROWS: usize = 24;
+const DEFAULT_COLUMNS: usize = 80;
+
+/// Height and width of the terminal, or a conventional 24x80 default
+pub fn terminal_dimensions() -> (usize, usize) {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(w), terminal_size::Height(h))) => (h as usize, w as usize),
+        None => (DEFAULT_ROWS, DEFAULT_COLUMNS),
+    }
+}
+
+// Number of screen rows an entry occupies once wrapped, ignoring color escape codes
+fn display_rows(entry: &str, columns: usize) -> usize {
+    lazy_static! {
+        static ref ANSI_RE: Regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    }
+    let columns = columns.max(1);
+    ANSI_RE
+        .replace_all(entry, "")
+        .lines()
+        .map(|line| line.chars().count().max(1).div_ceil(columns))
+        .sum::<usize>()
+        .max(1)
+}
+
+/// Split entries into pages that fit `rows` screen rows, leaving one row for the prompt.
+/// Returns the number of entries on each page; an entry taller than the screen gets a page of its own.
+pub fn paginate(entries: &[String], rows: usize, columns: usize) -> Vec<usize> {
+    let available = rows.saturating_sub(1).max(1);
+    let mut pages = Vec::new();
+    let mut count = 0;
+    let mut used = 0;
+
+    for entry in entries {
+        let height = display_rows(entry, columns);
+        if count > 0 && used + height > available {
+            pages.push(count);
+            count = 0;
+            used = 0;
+        }
+        count += 1;
+        used += height;
+    }
+    if count > 0 {
+        pages.push(count);
+    }
+    pages
+}
+
+/// Print entries a page at a time, prompting between pages.
+/// Enter (or space) shows the next page, `q` stops.
+pub fn page_entries(entries: &[String]) {
+    let (rows, columns) = terminal_dimensions();
+    let pages = paginate(entries, rows, columns);
+    let mut start = 0;
+
+    for (index, count) in pages.iter().enumerate() {
+        for entry in &entries[start..start + count] {
+            println!("{}", entry);
+        }
+        start += count;
+
+        if index + 1 < pages.len() {
+            print!("-- more (space/q) --");
+            io::stdout().flush().unwrap();
+
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).expect("Failed to read line");
+            if answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_short_entries() {
+        let entries: Vec<String> = (0..10).map(|i| format!("entry {}", i)).collect();
+        // 4 rows leaves 3 for entries
+        assert_eq!(paginate(&entries, 4, 80), vec![3, 3, 3, 1]);
+    }
+
+    #[test]
+    fn test_paginate_wrapped_entries() {
+        let long = "x".repeat(150);
+        let entries = vec![long.clone(), "short".to_string(), long];
+        // The 150-char entries take two rows at 80 columns
+        assert_eq!(paginate(&entries, 4, 80), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_color_codes_do_not_count_towards_width() {
+        let colored = format!("\x1b[36m{}\x1b[0m", "x".repeat(80));
+        assert_eq!(display_rows(&colored, 80), 1);
+    }
+
+    #[test]
+    fn test_paginate_empty() {
+        assert!(paginate(&[], 24, 80).is_empty());
+    }
+}
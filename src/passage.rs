@@ -0,0 +1,187 @@
+// passage.rs
+// Rendering of multi-verse passages (chapters, ranges) for reading
+
+use colored::*;
+use crate::bible::{Delimiter, NoteDisplay, Verse};
+use crate::output::{format_results, OutputFormat};
+
+/// How a passage of several verses is laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PassageStyle {
+    /// One `Book Chapter:Verse text` line per verse
+    #[default]
+    Lines,
+    /// Each chapter as one flowing paragraph with superscript verse numbers
+    Prose,
+    /// Uncolored `Book Chapter:Verse<TAB>text` lines, loadable as a text Bible; `delimiter`
+    /// replaces the tab
+    Plain { delimiter: Delimiter },
+    /// Only the `Book Chapter:Verse` references, one per line, as a JSON array or as JSON Lines
+    References { format: OutputFormat },
+    /// The verses as JSON (see `output::format_results`); `format` is `Json` or `JsonLines`
+    Json { format: OutputFormat },
+    /// One block with bracketed verse numbers (`[16] For God... [17] For God sent...`);
+    /// a single verse is printed as a line unless `always` is set
+    Inline { always: bool },
+}
+
+/// Render a verse number with Unicode superscript digits (16 -> ¹⁶)
+pub fn superscript(number: u32) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    number
+        .to_string()
+        .chars()
+        .map(|c| DIGITS[c.to_digit(10).unwrap() as usize])
+        .collect()
+}
+
+/// Join verses into paragraphs, one per chapter, each headed by its `Book Chapter`
+pub fn format_prose(verses: &[&Verse], notes: NoteDisplay, use_color: bool) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current: Option<(&str, u32)> = None;
+    let mut paragraph = String::new();
+
+    for verse in verses {
+        if current != Some((verse.book.as_str(), verse.chapter)) {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            let heading = format!("{} {}", verse.book, verse.chapter);
+            paragraph.push_str(&if use_color { heading.cyan().bold().to_string() } else { heading });
+            paragraph.push('\n');
+            current = Some((verse.book.as_str(), verse.chapter));
+        } else {
+            paragraph.push(' ');
+        }
+        paragraph.push_str(&superscript(verse.verse));
+        paragraph.push_str(&verse.shown_text(notes));
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    paragraphs.join("\n\n")
+}
+
+/// Join verses into one block with bracketed verse numbers. A new chapter is marked
+/// `[Chapter:Verse]` and a new book `[Book Chapter:Verse]`.
+pub fn format_inline(verses: &[&Verse], notes: NoteDisplay) -> String {
+    let mut block = String::new();
+    let mut previous: Option<&Verse> = None;
+
+    for verse in verses {
+        let number = match previous {
+            Some(p) if p.book == verse.book && p.chapter == verse.chapter => verse.verse.to_string(),
+            Some(p) if p.book == verse.book => format!("{}:{}", verse.chapter, verse.verse),
+            Some(_) => format!("{} {}:{}", verse.book, verse.chapter, verse.verse),
+            None => verse.verse.to_string(),
+        };
+        if previous.is_some() {
+            block.push(' ');
+        }
+        block.push_str(&format!("[{}] {}", number, verse.shown_text(notes)));
+        previous = Some(verse);
+    }
+    block
+}
+
+/// List the references of verses, without their text: one per line, a JSON array of strings,
+/// or one JSON string per line
+pub fn format_references(verses: &[&Verse], format: OutputFormat) -> String {
+    let references: Vec<String> = verses.iter().map(|v| v.reference()).collect();
+    match format {
+        OutputFormat::Text | OutputFormat::Csv => references.join("\n"),
+        OutputFormat::Json => serde_json::to_string_pretty(&references).unwrap_or_default(),
+        OutputFormat::JsonLines => references.iter().map(|r| serde_json::to_string(r).unwrap_or_default()).collect::<Vec<_>>().join("\n"),
+    }
+}
+
+/// Print a passage in the requested style, with the verses' notes when `notes` shows them
+pub fn print_passage(verses: &[&Verse], style: PassageStyle, notes: NoteDisplay, use_color: bool) {
+    match style {
+        PassageStyle::Lines => {
+            for verse in verses {
+                println!("{}", verse.shown(notes));
+            }
+        }
+        PassageStyle::Prose => println!("{}", format_prose(verses, notes, use_color)),
+        PassageStyle::References { format } => println!("{}", format_references(verses, format)),
+        PassageStyle::Json { format } => println!("{}", format_results(verses, format, notes)),
+        PassageStyle::Inline { always: false } if verses.len() == 1 => println!("{}", verses[0].shown(notes)),
+        PassageStyle::Inline { .. } => println!("{}", format_inline(verses, notes)),
+        PassageStyle::Plain { delimiter } => {
+            for verse in verses {
+                println!("{}", verse.delimited_line(delimiter, notes));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verse(chapter: u32, verse: u32, text: &str) -> Verse {
+        Verse::new("John", chapter, verse, text)
+    }
+
+    #[test]
+    fn test_superscript() {
+        assert_eq!(superscript(1), "¹");
+        assert_eq!(superscript(16), "¹⁶");
+        assert_eq!(superscript(109), "¹⁰⁹");
+    }
+
+    #[test]
+    fn test_format_prose_single_chapter() {
+        let verses = [verse(3, 16, "For God so loved the world."), verse(3, 17, "For God sent not his Son.")];
+        let refs: Vec<&Verse> = verses.iter().collect();
+
+        assert_eq!(
+            format_prose(&refs, NoteDisplay::default(), false),
+            "John 3\n¹⁶For God so loved the world. ¹⁷For God sent not his Son."
+        );
+    }
+
+    #[test]
+    fn test_format_prose_starts_a_paragraph_per_chapter() {
+        let verses = [verse(1, 51, "Hereafter ye shall see."), verse(2, 1, "And the third day.")];
+        let refs: Vec<&Verse> = verses.iter().collect();
+
+        assert_eq!(
+            format_prose(&refs, NoteDisplay::default(), false),
+            "John 1\n⁵¹Hereafter ye shall see.\n\nJohn 2\n¹And the third day."
+        );
+    }
+
+    #[test]
+    fn test_format_inline() {
+        let verses = [verse(3, 16, "For God so loved the world."), verse(3, 17, "For God sent not his Son."), verse(4, 1, "When therefore the Lord knew.")];
+        let refs: Vec<&Verse> = verses.iter().collect();
+
+        assert_eq!(
+            format_inline(&refs[..2], NoteDisplay::default()),
+            "[16] For God so loved the world. [17] For God sent not his Son."
+        );
+        assert_eq!(
+            format_inline(&refs, NoteDisplay::default()),
+            "[16] For God so loved the world. [17] For God sent not his Son. [4:1] When therefore the Lord knew."
+        );
+        // A single verse only gets a number when forced; print_passage prints it as a line otherwise
+        assert_eq!(format_inline(&refs[..1], NoteDisplay::default()), "[16] For God so loved the world.");
+    }
+
+    #[test]
+    fn test_format_references_omits_text() {
+        let verses = [verse(3, 16, "For God so loved the world."), verse(3, 17, "For God sent not his Son.")];
+        let refs: Vec<&Verse> = verses.iter().collect();
+
+        let lines = format_references(&refs, OutputFormat::Text);
+        assert_eq!(lines, "John 3:16\nJohn 3:17");
+
+        let json = format_references(&refs, OutputFormat::Json);
+        let parsed: Vec<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, vec!["John 3:16", "John 3:17"]);
+        assert!(!lines.contains("God") && !json.contains("God"));
+        assert_eq!(format_references(&refs, OutputFormat::JsonLines), "\"John 3:16\"\n\"John 3:17\"");
+    }
+}
@@ -0,0 +1,161 @@
+// random.rs
+// Picking random verses, chapters and passages (--random, --random-chapter, --random-passage,
+// --shuffle), reproducibly when a --seed is given
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::bible::{Bible, Verse};
+use crate::filter::VerseFilter;
+
+/// A small seedable random number generator (SplitMix64). The same seed always gives the same
+/// sequence, on every platform, so a `--seed` picks the same passage each time.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Seeded from the clock, for a different pick on each run
+    pub fn from_time() -> Self {
+        Rng::new(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0))
+    }
+
+    /// `Rng::new` with a seed, otherwise `Rng::from_time`
+    pub fn seeded(seed: Option<u64>) -> Self {
+        seed.map(Rng::new).unwrap_or_else(Rng::from_time)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`; `n` must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A random verse passing `filter`, `None` when no verse does
+pub fn random_verse<'a>(bible: &'a [Verse], filter: &VerseFilter, rng: &mut Rng) -> Option<&'a Verse> {
+    let candidates: Vec<&Verse> = bible.iter().filter(|v| filter.matches(v)).collect();
+    (!candidates.is_empty()).then(|| candidates[rng.below(candidates.len())])
+}
+
+/// `n` distinct random verses passing `filter`, in random order; all of them when fewer than
+/// `n` pass. A partial Fisher-Yates shuffle over the candidates' indices: only the first `n`
+/// places are shuffled, however many verses there are.
+pub fn shuffled_verses<'a>(bible: &'a [Verse], filter: &VerseFilter, n: usize, rng: &mut Rng) -> Vec<&'a Verse> {
+    let mut candidates: Vec<usize> = (0..bible.len()).filter(|&i| filter.matches(&bible[i])).collect();
+    let n = n.min(candidates.len());
+    for i in 0..n {
+        let j = i + rng.below(candidates.len() - i);
+        candidates.swap(i, j);
+    }
+    candidates[..n].iter().map(|&i| &bible[i]).collect()
+}
+
+/// A random whole chapter, chosen from the chapters with a verse passing `filter` (so
+/// `--book Psalms` picks a psalm); each chapter is equally likely
+pub fn random_chapter<'a>(bible: &'a Bible, filter: &VerseFilter, rng: &mut Rng) -> Option<&'a [Verse]> {
+    let chapters: Vec<&[Verse]> = bible
+        .books()
+        .flat_map(|book| bible.chapters(book).map(move |chapter| bible.chapter_verses(book, chapter)))
+        .filter(|verses| verses.iter().any(|v| filter.matches(v)))
+        .collect();
+    (!chapters.is_empty()).then(|| chapters[rng.below(chapters.len())])
+}
+
+/// `n` consecutive verses from a random verse passing `filter`, kept within its chapter.
+/// A start too near the end of the chapter moves back so that `n` verses still fit; a chapter
+/// shorter than `n` is given whole.
+pub fn random_passage<'a>(bible: &'a Bible, filter: &VerseFilter, n: usize, rng: &mut Rng) -> Option<&'a [Verse]> {
+    let start = random_verse(bible, filter, rng)?;
+    let chapter = bible.chapter_verses(&start.book, start.chapter);
+    let position = chapter.iter().position(|v| v.verse == start.verse)?;
+    let first = position.min(chapter.len().saturating_sub(n.max(1)));
+    Some(&chapter[first..(first + n.max(1)).min(chapter.len())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::books::Testament;
+
+    fn bible() -> Bible {
+        let mut verses = Vec::new();
+        for (book, chapters) in [("Genesis", 3), ("Psalms", 5), ("John", 4)] {
+            for chapter in 1..=chapters {
+                for verse in 1..=10 {
+                    verses.push(Verse::new(book, chapter, verse, format!("{} {}:{}", book, chapter, verse)));
+                }
+            }
+        }
+        Bible::new(verses)
+    }
+
+    #[test]
+    fn test_seeded_picks_are_reproducible() {
+        let bible = bible();
+        let filter = VerseFilter::default();
+        let pick = |seed| random_passage(&bible, &filter, 4, &mut Rng::new(seed)).unwrap().to_vec();
+
+        assert_eq!(pick(7), pick(7));
+        assert!((1..20).any(|seed| pick(seed) != pick(7)), "different seeds should give different passages");
+        for seed in 0..50 {
+            let passage = pick(seed);
+            assert_eq!(passage.len(), 4);
+            assert!(passage.iter().all(|v| v.book == passage[0].book && v.chapter == passage[0].chapter));
+            assert!(passage.windows(2).all(|w| w[1].verse == w[0].verse + 1));
+        }
+        // A chapter shorter than the passage is given whole
+        assert_eq!(random_passage(&bible, &filter, 25, &mut Rng::new(3)).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_shuffle_gives_distinct_verses() {
+        let bible = bible();
+        let filter = VerseFilter::default();
+        let shuffle = |n, seed| -> Vec<String> { shuffled_verses(&bible, &filter, n, &mut Rng::new(seed)).iter().map(|v| v.reference()).collect() };
+
+        for seed in 0..20 {
+            let mut references = shuffle(25, seed);
+            assert_eq!(references.len(), 25);
+            references.sort();
+            references.dedup();
+            assert_eq!(references.len(), 25, "no verse is picked twice");
+        }
+        // The same seed gives the same verses in the same order
+        assert_eq!(shuffle(10, 42), shuffle(10, 42));
+        assert_ne!(shuffle(10, 42), shuffle(10, 43));
+        // Asking for more verses than pass the filter gives each of them once
+        let john = VerseFilter { book: Some("jn".to_string()), ..Default::default() };
+        let mut all: Vec<String> = shuffled_verses(&bible, &john, 50, &mut Rng::new(1)).iter().map(|v| v.reference()).collect();
+        assert_eq!(all.len(), 40);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 40);
+        assert!(all.iter().all(|reference| reference.starts_with("John ")));
+    }
+
+    #[test]
+    fn test_random_chapter_respects_filter() {
+        let bible = bible();
+        let psalms = VerseFilter { book: Some("ps".to_string()), ..Default::default() };
+        let new_testament = VerseFilter { testament: Some(Testament::New), ..Default::default() };
+
+        for seed in 0..20 {
+            let chapter = random_chapter(&bible, &psalms, &mut Rng::new(seed)).unwrap();
+            assert_eq!((chapter[0].book.as_str(), chapter.len()), ("Psalms", 10));
+            assert_eq!(random_verse(&bible, &new_testament, &mut Rng::new(seed)).unwrap().book, "John");
+        }
+        let missing = VerseFilter { book: Some("Jude".to_string()), ..Default::default() };
+        assert!(random_chapter(&bible, &missing, &mut Rng::new(1)).is_none());
+    }
+}
@@ -0,0 +1,171 @@
+// reading.rs
+// A reading position kept between runs (--resume, --set-position): the last chapter read, so
+// each --resume prints the chapter after it
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use colored::*;
+use crate::bible::{clean_line, Bible, Verse};
+use crate::books::resolve_book;
+use crate::error::BibleError;
+use crate::passage::{print_passage, PassageStyle};
+use crate::reference::{canonicalize_reference, invalid_reference_reason, parse_reference, Reference};
+
+/// File the reading position is kept in when no --position-file is given
+pub const DEFAULT_POSITION_FILE: &str = "reading_position.txt";
+
+/// The last chapter read, stored in canonical form (`John 3`) as the one line of its file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadingPosition {
+    file: String,
+    last: Option<(String, u32)>,
+}
+
+impl ReadingPosition {
+    /// Nothing read yet, to be saved to `file`
+    pub fn new(file: &str) -> Self {
+        ReadingPosition { file: file.to_string(), last: None }
+    }
+
+    /// Load the position from `file`; a missing file means nothing has been read yet. Blank
+    /// lines and `#` comments are skipped, and the first reference found is the position.
+    pub fn load(file: &str) -> io::Result<Self> {
+        let mut position = ReadingPosition::new(file);
+        if !Path::new(file).exists() {
+            return Ok(position);
+        }
+        for (index, line) in fs::read_to_string(file)?.lines().enumerate() {
+            let line = clean_line(line, index).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if position.set(line).is_ok() {
+                break;
+            }
+        }
+        Ok(position)
+    }
+
+    /// Record a chapter as the last one read, however it is typed (`jn 3`). A verse stands for
+    /// its chapter and a chapter range for its last chapter. Returns the chapter in canonical form.
+    pub fn set(&mut self, input: &str) -> Result<String, BibleError> {
+        let invalid = || BibleError::InvalidReference {
+            reason: invalid_reference_reason(input),
+            expected: "a chapter of a known book, like 'John 3'",
+        };
+        let canonical = canonicalize_reference(input).ok_or_else(invalid)?;
+        let (book, chapter) = match parse_reference(&canonical).ok_or_else(invalid)? {
+            Reference::Book(book) => {
+                return Err(BibleError::InvalidReference { reason: format!("'{}' has no chapter", book), expected: "a chapter of a known book, like 'John 3'" })
+            }
+            Reference::Chapter(book, chapter) | Reference::Verse(book, chapter, _) | Reference::Range(book, chapter, _, _) => (book, chapter),
+            Reference::Chapters(book, _, last) => (book, last),
+        };
+        let reference = format!("{} {}", book, chapter);
+        self.last = Some((book, chapter));
+        Ok(reference)
+    }
+
+    /// The last chapter read, as `Book Chapter`
+    pub fn last(&self) -> Option<String> {
+        self.last.as_ref().map(|(book, chapter)| format!("{} {}", book, chapter))
+    }
+
+    /// Move on to the chapter after the last one read, across book boundaries, and return its
+    /// verses. With nothing read yet, or after the last chapter of the Bible, reading starts
+    /// from the first chapter. An error when the last book read isn't in this Bible.
+    pub fn advance<'a>(&mut self, bible: &'a Bible) -> Result<&'a [Verse], BibleError> {
+        let next = match &self.last {
+            Some((book, chapter)) => {
+                if bible.chapters(book).next().is_none() {
+                    return Err(BibleError::NotFound { message: format!("{} not found.", book), nearest: Vec::new() });
+                }
+                bible.next_chapter(book, *chapter)
+            }
+            None => None,
+        };
+        let Some(chapter) = next.or_else(|| bible.first_chapter()).filter(|verses| !verses.is_empty()) else {
+            return Err(BibleError::NoResults { message: "The Bible has no verses to read.".to_string(), hint: None });
+        };
+        let book = &chapter[0].book;
+        self.last = Some((resolve_book(book).unwrap_or(book).to_string(), chapter[0].chapter));
+        Ok(chapter)
+    }
+
+    /// Write the position back to its file
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(&self.file, self.last().map(|last| last + "\n").unwrap_or_default())
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+}
+
+// Update the reading position in `file`: record `set` as the last chapter read (--set-position),
+// then with `resume` print the next chapter and record that instead (--resume). `quiet` leaves
+// out the status line.
+pub fn print_reading_cli(bible: &Bible, file: &str, set: Option<&str>, resume: bool, style: PassageStyle, use_color: bool, quiet: bool) -> Result<(), BibleError> {
+    let mut position = ReadingPosition::load(file).map_err(|e| BibleError::LoadFailed { file: file.to_string(), message: e.to_string() })?;
+    if let Some(reference) = set {
+        let reference = position.set(reference)?;
+        if !resume && !quiet {
+            println!("{} Reading position set to {} (--resume reads on from the next chapter)", "✅".green(), reference);
+        }
+    }
+    if resume {
+        let verses: Vec<&Verse> = position.advance(bible)?.iter().collect();
+        print_passage(&verses, style, bible.note_display(), use_color);
+    }
+    position.save().map_err(|e| BibleError::WriteFailed { file: file.to_string(), message: e.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_moves_on_across_books() {
+        let verse = |book: &str, chapter, number| Verse::new(book, chapter, number, format!("{} {}:{}", book, chapter, number));
+        let bible = Bible::new(vec![
+            verse("Jude", 1, 1),
+            verse("Jude", 1, 2),
+            verse("Revelation", 1, 1),
+            verse("Revelation", 2, 1),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("position.txt");
+        let file = file.to_str().unwrap();
+
+        let mut position = ReadingPosition::load(file).unwrap();
+        assert_eq!(position.last(), None);
+        // Nothing read yet starts at the beginning
+        assert_eq!(position.advance(&bible).unwrap().len(), 2);
+        assert_eq!(position.last().as_deref(), Some("Jude 1"));
+        // Past the end of a book into the next one, then round to the start again
+        assert_eq!(position.advance(&bible).unwrap()[0].reference(), "Revelation 1:1");
+        assert_eq!(position.advance(&bible).unwrap()[0].reference(), "Revelation 2:1");
+        assert_eq!(position.advance(&bible).unwrap()[0].reference(), "Jude 1:1");
+        position.save().unwrap();
+        assert_eq!(ReadingPosition::load(file).unwrap(), position);
+
+        assert_eq!(position.set("rev 1:3").unwrap(), "Revelation 1");
+        assert_eq!(position.advance(&bible).unwrap()[0].reference(), "Revelation 2:1");
+        assert_eq!(position.set("Jude").unwrap_err().kind(), "InvalidReference");
+        position.set("John 3").unwrap();
+        assert_eq!(position.advance(&bible).unwrap_err().kind(), "NotFound");
+    }
+
+    #[test]
+    fn test_resume_follows_bible_order() {
+        // Books and chapters stored out of order are still read in Bible order
+        let verse = |book: &str, chapter| Verse::new(book, chapter, 1, "text");
+        let bible = Bible::new(vec![verse("Revelation", 1), verse("Jude", 1), verse("Genesis", 2), verse("Genesis", 1)]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut position = ReadingPosition::load(dir.path().join("position.txt").to_str().unwrap()).unwrap();
+
+        let read: Vec<String> = (0..5).map(|_| position.advance(&bible).unwrap()[0].reference()).collect();
+        assert_eq!(read, ["Genesis 1:1", "Genesis 2:1", "Jude 1:1", "Revelation 1:1", "Genesis 1:1"]);
+    }
+}
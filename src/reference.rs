@@ -0,0 +1,287 @@
+// reference.rs
+// Parsing of user-typed references like "John", "John 3", "Matthew 5-7", "John 3:16" or "John 3:16-18"
+
+use std::borrow::Cow;
+use regex::Regex;
+use lazy_static::lazy_static;
+use crate::bible::Verse;
+use crate::books::resolve_book;
+use crate::translations::BUILTIN_TRANSLATIONS;
+
+/// A parsed reference: a whole book, a whole chapter, a run of chapters, a single verse or a verse range
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    Book(String),
+    Chapter(String, u32),
+    /// Book, first chapter, last chapter (inclusive)
+    Chapters(String, u32, u32),
+    Verse(String, u32, u32),
+    /// Book, chapter, first verse, last verse (inclusive)
+    Range(String, u32, u32, u32),
+}
+
+impl Reference {
+    /// Canonical book name, or the name as typed when it isn't a known book
+    pub fn book(&self) -> &str {
+        match self {
+            Reference::Book(book)
+            | Reference::Chapter(book, _)
+            | Reference::Chapters(book, _, _)
+            | Reference::Verse(book, _, _)
+            | Reference::Range(book, _, _, _) => book,
+        }
+    }
+
+    /// Check whether a verse falls within this reference
+    pub fn contains(&self, verse: &Verse) -> bool {
+        let book = resolve_book(&verse.book).unwrap_or(&verse.book);
+        if !book.eq_ignore_ascii_case(self.book()) {
+            return false;
+        }
+        match *self {
+            Reference::Book(_) => true,
+            Reference::Chapter(_, chapter) => verse.chapter == chapter,
+            Reference::Chapters(_, first, last) => verse.chapter >= first && verse.chapter <= last,
+            Reference::Verse(_, chapter, number) => verse.chapter == chapter && verse.verse == number,
+            Reference::Range(_, chapter, start, end) => {
+                verse.chapter == chapter && verse.verse >= start && verse.verse <= end
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Reference::Book(book) => write!(f, "{}", book),
+            Reference::Chapter(book, chapter) => write!(f, "{} {}", book, chapter),
+            Reference::Chapters(book, first, last) => write!(f, "{} {}-{}", book, first, last),
+            Reference::Verse(book, chapter, verse) => write!(f, "{} {}:{}", book, chapter, verse),
+            Reference::Range(book, chapter, start, end) => write!(f, "{} {}:{}-{}", book, chapter, start, end),
+        }
+    }
+}
+
+/// Parse a reference in one of the forms `Book`, `Book Chapter`, `Book Chapter-Chapter`,
+/// `Book Chapter:Verse` or `Book Chapter:Verse-Verse`, optionally followed by a translation tag (`John 3:16 KJV`)
+/// which is ignored
+///
+/// Numbered books (`1 John 4:8`), multi-word books (`Song of Solomon 2`) and abbreviations
+/// (`Jn 3:16`) are supported; known books are stored under their canonical name. Loose
+/// punctuation is tolerated: `Jn 3.16` and `John 3 : 16` read as `John 3:16`, and so do
+/// `John3:16` and `1Kings3:16` with no space before the chapter.
+/// Returns `None` when the input doesn't look like a reference.
+pub fn parse_reference(input: &str) -> Option<Reference> {
+    lazy_static! {
+        static ref REFERENCE_RE: Regex = Regex::new(
+            r"^(?P<book>(?:[1-3]\s*)?[A-Za-z][A-Za-z ]*?)(?:\s+(?P<chapter>\d+)(?::(?P<verse>\d+)(?:\s*-\s*(?P<end>\d+))?|\s*-\s*(?P<last>\d+))?)?$"
+        ).unwrap();
+    }
+
+    let input = normalize_punctuation(&split_book_and_chapter(input.trim()));
+    let caps = REFERENCE_RE.captures(strip_translation_tag(&input))?;
+    let typed = caps["book"].trim();
+    let book = resolve_book(typed).unwrap_or(typed).to_string();
+
+    let number = |name: &str| caps.name(name).map(|m| m.as_str().parse::<u32>());
+    if let (Some(first), Some(last)) = (number("chapter"), number("last")) {
+        let (first, last) = (first.ok()?, last.ok()?);
+        if last < first {
+            return None;
+        }
+        return Some(Reference::Chapters(book, first, last));
+    }
+    match (number("chapter"), number("verse"), number("end")) {
+        (None, _, _) => Some(Reference::Book(book)),
+        (Some(chapter), None, _) => Some(Reference::Chapter(book, chapter.ok()?)),
+        (Some(chapter), Some(verse), None) => Some(Reference::Verse(book, chapter.ok()?, verse.ok()?)),
+        (Some(chapter), Some(start), Some(end)) => {
+            let (start, end) = (start.ok()?, end.ok()?);
+            if end < start {
+                return None;
+            }
+            Some(Reference::Range(book, chapter.ok()?, start, end))
+        }
+    }
+}
+
+// Tidy the chapter/verse separator: "3.16" and "3 : 16" become "3:16"
+fn normalize_punctuation(input: &str) -> String {
+    lazy_static! {
+        static ref SEPARATOR_RE: Regex = Regex::new(r"(\d)\s*[:.]\s*(\d)").unwrap();
+    }
+    SEPARATOR_RE.replace_all(input, "$1:$2").into_owned()
+}
+
+// Put a space between a book and its chapter when there is none: "John3:16" becomes
+// "John 3:16". Only the first letter-to-digit boundary counts, so the leading number of a
+// numbered book ("1Kings3:16") stays with the book.
+fn split_book_and_chapter(input: &str) -> Cow<'_, str> {
+    lazy_static! {
+        static ref BOUNDARY_RE: Regex = Regex::new(r"([A-Za-z])(\d)").unwrap();
+    }
+    BOUNDARY_RE.replace(input, "$1 $2")
+}
+
+/// Normalize a reference for citing: the full book name and standard spacing, however it was
+/// typed (`jn 3.16` and `John 3 : 16` both give `John 3:16`). `None` when the input isn't a
+/// reference to a known book.
+pub fn canonicalize_reference(input: &str) -> Option<String> {
+    let reference = parse_reference(input)?;
+    resolve_book(reference.book())?;
+    Some(reference.to_string())
+}
+
+// Drop a translation tag after the chapter or verse number: "John 3:16 KJV", "Ps 23 (esv)".
+// A tag is an upper-case abbreviation or one of the built-in translation codes, so other
+// trailing words are still rejected.
+fn strip_translation_tag(input: &str) -> &str {
+    lazy_static! {
+        static ref TAG_RE: Regex = Regex::new(r"^(?P<reference>.*\d)\s+(?P<tag>\(?[A-Za-z]{2,6}\)?)$").unwrap();
+    }
+
+    let Some(caps) = TAG_RE.captures(input) else {
+        return input;
+    };
+    let tag = caps["tag"].trim_start_matches('(').trim_end_matches(')');
+    let is_tag = tag.chars().all(|c| c.is_ascii_uppercase())
+        || BUILTIN_TRANSLATIONS.iter().any(|(code, ..)| code.eq_ignore_ascii_case(tag));
+    if is_tag {
+        caps.name("reference").unwrap().as_str()
+    } else {
+        input
+    }
+}
+
+/// Explain why `input` isn't a reference, pointing at the part that couldn't be understood
+pub fn invalid_reference_reason(input: &str) -> String {
+    let input = input.trim();
+    if input.is_empty() {
+        return "no reference given".to_string();
+    }
+
+    // The longest leading run of words that is a chapter, verse or range reference
+    let words: Vec<&str> = input.split_whitespace().collect();
+    for end in (1..words.len()).rev() {
+        let prefix = words[..end].join(" ");
+        if matches!(parse_reference(&prefix), Some(r) if !matches!(r, Reference::Book(_))) {
+            return format!("unexpected '{}' after '{}'", words[end..].join(" "), prefix);
+        }
+    }
+    format!("'{}' doesn't look like a reference", input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_book_only() {
+        assert_eq!(parse_reference("John"), Some(Reference::Book("John".to_string())));
+        assert_eq!(parse_reference("1 John"), Some(Reference::Book("1 John".to_string())));
+        assert_eq!(parse_reference("Song of Solomon"), Some(Reference::Book("Song of Solomon".to_string())));
+    }
+
+    #[test]
+    fn test_parse_chapter() {
+        assert_eq!(parse_reference("John 3"), Some(Reference::Chapter("John".to_string(), 3)));
+        assert_eq!(parse_reference("  Psalm 119 "), Some(Reference::Chapter("Psalms".to_string(), 119)));
+    }
+
+    #[test]
+    fn test_parse_chapter_range() {
+        assert_eq!(parse_reference("Matthew 5-7"), Some(Reference::Chapters("Matthew".to_string(), 5, 7)));
+        assert_eq!(parse_reference("Ps 1 - 2 KJV"), Some(Reference::Chapters("Psalms".to_string(), 1, 2)));
+        assert_eq!(parse_reference("Matthew 7-5"), None);
+
+        let verse = Verse::new("Matthew", 6, 9, "");
+        assert!(Reference::Chapters("Matthew".to_string(), 5, 7).contains(&verse));
+        assert!(!Reference::Chapters("Matthew".to_string(), 7, 8).contains(&verse));
+    }
+
+    #[test]
+    fn test_parse_verse() {
+        assert_eq!(parse_reference("John 3:16"), Some(Reference::Verse("John".to_string(), 3, 16)));
+        assert_eq!(parse_reference("1 John 4:8"), Some(Reference::Verse("1 John".to_string(), 4, 8)));
+        assert_eq!(parse_reference("Jn 3:16"), Some(Reference::Verse("John".to_string(), 3, 16)));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_reference("John 3:16-18"), Some(Reference::Range("John".to_string(), 3, 16, 18)));
+        assert_eq!(parse_reference("Ps 23:1 - 6"), Some(Reference::Range("Psalms".to_string(), 23, 1, 6)));
+        // Backwards ranges are rejected
+        assert_eq!(parse_reference("John 3:18-16"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse_reference(""), None);
+        assert_eq!(parse_reference("3:16"), None);
+        assert_eq!(parse_reference("John 3:"), None);
+        assert_eq!(parse_reference("John :16"), None);
+    }
+
+    #[test]
+    fn test_missing_space_before_chapter() {
+        assert_eq!(parse_reference("John3:16"), Some(Reference::Verse("John".to_string(), 3, 16)));
+        assert_eq!(parse_reference("1Kings3:16"), Some(Reference::Verse("1 Kings".to_string(), 3, 16)));
+        assert_eq!(parse_reference("Ps23"), Some(Reference::Chapter("Psalms".to_string(), 23)));
+        assert_eq!(parse_reference("Matthew5-7"), Some(Reference::Chapters("Matthew".to_string(), 5, 7)));
+        // The spaced forms read the same as before
+        assert_eq!(parse_reference("John 3:16"), parse_reference("John3:16"));
+        assert_eq!(parse_reference("1 Kings 3:16"), parse_reference("1Kings3:16"));
+        assert_eq!(parse_reference("1Kings 3:16"), parse_reference("1Kings3:16"));
+    }
+
+    #[test]
+    fn test_trailing_translation_tag() {
+        assert_eq!(parse_reference("John 3:16 KJV"), Some(Reference::Verse("John".to_string(), 3, 16)));
+        assert_eq!(parse_reference("Ps 23 (esv)"), Some(Reference::Chapter("Psalms".to_string(), 23)));
+        assert_eq!(parse_reference("John 3:16-18 nasb\n"), Some(Reference::Range("John".to_string(), 3, 16, 18)));
+    }
+
+    #[test]
+    fn test_trailing_junk() {
+        assert_eq!(parse_reference("John 3:16 extra"), None);
+        assert_eq!(parse_reference("John 3:16 KJV please"), None);
+        assert_eq!(invalid_reference_reason("John 3:16 extra"), "unexpected 'extra' after 'John 3:16'");
+        assert_eq!(invalid_reference_reason("John 3:16 KJV please"), "unexpected 'please' after 'John 3:16 KJV'");
+        assert_eq!(invalid_reference_reason("3:16"), "'3:16' doesn't look like a reference");
+        assert_eq!(invalid_reference_reason("  \n"), "no reference given");
+    }
+
+    #[test]
+    fn test_canonicalize_reference() {
+        assert_eq!(canonicalize_reference("jn 3.16").as_deref(), Some("John 3:16"));
+        assert_eq!(canonicalize_reference("John 3 : 16").as_deref(), Some("John 3:16"));
+        assert_eq!(canonicalize_reference("  JOHN   3:16 ").as_deref(), Some("John 3:16"));
+        assert_eq!(canonicalize_reference("1jn 4:8").as_deref(), Some("1 John 4:8"));
+        assert_eq!(canonicalize_reference("ps 23.1 - 6").as_deref(), Some("Psalms 23:1-6"));
+        assert_eq!(canonicalize_reference("song of songs 2").as_deref(), Some("Song of Solomon 2"));
+        assert_eq!(canonicalize_reference("Rev 22:21 KJV").as_deref(), Some("Revelation 22:21"));
+        // Unknown books and non-references have no canonical form
+        assert_eq!(canonicalize_reference("Hezekiah 1:1"), None);
+        assert_eq!(canonicalize_reference("3:16"), None);
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for input in ["John", "John 3", "Matthew 5-7", "1 John 4:8", "John 3:16-18"] {
+            assert_eq!(parse_reference(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let verse = Verse::new("John", 3, 16, "For God so loved the world...");
+
+        assert!(Reference::Book("john".to_string()).contains(&verse));
+        assert!(Reference::Chapter("John".to_string(), 3).contains(&verse));
+        assert!(Reference::Verse("John".to_string(), 3, 16).contains(&verse));
+        assert!(!Reference::Verse("John".to_string(), 3, 17).contains(&verse));
+        assert!(!Reference::Book("1 John".to_string()).contains(&verse));
+        assert!(Reference::Range("John".to_string(), 3, 14, 16).contains(&verse));
+        assert!(!Reference::Range("John".to_string(), 3, 17, 18).contains(&verse));
+    }
+}
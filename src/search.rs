@@ -0,0 +1,1452 @@
+// search.rs
+// Text search over verses: matching, relevance scoring and CLI output
+
+use std::cmp::Reverse;
+use std::ops::RangeInclusive;
+use std::collections::{BinaryHeap, HashMap};
+use colored::*;
+use crate::bible::{Bible, Delimiter, NoteBrackets, NoteDisplay, Verse};
+use crate::error::BibleError;
+use crate::filter::VerseFilter;
+use crate::names::NameEquivalents;
+use crate::numbers::find_numbers;
+use crate::highlight::{apply_highlights, apply_term_highlights, find_case_match_spans, find_group_spans, format_legend, merge_group_spans};
+use crate::pager::page_entries;
+use crate::output::{book_counts, fit_width, format_book_list, format_records_with_total, format_results, BookListDetail, MatchSpan, OutputFormat, ResultSummary, VerseRecord};
+use crate::passage::format_references;
+use crate::similarity::{filtered_tokens, WordFilter};
+use crate::synonyms::{describe_expansion, SynonymMapper};
+
+/// Order in which search results are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortOrder {
+    /// Bible order (the order verses were loaded in)
+    #[default]
+    Canonical,
+    /// Highest relevance score first, ties in Bible order
+    Relevance,
+}
+
+impl SearchOptions {
+    // Output for pipes or other programs: results only, no status lines
+    fn bare(&self) -> bool {
+        self.plain || self.refs_only || self.format.is_json() || self.book_list.is_some()
+    }
+
+    /// The most results to return; `None` when unlimited, which a limit of 0 also means
+    pub fn max_results(&self) -> Option<usize> {
+        self.limit.filter(|&limit| limit > 0)
+    }
+}
+
+impl SortOrder {
+    pub fn parse(s: &str) -> Option<SortOrder> {
+        match s.to_lowercase().as_str() {
+            "canonical" => Some(SortOrder::Canonical),
+            "relevance" => Some(SortOrder::Relevance),
+            _ => None,
+        }
+    }
+}
+
+// Options controlling a CLI search
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub use_synonyms: bool,
+    // With `use_synonyms`, expand only the first query word that has synonyms and keep the
+    // rest as typed (--synonyms-first)
+    pub synonyms_first: bool,
+    pub case_sensitive: bool,
+    pub filter: VerseFilter,
+    // Most results to print; 0 means no limit, like `None`
+    pub limit: Option<usize>,
+    pub use_color: bool,
+    // Highlight the whole word when a term only matches part of it
+    pub highlight_words: bool,
+    pub sort: SortOrder,
+    pub show_score: bool,
+    // Minimum number of distinct query words a verse must contain (1 = any, all = every word)
+    pub min_terms: Option<usize>,
+    // Require every query word (AND); overrides `min_terms`
+    pub match_all: bool,
+    // Verses containing any of these terms never match (`-word` in a query)
+    pub exclude_terms: Vec<String>,
+    // Exclude only the `-word` itself, not its synonyms, when searching with synonyms
+    pub literal_exclusions: bool,
+    // Page long result lists (interactive mode)
+    pub paged: bool,
+    // Print only `Book Chapter:Verse<TAB>Text` lines, with no color or status messages
+    pub plain: bool,
+    // What separates the reference from the text in plain lines (--delimiter)
+    pub delimiter: Delimiter,
+    // Print only the references of the results, with no text or status messages
+    pub refs_only: bool,
+    // Print the results as JSON or JSON Lines (with `refs_only`, just the references)
+    pub format: OutputFormat,
+    // Leave the books/chapters summary out of the footer
+    pub quiet: bool,
+    // Also search for other spellings of names in the query (--name-equiv)
+    pub names: Option<NameEquivalents>,
+    // Print only the matched books (or chapters) with their verse counts (--only-book-list)
+    pub book_list: Option<BookListDetail>,
+    // Match query words written with capitals in exactly that case ("LORD" but not "Lord"),
+    // the rest of the query ignoring case (--match-case-exact)
+    pub match_case_exact: bool,
+    // Print only the result at this 1-based position, after sorting and the limit (--nth)
+    pub nth: Option<usize>,
+    // Print each result on one line, references in a column and the text cut to fit this many
+    // columns (--compact)
+    pub compact: Option<usize>,
+    // Search the verses' study notes instead of their text (--search-notes)
+    pub search_notes: bool,
+    // Highlight each query word, with its synonyms, in its own color and print a legend when
+    // there are several (the CLI's default; off with --no-term-colors)
+    pub term_colors: bool,
+    // Show at most this many matching verses from any one chapter, before the limit; the
+    // footer still counts every match (--max-results-per-chapter)
+    pub max_per_chapter: Option<usize>,
+    // Match query words by their dictionary form, whole words only: "love" finds "loved" and
+    // "loveth" (--lemmatize)
+    pub lemmatize: bool,
+}
+
+/// A verse matched by a search, with its relevance score
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    pub verse: &'a Verse,
+    /// Fraction (0.0-1.0) of the distinct query words found in the verse.
+    /// A query word counts as found when it, or any of its synonyms, appears.
+    pub score: f32,
+    /// Where the search terms matched in the text. Only recorded for JSON output (see
+    /// `SearchOptions::format`), which reports them; empty otherwise.
+    pub matches: Vec<MatchSpan>,
+}
+
+/// Split a query into one group of alternatives per distinct query word.
+/// Without synonyms each group is just the word itself. Excluded `-words` are skipped.
+/// With synonyms, a multi-word synonym (`most high`) is one word, matched as a phrase, and
+/// `first_only` expands only the first word that has synonyms (see `expand_query`).
+pub fn query_groups(synonym_mapper: &SynonymMapper, query: &str, use_synonyms: bool, first_only: bool) -> Vec<Vec<String>> {
+    if use_synonyms {
+        // Expanded as a whole query, so `first_only` sees which word comes first
+        let included: Vec<String> = synonym_mapper.split_phrases(query).into_iter().filter(|word| !word.starts_with('-')).collect();
+        return synonym_mapper.expand_query(&included.join(" "), first_only).into_iter().map(|(_, terms)| terms).collect();
+    }
+
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    for word in query.split_whitespace().filter(|word| !word.starts_with('-')) {
+        if !groups.iter().any(|group| group[0] == word) {
+            groups.push(vec![word.to_string()]);
+        }
+    }
+    groups
+}
+
+/// The word each of `query_groups`' groups stands for, in the same order: the query word, or
+/// the multi-word synonym, as the query has it
+pub fn query_labels(synonym_mapper: &SynonymMapper, query: &str, use_synonyms: bool) -> Vec<String> {
+    if use_synonyms {
+        return synonym_mapper.expand_query(&included_words(query), false).into_iter().map(|(word, _)| word).collect();
+    }
+    query_groups(synonym_mapper, query, false, false).into_iter().map(|mut group| group.remove(0)).collect()
+}
+
+/// The terms a query excludes: each `-word`, expanded to its synonyms when `use_synonyms`
+pub fn excluded_terms(synonym_mapper: &SynonymMapper, query: &str, use_synonyms: bool) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in query.split_whitespace().filter_map(|w| w.strip_prefix('-')).filter(|w| !w.is_empty()) {
+        if use_synonyms {
+            terms.extend(synonym_mapper.expanded_terms(word));
+        } else {
+            terms.push(word.to_string());
+        }
+    }
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+// The query groups searched by the CLI: `query_groups` widened with equivalent names
+fn search_groups(synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) -> Vec<Vec<String>> {
+    let mut groups = query_groups(synonym_mapper, query, options.use_synonyms, options.synonyms_first);
+    if options.match_case_exact && options.use_synonyms {
+        // Synonym expansion lowercases the query; put back the capitalized words to match exactly
+        for word in synonym_mapper.split_phrases(query).iter().filter(|w| !w.starts_with('-') && has_capitals(w)) {
+            let literal = word.trim_matches(|c: char| !c.is_alphabetic());
+            let lower = literal.to_lowercase();
+            for term in groups.iter_mut().flatten().filter(|term| **term == lower) {
+                *term = literal.to_string();
+            }
+        }
+    }
+    if let Some(names) = &options.names {
+        names.expand_groups(&mut groups);
+    }
+    groups
+}
+
+fn has_capitals(term: &str) -> bool {
+    term.chars().any(char::is_uppercase)
+}
+
+// The words a query searches for, without its exclusions
+fn included_words(query: &str) -> String {
+    query.split_whitespace().filter(|w| !w.starts_with('-')).collect::<Vec<_>>().join(" ")
+}
+
+// Options with the query's `-word` exclusions added
+fn with_exclusions(synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) -> SearchOptions {
+    let mut options = options.clone();
+    let use_synonyms = options.use_synonyms && !options.literal_exclusions;
+    options.exclude_terms.extend(excluded_terms(synonym_mapper, query, use_synonyms));
+    options
+}
+
+// Does the verse contain a term? For case-insensitive search `lower_text` is the verse's
+// lowercased text and the term must already be lowercase. A phrase term matches its words
+// in order, however much whitespace separates them in the verse.
+fn contains_term(text: &str, lower_text: &str, term: &str, case_sensitive: bool) -> bool {
+    let text = if case_sensitive { text } else { lower_text };
+    text.contains(term) || (term.contains(' ') && contains_phrase(text, term))
+}
+
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    phrase_spans(text, phrase).next().is_some()
+}
+
+// The byte spans where a phrase's words come in order in the text, with any whitespace
+// between them
+fn phrase_spans<'t>(text: &'t str, phrase: &'t str) -> impl Iterator<Item = (usize, usize)> + 't {
+    let mut words = phrase.split_whitespace();
+    let first = words.next().unwrap_or_default();
+    let rest: Vec<&str> = words.collect();
+    text.match_indices(first).filter(|_| !first.is_empty()).filter_map(move |(start, _)| {
+        let mut end = start + first.len();
+        for word in &rest {
+            let remaining = &text[end..];
+            let after_space = remaining.trim_start();
+            if after_space.len() == remaining.len() || !after_space.starts_with(word) {
+                return None;
+            }
+            end += remaining.len() - after_space.len() + word.len();
+        }
+        Some((start, end))
+    })
+}
+
+// A text's words by their dictionary form, lowercased, with their byte spans (--lemmatize)
+fn lemma_tokens(text: &str) -> Vec<(String, usize, usize)> {
+    filtered_tokens(text, WordFilter { lemmatize: true, ..WordFilter::all_words() })
+}
+
+// A text as --lemmatize searches it: the dictionary forms of its words, each with a space on
+// either side, so a term's lemmas (in the same form) are found only as whole words
+fn lemma_text(text: &str) -> String {
+    let lemmas: Vec<String> = lemma_tokens(text).into_iter().map(|(lemma, _, _)| lemma).collect();
+    format!(" {} ", lemmas.join(" "))
+}
+
+// The byte spans of the runs of words in `text` whose dictionary forms are the term's
+fn lemma_spans(text: &str, term: &str) -> Vec<(usize, usize)> {
+    let lemmas: Vec<String> = lemma_tokens(term).into_iter().map(|(lemma, _, _)| lemma).collect();
+    if lemmas.is_empty() {
+        return Vec::new();
+    }
+    lemma_tokens(text)
+        .windows(lemmas.len())
+        .filter(|words| words.iter().zip(&lemmas).all(|(word, lemma)| &word.0 == lemma))
+        .map(|words| (words[0].1, words[words.len() - 1].2))
+        .collect()
+}
+
+// Terms as a search looks for them: with --lemmatize, the terms that ignore case become their
+// `lemma_text`, to be found in the verses' `lemma_text`
+fn searched_terms(terms: &[(String, bool)], options: &SearchOptions) -> Vec<(String, bool)> {
+    terms
+        .iter()
+        .map(|(term, exact)| if options.lemmatize && !exact { (lemma_text(term), false) } else { (term.clone(), *exact) })
+        .collect()
+}
+
+// Query groups as matched: each term with whether it matches case sensitively, lowercased
+// once up front when it doesn't
+fn matching_groups(groups: &[Vec<String>], options: &SearchOptions) -> Vec<Vec<(String, bool)>> {
+    groups.iter().map(|group| matching_terms(group, options)).collect()
+}
+
+fn matching_terms(terms: &[String], options: &SearchOptions) -> Vec<(String, bool)> {
+    terms
+        .iter()
+        .map(|term| {
+            if options.case_sensitive || (options.match_case_exact && has_capitals(term)) {
+                (term.clone(), true)
+            } else {
+                (term.to_lowercase(), false)
+            }
+        })
+        .collect()
+}
+
+// The spans of a verse to highlight for the query groups, each term in the case it was
+// matched with: each in its group's color with `term_colors`, otherwise all in the first
+fn term_spans(text: &str, groups: &[Vec<(String, bool)>], options: &SearchOptions) -> Vec<(usize, usize, usize)> {
+    // Lemmatized terms are found word by word instead
+    if options.lemmatize {
+        let spans = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, terms)| {
+                let index = if options.term_colors { index } else { 0 };
+                terms.iter().flat_map(move |term| {
+                    let spans = if term.1 { find_case_match_spans(text, std::slice::from_ref(term), options.highlight_words) } else { lemma_spans(text, &term.0) };
+                    spans.into_iter().map(move |(start, end)| (start, end, index))
+                })
+            })
+            .collect();
+        return merge_group_spans(spans);
+    }
+    if options.term_colors {
+        return find_group_spans(text, groups, options.highlight_words);
+    }
+    let terms: Vec<(String, bool)> = groups.iter().flatten().cloned().collect();
+    find_case_match_spans(text, &terms, options.highlight_words).into_iter().map(|(start, end)| (start, end, 0)).collect()
+}
+
+// Print the legend of the term colors when the results are in them and the query has
+// several words
+fn print_legend(synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) {
+    if !(options.term_colors && options.use_color) {
+        return;
+    }
+    let labels = query_labels(synonym_mapper, query, options.use_synonyms);
+    if labels.len() > 1 {
+        println!("{}", format_legend(&labels));
+    }
+}
+
+/// Where each search term matches in `text`, as character offsets, in order of position: every
+/// place the search found it (see `contains_term`, with `lower_text` the same), including inside
+/// longer words, and phrases across any whitespace; with `lemmatize`, the words of the text with
+/// the term's dictionary form, as terms that ignore case are then searched. Each match is
+/// reported on its own even where it overlaps another; when two terms match exactly the same
+/// text the first is reported.
+pub fn term_matches(text: &str, lower_text: &str, terms: &[(String, bool)], lemmatize: bool) -> Vec<MatchSpan> {
+    let mut spans: Vec<(usize, usize, &str)> = Vec::new();
+    for (term, case_sensitive) in terms {
+        let original = text;
+        let text = if *case_sensitive { text } else { lower_text };
+        let found: Vec<(usize, usize)> = if lemmatize && !case_sensitive {
+            lemma_spans(original, term)
+        } else {
+            let found = text.match_indices(term.as_str()).map(|(start, m)| (start, start + m.len()));
+            if term.contains(' ') { found.chain(phrase_spans(text, term)).collect() } else { found.collect() }
+        };
+        // Offsets into the lowercased text are mapped back to the characters of the original,
+        // since lowercasing can change how many there are ('İ' becomes "i̇")
+        let lowered = !(*case_sensitive || lemmatize || original.is_ascii());
+        let starts = if lowered { lowercase_starts(original) } else { Vec::new() };
+        let text = if lemmatize && !case_sensitive { original } else { text };
+        let chars = |byte: usize, end: bool| {
+            if !lowered {
+                text[..byte].chars().count()
+            } else if end {
+                starts.partition_point(|&start| start < byte)
+            } else {
+                starts.partition_point(|&start| start <= byte) - 1
+            }
+        };
+        for (start, end) in found {
+            let (start, end) = (chars(start, false), chars(end, true));
+            if !spans.iter().any(|span| (span.0, span.1) == (start, end)) {
+                spans.push((start, end, term));
+            }
+        }
+    }
+    spans.sort();
+    spans.into_iter().map(|(start, end, term)| MatchSpan { term: term.to_string(), start, end }).collect()
+}
+
+// Where each character of `text` starts in its lowercased form, by byte, and then its length
+fn lowercase_starts(text: &str) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(text.len() + 1);
+    let mut length = 0;
+    for c in text.chars() {
+        starts.push(length);
+        length += c.to_lowercase().map(char::len_utf8).sum::<usize>();
+    }
+    starts.push(length);
+    starts
+}
+
+/// Find verses matching at least `options.min_terms` of the query groups (default: any; every
+/// group with `options.match_all`),
+/// scored by the fraction of groups matched. `options.limit` applies after sorting, so with
+/// relevance order it keeps the best-scoring verses.
+pub fn search_verses<'a>(bible: &'a Bible, groups: &[Vec<String>], options: &SearchOptions) -> Vec<SearchHit<'a>> {
+    let mut hits = Vec::new();
+    if groups.is_empty() {
+        return hits;
+    }
+    // More than every word can't match anything, so treat it as "all words"
+    let min_terms = if options.match_all { groups.len() } else { options.min_terms.unwrap_or(1).clamp(1, groups.len()) };
+    let terms = matching_groups(groups, options);
+    let searched: Vec<Vec<(String, bool)>> = terms.iter().map(|group| searched_terms(group, options)).collect();
+    let excluded = searched_terms(&matching_terms(&options.exclude_terms, options), options);
+    let ranked_limit = options.max_results().filter(|_| options.sort == SortOrder::Relevance);
+    // Match positions in the text, for JSON output
+    let all_terms: Vec<(String, bool)> = terms.iter().flatten().cloned().collect();
+    let record_matches = options.format.is_json() && !options.search_notes;
+    let matches = |verse: &Verse, lower_text: &str| if record_matches { term_matches(&verse.text, lower_text, &all_terms, options.lemmatize) } else { Vec::new() };
+    // With a limit and relevance order only the best `limit` matches so far are kept: a
+    // min-heap on (matched groups, earliest position) whose top is the first to drop
+    let mut best: BinaryHeap<Reverse<(usize, Reverse<usize>)>> = BinaryHeap::new();
+    let (note_texts, lower_notes) = bible.note_texts();
+
+    for (position, (verse, lower_text)) in bible.verses().iter().zip(bible.lowercase_texts()).enumerate() {
+        // Apply book/testament filters if specified
+        if !options.filter.matches(verse) {
+            continue;
+        }
+        // With --search-notes the notes are matched instead of the text
+        let (text, lower_text) = if options.search_notes {
+            (note_texts[position].as_str(), lower_notes[position].as_str())
+        } else {
+            (verse.text.as_str(), lower_text.as_str())
+        };
+        let lemmas = if options.lemmatize { lemma_text(text) } else { String::new() };
+        let contains = |term: &str, exact: bool| if options.lemmatize && !exact { lemmas.contains(term) } else { contains_term(text, lower_text, term, exact) };
+        if excluded.iter().any(|(term, exact)| contains(term, *exact)) {
+            continue;
+        }
+
+        // Count the query words with at least one matching alternative
+        let matched_groups = searched
+            .iter()
+            .filter(|group| group.iter().any(|(term, exact)| contains(term, *exact)))
+            .count();
+        if matched_groups < min_terms {
+            continue;
+        }
+
+        if let Some(limit) = ranked_limit {
+            best.push(Reverse((matched_groups, Reverse(position))));
+            if best.len() > limit {
+                best.pop();
+            }
+            continue;
+        }
+
+        hits.push(SearchHit {
+            verse,
+            score: matched_groups as f32 / groups.len() as f32,
+            matches: matches(verse, lower_text),
+        });
+        // In Bible order the first `limit` matches are the answer, so stop scanning
+        if options.max_results().is_some_and(|limit| hits.len() >= limit) {
+            break;
+        }
+    }
+
+    if ranked_limit.is_some() {
+        let mut kept: Vec<(usize, usize)> = best.into_iter().map(|Reverse((matched, Reverse(position)))| (matched, position)).collect();
+        kept.sort_by_key(|&(matched, position)| (Reverse(matched), position));
+        return kept
+            .into_iter()
+            .map(|(matched, position)| SearchHit {
+                verse: &bible.verses()[position],
+                score: matched as f32 / groups.len() as f32,
+                matches: matches(&bible.verses()[position], &bible.lowercase_texts()[position]),
+            })
+            .collect();
+    }
+
+    if options.sort == SortOrder::Relevance {
+        // Stable sort keeps Bible order among equal scores
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    }
+    hits
+}
+
+/// The verses matching the query groups as they are shown: every match is found and sorted,
+/// each chapter is cut to its first `options.max_per_chapter` hits in that order, and then the
+/// limit applies. Returned with every matching verse, for counting what the cap and the limit
+/// left out (see `found_line`).
+pub fn capped_search<'a>(bible: &'a Bible, groups: &[Vec<String>], options: &SearchOptions) -> (Vec<SearchHit<'a>>, Vec<&'a Verse>) {
+    let cap = options.max_per_chapter.unwrap_or(usize::MAX);
+    let all = search_verses(bible, groups, &SearchOptions { limit: None, ..options.clone() });
+    let matched: Vec<&'a Verse> = all.iter().map(|hit| hit.verse).collect();
+    let mut per_chapter: HashMap<(&str, u32), usize> = HashMap::new();
+    let mut hits: Vec<SearchHit<'a>> = all
+        .into_iter()
+        .filter(|hit| {
+            let shown = per_chapter.entry((hit.verse.book.as_str(), hit.verse.chapter)).or_insert(0);
+            *shown += 1;
+            *shown <= cap
+        })
+        .collect();
+    if let Some(limit) = options.max_results() {
+        hits.truncate(limit);
+    }
+    (hits, matched)
+}
+
+// The "Found N matching verses." line, counting every match and saying how many are shown when
+// the limit or a per-chapter cap left some out
+fn found_line(shown: usize, matched: &[&Verse], options: &SearchOptions) -> String {
+    let total = matched.len();
+    if shown >= total {
+        return format!("Found {} matching verses.", total);
+    }
+    let mut per_chapter: HashMap<(&str, u32), usize> = HashMap::new();
+    for verse in matched {
+        *per_chapter.entry((verse.book.as_str(), verse.chapter)).or_insert(0) += 1;
+    }
+    let cap = options.max_per_chapter.filter(|&cap| per_chapter.values().any(|&count| count > cap));
+    let capped: usize = per_chapter.values().map(|&count| count.min(cap.unwrap_or(usize::MAX))).sum();
+    let first = if options.max_results().is_some_and(|limit| capped > limit) { "the first " } else { "" };
+    match cap {
+        Some(cap) => format!("Found {} matching verses (showing {}{}, at most {} per chapter).", total, first, shown, cap),
+        None => format!("Found {} matching verses (showing {}{}).", total, first, shown),
+    }
+}
+
+/// Find verses whose text opens with `prefix`: an anchored match at the start of the verse,
+/// not anywhere in it. Case is ignored unless `options.case_sensitive`. Each hit comes with
+/// the byte length of the matched opening, for highlighting.
+pub fn search_starts_with<'a>(bible: &'a Bible, prefix: &str, options: &SearchOptions) -> Vec<(&'a Verse, usize)> {
+    let prefix = prefix.trim_start();
+    let lower_prefix = prefix.to_lowercase();
+    let prefix_chars = prefix.chars().count();
+    let mut hits = Vec::new();
+
+    for (verse, lower_text) in bible.verses().iter().zip(bible.lowercase_texts()) {
+        if !options.filter.matches(verse) {
+            continue;
+        }
+        let matched = if options.case_sensitive {
+            verse.text.starts_with(prefix)
+        } else {
+            lower_text.starts_with(&lower_prefix)
+        };
+        if matched {
+            let length = verse.text.char_indices().nth(prefix_chars).map_or(verse.text.len(), |(i, _)| i);
+            hits.push((verse, length));
+            if options.max_results().is_some_and(|limit| hits.len() >= limit) {
+                break;
+            }
+        }
+    }
+    hits
+}
+
+// Print the verses opening with a prefix (--starts-with), the prefix highlighted
+pub fn starts_with_cli(bible: &Bible, prefix: &str, options: &SearchOptions) -> Result<(), BibleError> {
+    if prefix.trim().is_empty() {
+        println!("{}", "Search prefix cannot be empty.".yellow());
+        return Ok(());
+    }
+
+    let hits = search_starts_with(bible, prefix, options);
+    if !options.bare() {
+        println!("Searching for verses starting with '{}'...", prefix.trim_start());
+    }
+    if hits.is_empty() {
+        return Err(no_results("No results found."));
+    }
+    let hits = select_nth(hits, options)?;
+    if print_bare(&hits.iter().map(|(verse, _)| *verse).collect::<Vec<_>>(), bible.note_display(), options) {
+        return Ok(());
+    }
+
+    println!();
+    let entries: Vec<String> = hits
+        .iter()
+        .map(|(verse, length)| result_line(verse, &[(0, *length, 0)], &[], bible.note_display(), options))
+        .collect();
+    if options.paged {
+        page_entries(&entries);
+    } else {
+        for entry in &entries {
+            println!("{}", entry);
+        }
+    }
+    println!("\nFound {} matching verses.", hits.len());
+    print_summary(hits.iter().map(|(verse, _)| *verse), options);
+    Ok(())
+}
+
+/// Find verses mentioning a number in `range`, as digits or in words (see `numbers::find_numbers`).
+/// Each hit comes with the byte spans of the matching numbers, for highlighting.
+pub fn search_numbers<'a>(bible: &'a Bible, range: &RangeInclusive<u64>, options: &SearchOptions) -> Vec<(&'a Verse, Vec<(usize, usize)>)> {
+    let mut hits = Vec::new();
+    for verse in bible.verses() {
+        if !options.filter.matches(verse) {
+            continue;
+        }
+        let spans: Vec<(usize, usize)> = find_numbers(&verse.text)
+            .into_iter()
+            .filter(|mention| range.contains(&mention.value))
+            .map(|mention| (mention.start, mention.end))
+            .collect();
+        if !spans.is_empty() {
+            hits.push((verse, spans));
+            if options.max_results().is_some_and(|limit| hits.len() >= limit) {
+                break;
+            }
+        }
+    }
+    hits
+}
+
+// Print the verses mentioning a number or a number in a range (--number, --number-range)
+pub fn number_search_cli(bible: &Bible, range: RangeInclusive<u64>, options: &SearchOptions) -> Result<(), BibleError> {
+    let hits = search_numbers(bible, &range, options);
+    if !options.bare() {
+        if range.start() == range.end() {
+            println!("Searching for verses mentioning the number {}...", range.start());
+        } else {
+            println!("Searching for verses mentioning a number from {} to {}...", range.start(), range.end());
+        }
+    }
+    if hits.is_empty() {
+        return Err(no_results("No results found."));
+    }
+    let hits = select_nth(hits, options)?;
+    if print_bare(&hits.iter().map(|(verse, _)| *verse).collect::<Vec<_>>(), bible.note_display(), options) {
+        return Ok(());
+    }
+
+    println!();
+    let entries: Vec<String> = hits
+        .iter()
+        .map(|(verse, spans)| result_line(verse, &spans.iter().map(|&(start, end)| (start, end, 0)).collect::<Vec<_>>(), &[], bible.note_display(), options))
+        .collect();
+    if options.paged {
+        page_entries(&entries);
+    } else {
+        for entry in &entries {
+            println!("{}", entry);
+        }
+    }
+    println!("\nFound {} matching verses.", hits.len());
+    print_summary(hits.iter().map(|(verse, _)| *verse), options);
+    Ok(())
+}
+
+// Print the results as bare references, JSON or plain lines when the options ask for it;
+// false when they should get the normal display
+fn print_bare(verses: &[&Verse], notes: NoteDisplay, options: &SearchOptions) -> bool {
+    if options.refs_only {
+        println!("{}", format_references(verses, options.format));
+    } else if options.format.is_json() {
+        println!("{}", format_results(verses, options.format, notes));
+    } else if options.plain {
+        for verse in verses {
+            println!("{}", verse.delimited_line(options.delimiter, notes));
+        }
+    } else {
+        return false;
+    }
+    true
+}
+
+// The "Matched N verses across B books and C chapters." footer line, unless --quiet
+fn print_summary<'a>(verses: impl IntoIterator<Item = &'a Verse>, options: &SearchOptions) {
+    if !options.quiet {
+        println!("{}", ResultSummary::of(verses));
+    }
+}
+
+/// Width of the reference column in --compact output, enough for "1 Thessalonians 5:23"
+pub const COMPACT_REFERENCE_WIDTH: usize = 20;
+
+/// A verse on one line for --compact: the reference padded to `COMPACT_REFERENCE_WIDTH`, then
+/// the text from its beginning, cut with `…` so the line fits `columns`. `spans` of the text are
+/// highlighted when `use_color`, each in the color of its query word (see `apply_term_highlights`).
+pub fn compact_line(verse: &Verse, spans: &[(usize, usize, usize)], columns: usize, use_color: bool) -> String {
+    let reference = format!("{:<width$}", verse.reference(), width = COMPACT_REFERENCE_WIDTH);
+    let (text, cut) = fit_width(&verse.text, columns.saturating_sub(reference.chars().count() + 1));
+    let mut line = if use_color {
+        let spans: Vec<(usize, usize, usize)> = spans.iter().filter(|span| span.0 < text.len()).map(|&(start, end, index)| (start, end.min(text.len()), index)).collect();
+        format!("{} {}", reference.cyan(), apply_term_highlights(text, &spans))
+    } else {
+        format!("{} {}", reference, text)
+    };
+    if cut {
+        line.push('…');
+    }
+    line
+}
+
+// A result's line: the reference, its highlighted text and any notes `notes` shows
+// (`note_terms` highlighted in them), or a --compact line
+fn result_line(verse: &Verse, spans: &[(usize, usize, usize)], note_terms: &[(String, bool)], notes: NoteDisplay, options: &SearchOptions) -> String {
+    if let Some(columns) = options.compact {
+        return compact_line(verse, spans, columns, options.use_color);
+    }
+    let text = if options.use_color { apply_term_highlights(&verse.text, spans) } else { verse.text.clone() };
+    let mut line = format!("{} {}:{} {}", verse.book.cyan(), verse.chapter.to_string().cyan(), verse.verse.to_string().cyan(), text);
+    // Notes are only printed when shown (--show-notes), even when they were searched
+    let NoteBrackets { open, close } = notes.brackets;
+    for note in verse.notes.iter().filter(|_| notes.show) {
+        let note = if options.use_color { apply_highlights(note, &find_case_match_spans(note, note_terms, options.highlight_words)) } else { note.clone() };
+        line.push_str(&format!(" {}{}{}", open, note, close));
+    }
+    line
+}
+
+/// Keep only the result at `options.nth` (1-based), when it is set; an error when the search
+/// found fewer results than that
+pub fn select_nth<T>(mut hits: Vec<T>, options: &SearchOptions) -> Result<Vec<T>, BibleError> {
+    let Some(n) = options.nth else {
+        return Ok(hits);
+    };
+    if n == 0 || n > hits.len() {
+        let found = if hits.len() == 1 { "1 result was".to_string() } else { format!("{} results were", hits.len()) };
+        return Err(BibleError::NoResults { message: format!("Result {} is out of range: only {} found.", n, found), hint: None });
+    }
+    Ok(vec![hits.swap_remove(n - 1)])
+}
+
+fn no_results(message: &str) -> BibleError {
+    BibleError::NoResults { message: message.to_string(), hint: None }
+}
+
+/// A chapter containing every query word somewhere in its verses
+#[derive(Debug, Clone)]
+pub struct ChapterHit<'a> {
+    pub book: &'a str,
+    pub chapter: u32,
+    /// The verses of the chapter that contain at least one query word
+    pub verses: Vec<&'a Verse>,
+}
+
+/// Find chapters where every query group is matched by at least one verse (AND across verses).
+/// Only verses passing `options.filter` are considered; `options.limit` caps the chapter count.
+pub fn search_chapters<'a>(bible: &'a Bible, groups: &[Vec<String>], options: &SearchOptions) -> Vec<ChapterHit<'a>> {
+    let mut hits = Vec::new();
+    if groups.is_empty() {
+        return hits;
+    }
+
+    let terms: Vec<Vec<(String, bool)>> = matching_groups(groups, options).iter().map(|group| searched_terms(group, options)).collect();
+    let excluded = searched_terms(&matching_terms(&options.exclude_terms, options), options);
+    // Verses are stored book by book and chapter by chapter, so each chapter is one run
+    let mut start = 0;
+    for chapter_verses in bible.verses().chunk_by(|a, b| a.book == b.book && a.chapter == b.chapter) {
+        let lower_texts = &bible.lowercase_texts()[start..start + chapter_verses.len()];
+        start += chapter_verses.len();
+
+        let mut found = vec![false; groups.len()];
+        let mut verses = Vec::new();
+        let candidates = chapter_verses.iter().zip(lower_texts).filter(|(v, _)| options.filter.matches(v));
+        for (verse, lower_text) in candidates {
+            let lemmas = if options.lemmatize { lemma_text(&verse.text) } else { String::new() };
+            let contains = |term: &str, exact: bool| if options.lemmatize && !exact { lemmas.contains(term) } else { contains_term(&verse.text, lower_text, term, exact) };
+            if excluded.iter().any(|(term, exact)| contains(term, *exact)) {
+                continue;
+            }
+            let mut contributes = false;
+            for (index, group) in terms.iter().enumerate() {
+                if group.iter().any(|(term, exact)| contains(term, *exact)) {
+                    found[index] = true;
+                    contributes = true;
+                }
+            }
+            if contributes {
+                verses.push(verse);
+            }
+        }
+
+        if found.iter().all(|&f| f) {
+            let first = &chapter_verses[0];
+            hits.push(ChapterHit { book: &first.book, chapter: first.chapter, verses });
+            if options.max_results().is_some_and(|limit| hits.len() >= limit) {
+                return hits;
+            }
+        }
+    }
+    hits
+}
+
+// Print the chapters containing every query word, each followed by its contributing verses
+pub fn chapter_search_cli(bible: &Bible, synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) -> Result<(), BibleError> {
+    if query.trim().is_empty() {
+        println!("{}", "Search query cannot be empty.".yellow());
+        return Ok(());
+    }
+
+    let groups = search_groups(synonym_mapper, query, options);
+    let options = &with_exclusions(synonym_mapper, query, options);
+
+    let hits = search_chapters(bible, &groups, options);
+
+    if !options.bare() {
+        println!("Searching for chapters containing all of '{}'...", query);
+    }
+    if hits.is_empty() {
+        return Err(no_results("No chapters found."));
+    }
+    if print_bare(&hits.iter().flat_map(|hit| hit.verses.iter().copied()).collect::<Vec<_>>(), bible.note_display(), options) {
+        return Ok(());
+    }
+
+    print_legend(synonym_mapper, query, options);
+    let terms = matching_groups(&groups, options);
+    for hit in &hits {
+        let heading = format!("{} {}", hit.book, hit.chapter);
+        println!("\n{}", if options.use_color { heading.cyan().bold().to_string() } else { heading });
+        for verse in &hit.verses {
+            let text = if options.use_color {
+                apply_term_highlights(&verse.text, &term_spans(&verse.text, &terms, options))
+            } else {
+                verse.text.clone()
+            };
+            println!("  {}:{} {}", verse.chapter, verse.verse, text);
+        }
+    }
+    println!("\nFound {} matching chapters.", hits.len());
+    print_summary(hits.iter().flat_map(|hit| hit.verses.iter().copied()), options);
+    Ok(())
+}
+
+/// The verses matching a query as `--search` finds them (synonyms, names and `-word`
+/// exclusions included), without printing anything
+pub fn search_query<'a>(bible: &'a Bible, synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) -> Vec<SearchHit<'a>> {
+    let groups = search_groups(synonym_mapper, query, options);
+    search_verses(bible, &groups, &with_exclusions(synonym_mapper, query, options))
+}
+
+pub fn search_bible_cli(bible: &Bible, synonym_mapper: &SynonymMapper, query: &str, options: &SearchOptions) -> Result<(), BibleError> {
+    if query.trim().is_empty() {
+        println!("{}", "Search query cannot be empty.".yellow());
+        return Ok(());
+    }
+
+    let groups = search_groups(synonym_mapper, query, options);
+    let mut search_terms: Vec<String> = groups.iter().flatten().cloned().collect();
+    search_terms.sort();
+    search_terms.dedup();
+    let options = &with_exclusions(synonym_mapper, query, options);
+
+    if let Some(detail) = options.book_list {
+        // Every match counts, in Bible order, whatever the limit and sort
+        let all = SearchOptions { limit: None, sort: SortOrder::Canonical, ..options.clone() };
+        let hits = search_verses(bible, &groups, &all);
+        if hits.is_empty() {
+            return Err(no_results("No results found."));
+        }
+        println!("{}", format_book_list(&book_counts(hits.iter().map(|hit| hit.verse), detail), options.format));
+        return Ok(());
+    }
+
+    if options.bare() {
+        let (hits, matched) = capped_search(bible, &groups, options);
+        if hits.is_empty() {
+            return Err(no_results("No results found."));
+        }
+        let hits = select_nth(hits, options)?;
+        let shown = hits.len();
+        if options.format.is_json() && !options.refs_only {
+            // Each result with where its terms matched, and how many matched in all
+            let records = hits.into_iter().map(|hit| VerseRecord { matches: hit.matches, ..VerseRecord::new(hit.verse, bible.note_display()) }).collect();
+            println!("{}", format_records_with_total(records, matched.len(), options.format));
+        } else {
+            print_bare(&hits.iter().map(|hit| hit.verse).collect::<Vec<_>>(), bible.note_display(), options);
+        }
+        // Lines have no footer, so what was left out is counted on stderr, off the pipe; a
+        // JSON document carries its total
+        let counted = options.format == OutputFormat::Json && !options.refs_only;
+        if !counted && shown < matched.len() && options.nth.is_none() {
+            eprintln!("{}", found_line(shown, &matched, options));
+        }
+        return Ok(());
+    }
+
+    let words = included_words(query);
+    let mut target = if options.match_all { format!("all of '{}'", words) } else { format!("'{}'", words) };
+    if !options.exclude_terms.is_empty() {
+        target.push_str(&format!(" excluding {}", options.exclude_terms.join(", ")));
+    }
+    if let Some(names) = options.names.as_ref().and_then(|names| names.describe(&words)) {
+        target.push_str(&format!(" (with names: {})", names));
+    }
+    let synonym_terms: usize = query_groups(synonym_mapper, &words, options.use_synonyms, options.synonyms_first).iter().map(Vec::len).sum();
+    if options.use_synonyms && synonym_terms > words.split_whitespace().count() {
+        println!("Searching for {} (with synonyms: {})...", target, describe_expansion(&synonym_mapper.expand_query(&words, options.synonyms_first)));
+    } else if options.use_synonyms {
+        println!("Searching for {} (no synonyms defined for these terms)...", target);
+    } else {
+        println!("Searching for {}...", target);
+    }
+
+    let (hits, matched) = capped_search(bible, &groups, options);
+    if hits.is_empty() {
+        return Err(no_results("No results found."));
+    }
+    let hits = select_nth(hits, options)?;
+
+    println!();
+    print_legend(synonym_mapper, query, options);
+    let groups = matching_groups(&groups, options);
+    let terms = matching_terms(&search_terms, options);
+    let mut entries = Vec::with_capacity(hits.len());
+    for hit in &hits {
+        let verse = hit.verse;
+        let spans = if options.search_notes { Vec::new() } else { term_spans(&verse.text, &groups, options) };
+
+        let score = if options.show_score { format!("[{:.0}%] ", hit.score * 100.0) } else { String::new() };
+        // The score sits before the reference, so a compact line has that much less room
+        let line = match options.compact {
+            Some(columns) => compact_line(verse, &spans, columns.saturating_sub(score.len()), options.use_color),
+            None => result_line(verse, &spans, if options.search_notes { &terms } else { &[] }, bible.note_display(), options),
+        };
+        let score = if options.use_color { score.yellow().to_string() } else { score };
+        entries.push(format!("{}{}", score, line));
+    }
+
+    // Results are kept in memory so paging never re-runs the search
+    if options.paged {
+        page_entries(&entries);
+    } else {
+        for entry in &entries {
+            println!("{}", entry);
+        }
+    }
+    if options.nth.is_some() {
+        println!("\nFound {} matching verses.", hits.len());
+        print_summary(hits.iter().map(|hit| hit.verse), options);
+    } else {
+        println!("\n{}", found_line(hits.len(), &matched, options));
+        print_summary(matched, options);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_groups() {
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("love", vec!["love".to_string(), "charity".to_string()]);
+
+        assert_eq!(query_groups(&mapper, "faith love faith", false, false), vec![vec!["faith"], vec!["love"]]);
+        assert_eq!(query_groups(&mapper, "faith love", true, false), vec![vec!["faith"], vec!["charity", "love"]]);
+        // Only the first word with synonyms expands (--synonyms-first), wherever it is
+        assert_eq!(query_groups(&mapper, "faith love love", true, true), vec![vec!["faith"], vec!["charity", "love"]]);
+        assert_eq!(query_groups(&mapper, "love charity", true, true), vec![vec!["charity", "love"], vec!["charity"]]);
+        // A label for each group, for the legend of the term colors
+        assert_eq!(query_labels(&mapper, "faith love -fear faith", true), ["faith", "love"]);
+        assert_eq!(query_labels(&mapper, "faith love -fear faith", false), ["faith", "love"]);
+    }
+
+    #[test]
+    fn test_multi_word_synonym_phrase() {
+        let bible = Bible::new(vec![
+            Verse::new("Psalms", 9, 2, "I will sing praise to thy name, O thou most High."),
+            Verse::new("Psalms", 91, 1, "He that dwelleth in the secret place of the Most\n High shall abide"),
+            Verse::new("Isaiah", 14, 14, "I will ascend above the heights of the clouds; I will be like the most high."),
+            Verse::new("Exodus", 24, 10, "And they saw the God of Israel: and there was under his feet a paved work."),
+            Verse::new("Luke", 1, 32, "He shall be great, and shall be called the Son of the Highest"),
+        ]);
+        let mapper = SynonymMapper::builtin();
+        let options = SearchOptions { use_synonyms: true, ..Default::default() };
+        let books = |query: &str| -> Vec<String> {
+            search_verses(&bible, &search_groups(&mapper, query, &options), &options).iter().map(|hit| hit.verse.reference()).collect()
+        };
+
+        // `--synonyms --search god` finds "most High" as a phrase, even across a line break
+        assert_eq!(books("god"), ["Psalms 9:2", "Psalms 91:1", "Isaiah 14:14", "Exodus 24:10"]);
+        // and the phrase typed as a query is one word expanding to god's group
+        assert_eq!(books("most high"), books("god"));
+        assert_eq!(search_groups(&mapper, "most high", &options).len(), 1);
+        // Without synonyms the words are searched separately, so "Highest" counts
+        assert_eq!(search_verses(&bible, &search_groups(&mapper, "most high", &SearchOptions::default()), &SearchOptions::default()).len(), 4);
+    }
+
+    #[test]
+    fn test_nth_result() {
+        let bible = Bible::new(vec![
+            Verse::new("John", 11, 35, "Jesus wept."),
+            Verse::new("John", 11, 36, "Then said the Jews, Behold how he loved him!"),
+        ]);
+        let mapper = SynonymMapper::new();
+        let first = SearchOptions { nth: Some(1), ..Default::default() };
+        let hits = select_nth(search_verses(&bible, &search_groups(&mapper, "wept", &first), &first), &first).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].verse.reference(), "John 11:35");
+
+        let out_of_range = SearchOptions { nth: Some(99), ..Default::default() };
+        let error = select_nth(search_verses(&bible, &search_groups(&mapper, "wept", &out_of_range), &out_of_range), &out_of_range).unwrap_err();
+        assert_eq!(error.to_string(), "Result 99 is out of range: only 1 result was found.");
+
+        // The position counts after sorting: with relevance, the verse with both words is first
+        let ranked = SearchOptions { nth: Some(2), sort: SortOrder::Relevance, ..Default::default() };
+        let hits = select_nth(search_verses(&bible, &search_groups(&mapper, "loved jews wept", &ranked), &ranked), &ranked).unwrap();
+        assert_eq!(hits[0].verse.reference(), "John 11:35");
+    }
+
+    #[test]
+    fn test_json_match_spans() {
+        let bible = Bible::new(vec![
+            Verse::new("1 John", 4, 8, "He that loveth not knoweth not God; for God is love."),
+            Verse::new("Song of Solomon", 8, 7, "Many waters cannot quench love, neither can the floods drown it."),
+        ]);
+        let json = SearchOptions { format: OutputFormat::Json, ..Default::default() };
+        let hits = search_verses(&bible, &[vec!["God".to_string()], vec!["love".to_string()]], &json);
+
+        let span = |term: &str, start, end| MatchSpan { term: term.to_string(), start, end };
+        // Every match of every term, in order, where the search found it: "love" in "loveth"
+        assert_eq!(hits[0].matches, [span("love", 8, 12), span("god", 31, 34), span("god", 40, 43), span("love", 47, 51)]);
+        assert_eq!(hits[1].matches, [span("love", 26, 30)]);
+        let first = &hits[0].verse.text;
+        assert_eq!(first.chars().skip(31).take(3).collect::<String>(), "God");
+        // A phrase matches across a line break, as it is searched
+        let phrases = Bible::new(vec![Verse::new("John", 1, 1, "In the beginning was the\nWord")]);
+        let hits = search_verses(&phrases, &[vec!["the word".to_string()]], &json);
+        assert_eq!(hits[0].matches, [span("the word", 21, 29)]);
+
+        // Offsets count characters, so text before a match may have multi-byte letters
+        let accented = Bible::new(vec![Verse::new("John", 1, 1, "Au commencement était la Parole, et la Parole était avec Dieu.")]);
+        let hits = search_verses(&accented, &[vec!["dieu".to_string()]], &json);
+        assert_eq!(hits[0].matches, [span("dieu", 57, 61)]);
+        // ...and letters whose lowercase is longer: 'İ' lowercases to two characters
+        let dotted = Bible::new(vec![Verse::new("John", 1, 1, "İsa dedi ki: İsa ben")]);
+        let hits = search_verses(&dotted, &[vec!["dedi".to_string()], vec!["i̇sa".to_string()]], &json);
+        assert_eq!(hits[0].matches, [span("i̇sa", 0, 3), span("dedi", 4, 8), span("i̇sa", 13, 16)]);
+        assert_eq!(dotted[0].text.chars().skip(4).take(4).collect::<String>(), "dedi");
+        // Only JSON output records them
+        assert!(search_verses(&accented, &[vec!["dieu".to_string()]], &SearchOptions::default())[0].matches.is_empty());
+    }
+
+    #[test]
+    fn test_lemmatized_search() {
+        let bible = Bible::new(vec![
+            Verse::new("1 John", 4, 19, "We love him, because he first loved us."),
+            Verse::new("Psalms", 18, 1, "I will love thee, O LORD, my strength."),
+            Verse::new("Song of Solomon", 5, 9, "What is thy beloved more than another beloved?"),
+            Verse::new("Genesis", 37, 3, "Now Israel loved Joseph more than all his children, because he was the son of his old age."),
+        ]);
+        let found = |query: &str, options: &SearchOptions| {
+            search_verses(&bible, &[vec![query.to_string()]], options).iter().map(|hit| hit.verse.reference()).collect::<Vec<_>>()
+        };
+        let lemmas = SearchOptions { lemmatize: true, ..Default::default() };
+
+        // Every form of the word, and whole words only
+        assert_eq!(found("loved", &lemmas), ["1 John 4:19", "Psalms 18:1", "Genesis 37:3"]);
+        assert_eq!(found("loved", &SearchOptions::default()), ["1 John 4:19", "Song of Solomon 5:9", "Genesis 37:3"]);
+        assert_eq!(found("first loving", &lemmas), ["1 John 4:19"]);
+        let excluding = SearchOptions { exclude_terms: vec!["child".to_string()], ..lemmas.clone() };
+        assert_eq!(found("loved", &excluding), ["1 John 4:19", "Psalms 18:1"]);
+
+        // Highlights and JSON offsets are the words found
+        let text = &bible.verses()[0].text;
+        assert_eq!(term_spans(text, &matching_groups(&[vec!["loved".to_string()]], &lemmas), &lemmas), vec![(3, 7, 0), (30, 35, 0)]);
+        let json = SearchOptions { format: OutputFormat::Json, ..lemmas.clone() };
+        let hits = search_verses(&bible, &[vec!["loved".to_string()]], &json);
+        let spans: Vec<(usize, usize)> = hits[0].matches.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(spans, [(3, 7), (30, 35)]);
+    }
+
+    #[test]
+    fn test_search_notes() {
+        let mut verses = vec![
+            Verse::new("1 John", 4, 8, "He that loveth not knoweth not God; for God is love [agape]."),
+            Verse::new("John", 21, 17, "Simon, son of Jonas, lovest thou me [phileo]?"),
+            Verse::new("Song of Solomon", 2, 4, "his banner over me was agape love."),
+        ];
+        crate::bible::extract_notes(&mut verses, NoteBrackets::default());
+        let bible = Bible::new(verses);
+        let mapper = SynonymMapper::new();
+        let found = |query: &str, options: &SearchOptions| {
+            search_verses(&bible, &search_groups(&mapper, query, options), options).iter().map(|hit| hit.verse.reference()).collect::<Vec<_>>()
+        };
+
+        let notes = SearchOptions { search_notes: true, ..Default::default() };
+        assert_eq!(found("agape", &notes), ["1 John 4:8"]);
+        assert!(found("love", &notes).is_empty());
+        // The text no longer contains its notes
+        assert_eq!(found("agape", &SearchOptions::default()), ["Song of Solomon 2:4"]);
+        assert!(found("phileo", &SearchOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_name_equivalents() {
+        let bible = Bible::new(vec![
+            Verse::new("Malachi", 4, 5, "Behold, I will send you Elijah the prophet before the coming of the great and dreadful day of the LORD:"),
+            Verse::new("Matthew", 17, 3, "And, behold, there appeared unto them Moses and Elias talking with him."),
+        ]);
+        let mapper = SynonymMapper::new();
+
+        let without = SearchOptions::default();
+        assert_eq!(search_verses(&bible, &search_groups(&mapper, "Elijah", &without), &without).len(), 1);
+
+        let options = SearchOptions { names: Some(NameEquivalents::builtin()), ..Default::default() };
+        let books: Vec<&str> = search_verses(&bible, &search_groups(&mapper, "Elijah", &options), &options)
+            .iter()
+            .map(|hit| hit.verse.book.as_str())
+            .collect();
+        assert_eq!(books, vec!["Malachi", "Matthew"]);
+    }
+
+    #[test]
+    fn test_book_list_of_search() {
+        let bible = Bible::new(vec![
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+            Verse::new("Psalms", 80, 1, "Give ear, O Shepherd of Israel,"),
+            Verse::new("Isaiah", 40, 11, "He shall feed his flock like a shepherd:"),
+            Verse::new("Jeremiah", 1, 1, "The words of Jeremiah the son of Hilkiah."),
+            Verse::new("John", 10, 11, "I am the good shepherd: the good shepherd giveth his life for the sheep."),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "shepherd", false, false);
+
+        let hits = search_verses(&bible, &groups, &SearchOptions::default());
+        let books = book_counts(hits.iter().map(|hit| hit.verse), BookListDetail::Books);
+
+        assert_eq!(format_book_list(&books, OutputFormat::Text), "Psalms\t2\nIsaiah\t1\nJohn\t1");
+    }
+
+    #[test]
+    fn test_number_search() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 2, 2, "And on the seventh day God ended his work which he had made;"),
+            Verse::new("Genesis", 7, 4, "For yet seven days, and I will cause it to rain upon the earth forty days and forty nights;"),
+            Verse::new("Matthew", 18, 22, "Jesus saith unto him, I say not unto thee, Until seven times: but, Until seventy times seven."),
+            Verse::new("Revelation", 1, 20, "The 7 stars are the angels of the 7 churches."),
+            Verse::new("Revelation", 13, 18, "his number is Six hundred threescore and six."),
+        ]);
+        let options = SearchOptions::default();
+        let found = |range: RangeInclusive<u64>| -> Vec<String> {
+            search_numbers(&bible, &range, &options).iter().map(|(v, _)| v.reference()).collect()
+        };
+
+        // Digits and words alike; "seventh" and "six hundred" are other numbers
+        assert_eq!(found(7..=7), vec!["Genesis 7:4", "Matthew 18:22", "Revelation 1:20"]);
+        assert_eq!(found(40..=50), vec!["Genesis 7:4"]);
+        assert_eq!(found(600..=700), vec!["Revelation 13:18"]);
+        assert_eq!(search_numbers(&bible, &(7..=7), &options)[2].1, vec![(4, 5), (34, 35)]);
+    }
+
+    #[test]
+    fn test_relevance_ranks_full_matches_first() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "And hope was there."),
+            Verse::new("1 Corinthians", 13, 13, "And now abideth faith, hope, love, these three."),
+            Verse::new("Hebrews", 11, 1, "Now faith is the substance of things hoped for."),
+        ]);
+        let mapper = SynonymMapper::new();
+        let groups = query_groups(&mapper, "faith hope love", false, false);
+        let options = SearchOptions { sort: SortOrder::Relevance, ..Default::default() };
+
+        let hits = search_verses(&bible, &groups, &options);
+
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].verse.book, "1 Corinthians");
+        assert_eq!(hits[0].score, 1.0);
+        // "hoped" contains "hope", so Hebrews matches two of three words
+        assert_eq!(hits[1].verse.book, "Hebrews");
+        assert!((hits[1].score - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(hits[2].verse.book, "Genesis");
+        assert!((hits[2].score - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_min_terms() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "And hope was there."),
+            Verse::new("1 Corinthians", 13, 13, "And now abideth faith, hope, love, these three."),
+            Verse::new("Hebrews", 11, 1, "Now faith is the substance of things hoped for."),
+        ]);
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("love", vec!["love".to_string(), "charity".to_string()]);
+        let groups = query_groups(&mapper, "faith hope charity", true, false);
+
+        let options = SearchOptions { min_terms: Some(2), ..Default::default() };
+        let books: Vec<&str> = search_verses(&bible, &groups, &options).iter().map(|h| h.verse.book.as_str()).collect();
+        // "love" counts under "charity", so 1 Corinthians has all three and Hebrews two
+        assert_eq!(books, vec!["1 Corinthians", "Hebrews"]);
+
+        let options = SearchOptions { min_terms: Some(3), ..Default::default() };
+        assert_eq!(search_verses(&bible, &groups, &options).len(), 1);
+
+        // K beyond the number of query words means every word
+        let options = SearchOptions { min_terms: Some(10), ..Default::default() };
+        assert_eq!(search_verses(&bible, &groups, &options).len(), 1);
+    }
+
+    #[test]
+    fn test_exclusions_compose_with_text_matching() {
+        let bible = Bible::new(vec![
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd"),
+            Verse::new("John", 10, 11, "I am the good shepherd"),
+            Verse::new("Luke", 2, 8, "shepherds abiding in the field"),
+            Verse::new("Luke", 2, 9, "the glory of the Lord"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "shepherd", false, false);
+        let options = SearchOptions {
+            filter: VerseFilter {
+                book: Some("Gospels".to_string()),
+                exclude_books: vec!["John".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let hits = search_verses(&bible, &groups, &options);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].verse.book.as_str(), hits[0].verse.verse), ("Luke", 8));
+    }
+
+    #[test]
+    fn test_chapter_search_requires_every_word_in_the_chapter() {
+        let bible = Bible::new(vec![
+            Verse::new("Romans", 3, 24, "Being justified freely by his grace"),
+            Verse::new("Romans", 3, 28, "a man is justified by faith without the deeds of the law"),
+            Verse::new("James", 2, 17, "Even so faith, if it hath not works, is dead"),
+            Verse::new("James", 2, 24, "by works a man is justified, and not by faith only"),
+            Verse::new("James", 3, 1, "not many masters"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "faith works justified", false, false);
+
+        let hits = search_chapters(&bible, &groups, &SearchOptions::default());
+
+        // Romans 3 has no "works"; James 2 has every word across its verses
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].book, hits[0].chapter), ("James", 2));
+        assert_eq!(hits[0].verses.len(), 2);
+    }
+
+    #[test]
+    fn test_canonical_sort_keeps_bible_order() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "hope"),
+            Verse::new("Exodus", 1, 1, "faith and hope"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "faith hope", false, false);
+
+        let hits = search_verses(&bible, &groups, &SearchOptions::default());
+
+        assert_eq!(hits[0].verse.book, "Genesis");
+        assert_eq!(hits[1].verse.book, "Exodus");
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        let bible = Bible::new(vec![
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd"),
+            Verse::new("John", 10, 11, "I am the good shepherd"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "Lord Shepherd", false, false);
+
+        // Case-insensitive search matches the cached lowercase text, whatever the query's case
+        let hits = search_verses(&bible, &groups, &SearchOptions::default());
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].score, 1.0);
+
+        let options = SearchOptions { case_sensitive: true, ..Default::default() };
+        assert!(search_verses(&bible, &groups, &options).is_empty());
+        let groups = query_groups(&SynonymMapper::new(), "LORD", false, false);
+        assert_eq!(search_verses(&bible, &groups, &options).len(), 1);
+    }
+
+    #[test]
+    fn test_match_case_exact() {
+        // A file that keeps the small-caps LORD (the divine name) apart from Lord
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("kjv.txt");
+        std::fs::write(&file, "Psalms 23:1\tThe LORD is my shepherd; I shall not want.\n\
+            Psalms 110:1\tThe LORD said unto my Lord, Sit thou at my right hand\n\
+            Matthew 7:21\tNot every one that saith unto me, Lord, Lord, shall enter into the kingdom\n\
+            John 10:11\tI am the good Shepherd\n").unwrap();
+        let bible = Bible::new(crate::json_parser::load_bible_auto(file.to_str().unwrap()).unwrap());
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("lord", vec!["lord".to_string(), "master".to_string()]);
+        let references = |query: &str, options: &SearchOptions| -> Vec<String> {
+            search_verses(&bible, &search_groups(&mapper, query, options), options).iter().map(|hit| hit.verse.reference()).collect()
+        };
+
+        let exact = SearchOptions { match_case_exact: true, ..Default::default() };
+        assert_eq!(references("LORD", &exact), vec!["Psalms 23:1", "Psalms 110:1"]);
+        assert_eq!(references("Lord", &exact), vec!["Psalms 110:1", "Matthew 7:21"]);
+        // Lowercase words still ignore case
+        assert_eq!(references("LORD shepherd", &SearchOptions { match_all: true, ..exact.clone() }), vec!["Psalms 23:1"]);
+        assert_eq!(references("LORD", &SearchOptions::default()).len(), 3);
+        // The capitalized word stays exact through synonym expansion
+        let synonyms = SearchOptions { use_synonyms: true, ..exact.clone() };
+        assert_eq!(references("LORD", &synonyms), vec!["Psalms 23:1", "Psalms 110:1"]);
+
+        // Only the exact LORD is highlighted
+        let text = "The LORD said unto my Lord";
+        let spans = term_spans(text, &matching_groups(&[vec!["LORD".to_string()]], &exact), &exact);
+        assert_eq!(spans, vec![(4, 8, 0)]);
+        let (highlighted, expected) = crate::highlight::with_colors(|| (apply_term_highlights(text, &spans), apply_highlights(text, &[(4, 8)])));
+        assert_eq!(highlighted, expected);
+        assert_ne!(highlighted, text);
+    }
+
+    #[test]
+    fn test_starts_with_is_anchored() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "In the beginning God created the heaven and the earth."),
+            Verse::new("John", 1, 1, "In the beginning was the Word."),
+            Verse::new("John", 1, 2, "The same was in the beginning with God."),
+        ]);
+
+        let hits = search_starts_with(&bible, "  in the BEGINNING", &SearchOptions::default());
+        let found: Vec<_> = hits.iter().map(|(v, len)| (v.book.as_str(), &v.text[..*len])).collect();
+        // John 1:2 has the phrase mid-verse, so it isn't a match
+        assert_eq!(found, vec![("Genesis", "In the beginning"), ("John", "In the beginning")]);
+
+        let options = SearchOptions { filter: VerseFilter { book: Some("John".to_string()), ..Default::default() }, ..Default::default() };
+        assert_eq!(search_starts_with(&bible, "in the beginning", &options).len(), 1);
+
+        let options = SearchOptions { case_sensitive: true, ..Default::default() };
+        assert!(search_starts_with(&bible, "in the beginning", &options).is_empty());
+    }
+
+    #[test]
+    fn test_limit_applies_after_relevance_sort() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "hope"),
+            Verse::new("Exodus", 1, 1, "faith"),
+            Verse::new("Ruth", 1, 1, "faith and hope"),
+            Verse::new("Job", 1, 1, "love"),
+            Verse::new("Romans", 5, 5, "faith, hope and love"),
+            Verse::new("Jude", 1, 1, "hope and love"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "faith hope love", false, false);
+        let options = SearchOptions { sort: SortOrder::Relevance, limit: Some(3), ..Default::default() };
+
+        let found: Vec<&str> = search_verses(&bible, &groups, &options).iter().map(|h| h.verse.book.as_str()).collect();
+
+        // The full match first, then the two-word matches in Bible order
+        assert_eq!(found, vec!["Romans", "Ruth", "Jude"]);
+
+        // In Bible order the limit still takes the first matches
+        let options = SearchOptions { limit: Some(2), ..Default::default() };
+        let found: Vec<&str> = search_verses(&bible, &groups, &options).iter().map(|h| h.verse.book.as_str()).collect();
+        assert_eq!(found, vec!["Genesis", "Exodus"]);
+    }
+
+    #[test]
+    fn test_max_results_per_chapter() {
+        let mut verses: Vec<Verse> = (1..=6).map(|n| Verse::new("Psalms", 119, n, "thy word")).collect();
+        verses.push(Verse::new("Psalms", 120, 1, "a word"));
+        verses.push(Verse::new("John", 1, 1, "the Word"));
+        let bible = Bible::new(verses);
+        let groups = query_groups(&SynonymMapper::new(), "word", false, false);
+
+        // The dense chapter shows only the cap; the footer still counts every match
+        let options = SearchOptions { max_per_chapter: Some(2), ..Default::default() };
+        let (hits, matched) = capped_search(&bible, &groups, &options);
+        let shown: Vec<String> = hits.iter().map(|hit| hit.verse.reference()).collect();
+        assert_eq!(shown, ["Psalms 119:1", "Psalms 119:2", "Psalms 120:1", "John 1:1"]);
+        assert_eq!(matched.len(), 8);
+        assert_eq!(found_line(hits.len(), &matched, &options), "Found 8 matching verses (showing 4, at most 2 per chapter).");
+
+        // The limit applies after the cap, so later chapters still get their share
+        let limited = SearchOptions { limit: Some(3), ..options.clone() };
+        let (hits, matched) = capped_search(&bible, &groups, &limited);
+        assert_eq!(hits.last().unwrap().verse.reference(), "Psalms 120:1");
+        assert_eq!(matched.len(), 8);
+        assert_eq!(found_line(hits.len(), &matched, &limited), "Found 8 matching verses (showing the first 3, at most 2 per chapter).");
+
+        // A cap no chapter reaches changes nothing
+        let loose = SearchOptions { max_per_chapter: Some(6), ..Default::default() };
+        let (hits, matched) = capped_search(&bible, &groups, &loose);
+        assert_eq!(found_line(hits.len(), &matched, &loose), "Found 8 matching verses.");
+
+        // Without a cap the footer still counts every match, and blames the limit alone
+        let uncapped = SearchOptions { limit: Some(5), ..Default::default() };
+        let (hits, matched) = capped_search(&bible, &groups, &uncapped);
+        assert_eq!(found_line(hits.len(), &matched, &uncapped), "Found 8 matching verses (showing the first 5).");
+        let loose = SearchOptions { limit: Some(5), ..loose };
+        let (hits, matched) = capped_search(&bible, &groups, &loose);
+        assert_eq!(found_line(hits.len(), &matched, &loose), "Found 8 matching verses (showing the first 5).");
+    }
+
+    #[test]
+    fn test_zero_and_oversized_limits() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "hope"),
+            Verse::new("Ruth", 1, 1, "faith and hope"),
+            Verse::new("Romans", 5, 5, "faith, hope and love"),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "faith hope", false, false);
+
+        // 0 means no limit, the same as no --limit and as a limit beyond the result count,
+        // however the results are found
+        for limit in [None, Some(0), Some(usize::MAX)] {
+            for sort in [SortOrder::Canonical, SortOrder::Relevance] {
+                let options = SearchOptions { limit, sort, ..Default::default() };
+                assert_eq!(search_verses(&bible, &groups, &options).len(), 3, "limit {:?}, {:?}", limit, sort);
+            }
+            let options = SearchOptions { limit, ..Default::default() };
+            assert_eq!(search_starts_with(&bible, "faith", &options).len(), 2);
+            assert_eq!(search_chapters(&bible, &groups, &options).len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_match_all() {
+        let bible = Bible::new(vec![
+            Verse::new("1 John", 4, 8, "He that loveth not knoweth not God; for God is love."),
+            Verse::new("Genesis", 1, 1, "In the beginning God created the heaven and the earth."),
+            Verse::new("Song of Solomon", 2, 4, "His banner over me was love."),
+        ]);
+        let groups = query_groups(&SynonymMapper::new(), "god love", false, false);
+        let options = SearchOptions { match_all: true, min_terms: Some(1), ..Default::default() };
+
+        let hits = search_verses(&bible, &groups, &options);
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].verse.book, "1 John");
+    }
+
+    #[test]
+    fn test_excluded_terms() {
+        let bible = Bible::new(vec![
+            Verse::new("Romans", 13, 10, "Love worketh no ill: therefore love is the fulfilling of the law."),
+            Verse::new("Psalms", 119, 97, "O how love I thy statutes!"),
+            Verse::new("1 John", 4, 8, "God is love."),
+        ]);
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("law", vec!["law".to_string(), "statutes".to_string()]);
+        let groups = query_groups(&mapper, "love -law", false, false);
+        assert_eq!(groups, vec![vec!["love"]]);
+
+        // Without synonyms only the word itself is excluded
+        let options = SearchOptions { exclude_terms: excluded_terms(&mapper, "love -law", false), ..Default::default() };
+        let found: Vec<&str> = search_verses(&bible, &groups, &options).iter().map(|h| h.verse.book.as_str()).collect();
+        assert_eq!(found, vec!["Psalms", "1 John"]);
+
+        // With synonyms the excluded word's synonyms go too, unless exclusions are literal
+        let options = SearchOptions { use_synonyms: true, ..Default::default() };
+        let found: Vec<&str> = search_verses(&bible, &groups, &with_exclusions(&mapper, "love -law", &options)).iter().map(|h| h.verse.book.as_str()).collect();
+        assert_eq!(found, vec!["1 John"]);
+
+        let options = SearchOptions { use_synonyms: true, literal_exclusions: true, ..Default::default() };
+        assert_eq!(search_verses(&bible, &groups, &with_exclusions(&mapper, "love -law", &options)).len(), 2);
+    }
+
+    #[test]
+    fn test_compact_lines_align() {
+        let short = Verse::new("Genesis", 1, 1, "In the beginning God created the heaven and the earth.");
+        let long = Verse::new("1 Thessalonians", 5, 23, "And the very God of peace sanctify you wholly; and I pray God your whole spirit and soul and body be preserved blameless unto the coming of our Lord Jesus Christ.");
+
+        let lines = [compact_line(&short, &[], 60, false), compact_line(&long, &[], 60, false)];
+        assert_eq!(lines[0], "Genesis 1:1          In the beginning God created the heave…");
+        assert_eq!(lines[1], "1 Thessalonians 5:23 And the very God of peace sanctify you…");
+        // The text starts in the same column and every line fits the width
+        for (line, verse) in lines.iter().zip([&short, &long]) {
+            assert_eq!(line[..=COMPACT_REFERENCE_WIDTH].trim_end(), verse.reference());
+            assert!(line[COMPACT_REFERENCE_WIDTH + 1..].starts_with(&verse.text[..20]));
+            assert_eq!(line.chars().count(), 60);
+        }
+        // Text that fits is left whole
+        assert_eq!(compact_line(&short, &[], 200, false), format!("{:<20} {}", "Genesis 1:1", short.text));
+    }
+}
@@ -0,0 +1,169 @@
+// serve.rs
+// A resident mode for editors and GUIs (--serve): the Bible is loaded once, then each line of
+// standard input is a JSON request answered by one line of JSON on standard output
+
+use std::io::{self, BufRead, Write};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::bible::Bible;
+use crate::error::BibleError;
+use crate::filter::VerseFilter;
+use crate::output::{OutputFormat, VerseRecord};
+use crate::random::{random_verse, Rng};
+use crate::reference::{invalid_reference_reason, parse_reference};
+use crate::search::{search_query, SearchOptions};
+use crate::similarity::{cross_references_of, XrefOptions};
+use crate::synonyms::SynonymMapper;
+
+/// What a request asks for, chosen by its `"type"` field
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Request {
+    /// `{"type": "search", "query": "faith hope", "limit": 10, "synonyms": true}`
+    Search {
+        query: String,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        synonyms: bool,
+    },
+    /// `{"type": "reference", "reference": "John 3:16-18"}`
+    Reference { reference: String },
+    /// `{"type": "random", "book": "Psalms", "seed": 7}`
+    Random {
+        #[serde(default)]
+        book: Option<String>,
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    /// `{"type": "cross-references", "reference": "John 3:16", "similarity": "2-gram", "limit": 5}`
+    CrossReferences {
+        reference: String,
+        #[serde(default)]
+        similarity: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+}
+
+// A request line: the request, and an optional "id" of any JSON type echoed in the response
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    request: Request,
+}
+
+/// Cross-references returned when a request gives no limit
+pub const DEFAULT_XREF_LIMIT: usize = 10;
+
+/// Answer one request with `{"count": N, "results": [...]}`, results written as for
+/// `--format json`. Requests without a seed draw from `rng`.
+pub fn handle_request(bible: &Bible, synonym_mapper: &SynonymMapper, request: &Request, rng: &mut Rng) -> Result<Value, BibleError> {
+    let records: Vec<VerseRecord> = match request {
+        Request::Search { query, limit, synonyms } => {
+            let options = SearchOptions { use_synonyms: *synonyms, limit: *limit, format: OutputFormat::Json, ..Default::default() };
+            search_query(bible, synonym_mapper, query, &options)
+                .into_iter()
+                .map(|hit| VerseRecord { matches: hit.matches, ..VerseRecord::new(hit.verse, bible.note_display()) })
+                .collect()
+        }
+        Request::Reference { reference } => {
+            let parsed = parse_reference(reference).ok_or_else(|| BibleError::InvalidReference {
+                reason: invalid_reference_reason(reference),
+                expected: "'Book', 'Book Chapter', 'Book Chapter-Chapter', 'Book Chapter:Verse' or 'Book Chapter:Verse-Verse'",
+            })?;
+            let verses = bible.passage(&parsed);
+            if verses.is_empty() {
+                return Err(BibleError::NotFound { message: format!("{} not found.", parsed), nearest: Vec::new() });
+            }
+            verses.iter().map(|verse| VerseRecord::new(verse, bible.note_display())).collect()
+        }
+        Request::Random { book, seed } => {
+            let filter = VerseFilter { book: book.clone(), ..Default::default() };
+            let verse = match seed {
+                Some(seed) => random_verse(bible, &filter, &mut Rng::new(*seed)),
+                None => random_verse(bible, &filter, rng),
+            };
+            verse.into_iter().map(|verse| VerseRecord::new(verse, bible.note_display())).collect()
+        }
+        Request::CrossReferences { reference, similarity, limit } => {
+            let options = XrefOptions {
+                similarity: similarity.clone().unwrap_or_else(|| XrefOptions::default().similarity),
+                limit: Some(limit.unwrap_or(DEFAULT_XREF_LIMIT)),
+                ..Default::default()
+            };
+            cross_references_of(bible, synonym_mapper, reference, &options)?
+                .xrefs
+                .into_iter()
+                .map(|xref| VerseRecord { score: Some(xref.score), ..VerseRecord::new(xref.verse, bible.note_display()) })
+                .collect()
+        }
+    };
+    Ok(json!({ "count": records.len(), "results": records }))
+}
+
+/// The response to one request line: the results, or the error as `BibleError::to_json` gives
+/// it (`InvalidRequest` for a line that isn't a request), with the request's "id" when it had one
+pub fn respond(bible: &Bible, synonym_mapper: &SynonymMapper, line: &str, rng: &mut Rng) -> Value {
+    let (id, mut response) = match serde_json::from_str::<Envelope>(line) {
+        Ok(Envelope { id, request }) => {
+            let response = handle_request(bible, synonym_mapper, &request, rng).unwrap_or_else(|e| e.to_json());
+            (id, response)
+        }
+        Err(e) => (None, json!({ "error": "InvalidRequest", "message": e.to_string(), "exit_code": 2 })),
+    };
+    if let Some(id) = id {
+        response["id"] = id;
+    }
+    response
+}
+
+/// Answer each line of `input` with a line of `output` until the input ends, flushing after
+/// every response so a client can wait for it. Blank lines are skipped. Returns the number of
+/// requests answered.
+pub fn serve(bible: &Bible, synonym_mapper: &SynonymMapper, input: impl BufRead, mut output: impl Write, rng: &mut Rng) -> io::Result<usize> {
+    let mut answered = 0;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", respond(bible, synonym_mapper, &line, rng))?;
+        output.flush()?;
+        answered += 1;
+    }
+    Ok(answered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bible::Verse;
+
+    #[test]
+    fn test_requests() {
+        let bible = Bible::new(vec![
+            Verse::new("John", 3, 16, "For God so loved the world, that he gave his only begotten Son,"),
+            Verse::new("John", 3, 17, "For God sent not his Son into the world to condemn the world;"),
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+        ]);
+        let mapper = SynonymMapper::new();
+        let mut rng = Rng::new(1);
+        let mut ask = |line: &str| respond(&bible, &mapper, line, &mut rng);
+
+        let found = ask(r#"{"type": "search", "query": "world", "id": 7}"#);
+        assert_eq!((found["count"].clone(), found["id"].clone()), (json!(2), json!(7)));
+        assert_eq!(found["results"][0]["matches"][0]["term"], "world");
+        assert_eq!(ask(r#"{"type": "reference", "reference": "jn 3:17"}"#)["results"][0]["reference"], "John 3:17");
+        assert_eq!(ask(r#"{"type": "random", "book": "Psalms"}"#)["results"][0]["reference"], "Psalms 23:1");
+        let xrefs = ask(r#"{"type": "cross-references", "reference": "John 3:16", "similarity": "0.1"}"#);
+        assert_eq!(xrefs["results"][0]["reference"], "John 3:17");
+        assert!(xrefs["results"][0]["score"].as_f64().unwrap() > 0.0);
+
+        assert_eq!(ask(r#"{"type": "reference", "reference": "Mark 1"}"#)["error"], "NotFound");
+        assert_eq!(ask(r#"{"type": "sing"}"#)["error"], "InvalidRequest");
+        assert_eq!(ask("John 3:16")["error"], "InvalidRequest");
+    }
+}
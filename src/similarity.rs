@@ -0,0 +1,1424 @@
+// similarity.rs
+// Scoring how similar two verses are: Jaccard word overlap, shared n-gram phrases and proper nouns
+
+use std::collections::HashSet;
+use colored::*;
+use lazy_static::lazy_static;
+use crate::bible::Verse;
+use crate::books::{resolve_book, testament_of};
+use crate::error::BibleError;
+use crate::lemma::lemmatize;
+use crate::reference::{invalid_reference_reason, parse_reference, Reference};
+use crate::synonyms::SynonymMapper;
+
+/// Ways of scoring how similar two verses are (see `--similarity`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimilarityMetric {
+    Jaccard(f32),  // Threshold value
+    NGram(usize),  // N-gram size (2-gram, 3-gram, etc.)
+    Names,         // Overlap of proper nouns (people and places)
+}
+
+/// Parse a `--similarity` value: a Jaccard threshold, `N-gram`, or `names`.
+/// Invalid values fall back to a 0.3 Jaccard threshold with a warning.
+pub fn parse_similarity_metric(s: &str) -> SimilarityMetric {
+    let s = s.trim().to_lowercase();
+
+    if s == "names" || s == "proper-nouns" {
+        return SimilarityMetric::Names;
+    }
+    
+    // Check for n-gram pattern
+    if s.ends_with("-gram") || s.ends_with("gram") {
+        let n_str = s.trim_end_matches("-gram").trim_end_matches("gram");
+        if let Ok(n) = n_str.parse::<usize>() {
+            if n > 0 {
+                return SimilarityMetric::NGram(n);
+            }
+        }
+    }
+    
+    // Otherwise treat as Jaccard threshold
+    match s.parse::<f32>() {
+        Ok(threshold) => SimilarityMetric::Jaccard(threshold.clamp(0.0, 1.0)),
+        Err(_) => {
+            eprintln!("Warning: Invalid similarity metric '{}', using default 0.3", s);
+            SimilarityMetric::Jaccard(0.3)
+        }
+    }
+}
+
+/// Describe a metric for display, e.g. "3-gram phrase matching"
+pub fn format_metric_description(metric: &SimilarityMetric) -> String {
+    match metric {
+        SimilarityMetric::Jaccard(threshold) => format!("similarity >= {:.1}%", threshold * 100.0),
+        SimilarityMetric::NGram(n) => format!("{}-gram phrase matching", n),
+        SimilarityMetric::Names => "shared proper nouns".to_string(),
+    }
+}
+
+/// The words a source verse is compared by, for --show-keywords: `Keywords (3): god, loved, world`
+pub fn format_keywords(keywords: &[String]) -> String {
+    if keywords.is_empty() {
+        "Keywords: none".to_string()
+    } else {
+        format!("Keywords ({}): {}", keywords.len(), keywords.join(", "))
+    }
+}
+
+/// Width in characters of a `--bars` similarity bar
+pub const BAR_WIDTH: usize = 10;
+
+/// Draw a fraction (0.0-1.0) as a bar of `width` cells, e.g. `██████░░░░` for 0.6.
+/// With color the filled part is green and the rest dimmed.
+pub fn similarity_bar(fraction: f32, width: usize, use_color: bool) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f32).round() as usize).min(width);
+    let (full, empty) = ("█".repeat(filled), "░".repeat(width - filled));
+    if use_color {
+        format!("{}{}", full.green(), empty.bright_black())
+    } else {
+        format!("{}{}", full, empty)
+    }
+}
+
+/// A verse similar to a source verse, as found by `cross_references` (or to a quoted text,
+/// as found by `find_like`)
+#[derive(Debug, Clone)]
+pub struct CrossReference<'a> {
+    pub verse: &'a Verse,
+    /// Jaccard, proper-noun or character n-gram overlap (0.0-1.0), or the number of shared n-grams
+    pub score: f32,
+    /// Byte spans of `verse.text` shared with the source verse (n-gram metric only)
+    pub shared: Vec<(usize, usize)>,
+}
+
+/// A cross-reference of a source verse, as returned by `cross_references_of`, with what the
+/// two verses have in common
+#[derive(Debug, Clone)]
+pub struct Xref<'a> {
+    pub verse: &'a Verse,
+    /// As for `CrossReference`: an overlap fraction, or a count of shared phrases for n-grams
+    pub score: f32,
+    /// The metric the score was computed with
+    pub metric: SimilarityMetric,
+    /// What the verse shares with the source: the words as compared for Jaccard (lowercased,
+    /// and mapped through synonyms when used), the proper nouns for names, or the phrases
+    /// quoted from `verse.text` for n-grams
+    pub shared: Vec<String>,
+    /// Byte spans of the shared phrases in `verse.text` (n-gram metric only), for highlighting
+    pub spans: Vec<(usize, usize)>,
+    /// How the score was reached (see `format_explanation`)
+    pub detail: ScoreDetail,
+}
+
+/// What a cross-reference's score is made of (--explain-ranking)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreDetail {
+    /// Jaccard and names: the score is `shared / union`, the words (or names) the verses have in
+    /// common over the distinct words either has
+    Overlap { shared: usize, union: usize },
+    /// N-grams: the distinct shared n-grams, each adding 1 to the score
+    Phrases(Vec<String>),
+}
+
+/// A cross-reference's score broken down, printed under it by --explain-ranking:
+/// `3 shared / 7 words in either = 42.9%: good, sheep, shepherd` for Jaccard and names, or
+/// `2 shared 2-grams, 1 each: "good shepherd", "the sheep"` for n-grams
+pub fn format_explanation(xref: &Xref) -> String {
+    match &xref.detail {
+        ScoreDetail::Overlap { shared, union } => {
+            let unit = if xref.metric == SimilarityMetric::Names { "names" } else { "words" };
+            format!("{} shared / {} {} in either = {:.1}%: {}", shared, union, unit, overlap_fraction(*shared, *union) * 100.0, xref.shared.join(", "))
+        }
+        ScoreDetail::Phrases(phrases) => {
+            let n = match xref.metric {
+                SimilarityMetric::NGram(n) => n,
+                _ => 0,
+            };
+            let quoted: Vec<String> = phrases.iter().map(|phrase| format!("\"{}\"", phrase)).collect();
+            format!("{} shared {}-grams, 1 each: {}", phrases.len(), n, quoted.join(", "))
+        }
+    }
+}
+
+/// Which verses are candidates for cross-references (see `--xref-scope`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum XrefScope {
+    #[default]
+    All,
+    /// Only verses from the source verse's book
+    Book,
+    /// Only verses from the source verse's testament
+    Testament,
+}
+
+impl XrefScope {
+    pub fn parse(s: &str) -> Option<XrefScope> {
+        match s.to_lowercase().as_str() {
+            "all" => Some(XrefScope::All),
+            "book" => Some(XrefScope::Book),
+            "testament" => Some(XrefScope::Testament),
+            _ => None,
+        }
+    }
+
+    /// Whether `candidate` is in scope for cross-references from `source`
+    pub fn includes(&self, source: &Verse, candidate: &Verse) -> bool {
+        let same_book = || candidate.book.eq_ignore_ascii_case(&source.book);
+        match self {
+            XrefScope::All => true,
+            XrefScope::Book => same_book(),
+            // Books outside the canon have no testament; fall back to the same book
+            XrefScope::Testament => match testament_of(&source.book) {
+                Some(testament) => testament_of(&candidate.book) == Some(testament),
+                None => same_book(),
+            },
+        }
+    }
+}
+
+/// Settings for a cross-reference search
+#[derive(Debug, Clone)]
+pub struct XrefOptions {
+    /// A `--similarity` value: Jaccard threshold, `N-gram` or `names`
+    pub similarity: String,
+    pub use_synonyms: bool,
+    pub scope: XrefScope,
+    /// Most matches to return; 0 means no limit
+    pub limit: Option<usize>,
+    /// Return the K most similar verses, ignoring the Jaccard threshold; 0, as for `limit`,
+    /// keeps every verse sharing anything
+    pub top: Option<usize>,
+    /// Which words are compared (Jaccard and n-gram metrics)
+    pub words: WordFilter,
+}
+
+impl Default for XrefOptions {
+    fn default() -> Self {
+        XrefOptions { similarity: "0.3".to_string(), use_synonyms: false, scope: XrefScope::All, limit: None, top: None, words: WordFilter::default() }
+    }
+}
+
+impl XrefOptions {
+    /// How many matches to keep: `top`, else `limit`; `None` when every match is kept
+    pub fn kept(&self) -> Option<usize> {
+        self.top.or(self.limit).filter(|&kept| kept > 0)
+    }
+}
+
+/// Score the other verses in scope against `source_verse` with the metric named by
+/// `options.similarity` (see `--similarity`) and return the matches, best first, up to the limit
+pub fn cross_references<'a>(bible: &'a [Verse], synonym_mapper: &SynonymMapper, source_verse: &Verse, options: &XrefOptions) -> Vec<CrossReference<'a>> {
+    XrefCorpus::new(bible, synonym_mapper, options).cross_references(source_verse, options)
+}
+
+/// The verse a cross-reference search starts from: `reference` must name a single verse of
+/// `bible`
+pub fn source_verse<'a>(bible: &'a [Verse], reference: &str) -> Result<&'a Verse, BibleError> {
+    let invalid = |reason: String| BibleError::InvalidReference { reason, expected: "'Book Chapter:Verse'" };
+    let reference = match parse_reference(reference) {
+        Some(r @ Reference::Verse(..)) => r,
+        Some(r) => return Err(invalid(format!("'{}' is not a single verse", r))),
+        None => return Err(invalid(invalid_reference_reason(reference))),
+    };
+    bible.iter().find(|v| reference.contains(v)).ok_or_else(|| BibleError::NotFound {
+        message: "Source verse not found.".to_string(),
+        nearest: Vec::new(),
+    })
+}
+
+/// The cross-references of a source verse, as returned by `cross_references_of`
+#[derive(Debug, Clone)]
+pub struct XrefResults<'a> {
+    pub source: &'a Verse,
+    /// What the source is compared by: its words, or its proper nouns for the names metric
+    pub keywords: Vec<String>,
+    /// Best first
+    pub xrefs: Vec<Xref<'a>>,
+}
+
+/// The verse at `reference` and its cross-references, best first, each with the words or
+/// phrases it shares with the source: `cross_references` for library users, with the
+/// errors the command line reports. A source with nothing to compare (no significant words,
+/// or no proper nouns for the names metric) is a `NoResults` error; finding no similar verse
+/// is an empty list.
+pub fn cross_references_of<'a>(bible: &'a [Verse], synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions) -> Result<XrefResults<'a>, BibleError> {
+    let source = source_verse(bible, reference)?;
+    let corpus = XrefCorpus::new(bible, synonym_mapper, options);
+    let tokens = corpus.source_tokens(source);
+    if tokens.words.is_empty() {
+        return Err(BibleError::NoResults {
+            message: "No significant words found in source verse.".to_string(),
+            hint: Some("Try --no-stopwords or a lower --min-word-len".to_string()),
+        });
+    }
+    if corpus.metric == SimilarityMetric::Names && tokens.names.is_empty() {
+        return Err(BibleError::NoResults { message: "No proper nouns found in source verse.".to_string(), hint: None });
+    }
+    let xrefs = corpus.xrefs(source, &tokens, options);
+    // The names metric compares proper nouns rather than words
+    let keywords = match corpus.metric {
+        SimilarityMetric::Names => tokens.names,
+        _ => tokens.words,
+    };
+    Ok(XrefResults { source, keywords, xrefs })
+}
+
+// How many distinct words two sorted word lists have in common, and how many they have
+// between them, counted by walking both at once
+fn sorted_overlap(words1: &[String], words2: &[String]) -> (usize, usize) {
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < words1.len() && j < words2.len() {
+        match words1[i].cmp(&words2[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (shared, words1.len() + words2.len() - shared)
+}
+
+// The words two sorted word lists have in common, in order
+fn sorted_shared(words1: &[String], words2: &[String]) -> Vec<String> {
+    words1.iter().filter(|word| words2.binary_search(word).is_ok()).cloned().collect()
+}
+
+// The distinct phrases of n-gram matches, in order of first appearance
+fn distinct_phrases(matches: &[NgramMatch]) -> Vec<String> {
+    let mut phrases: Vec<String> = Vec::new();
+    for m in matches {
+        if !phrases.contains(&m.phrase) {
+            phrases.push(m.phrase.clone());
+        }
+    }
+    phrases
+}
+
+/// A source verse split the way an `XrefCorpus` compares verses (see `XrefCorpus::source_tokens`)
+#[derive(Debug, Clone)]
+pub struct SourceTokens {
+    /// Its distinct words, sorted, as `extract_words` gives them
+    pub words: Vec<String>,
+    /// Its proper nouns, sorted (corpora built for the names metric only)
+    pub names: Vec<String>,
+    // Its n-grams and every variation of them (n-gram metric only)
+    ngrams: HashSet<Vec<String>>,
+}
+
+/// Every verse tokenized once for one metric, so cross-references for many source verses
+/// (see `book_cross_references`) don't split and lowercase the whole Bible for each of them
+pub struct XrefCorpus<'a, 'm> {
+    verses: &'a [Verse],
+    synonym_mapper: &'m SynonymMapper,
+    metric: SimilarityMetric,
+    use_synonyms: bool,
+    filter: WordFilter,
+    // Only the names metric needs the vocabulary; building it scans every verse
+    vocabulary: HashSet<String>,
+    // Per verse, for the metrics the corpus was built for: its sorted words (Jaccard), sorted
+    // names (names) and n-grams (n-gram, of `ngram_size` words)
+    words: Vec<Vec<String>>,
+    names: Vec<Vec<String>>,
+    ngrams: Vec<Vec<Ngram>>,
+    ngram_size: Option<usize>,
+}
+
+impl<'a, 'm> XrefCorpus<'a, 'm> {
+    pub fn new(verses: &'a [Verse], synonym_mapper: &'m SynonymMapper, options: &XrefOptions) -> Self {
+        Self::with_metrics(verses, synonym_mapper, options, &[parse_similarity_metric(&options.similarity)])
+    }
+
+    // A corpus that scores by any of `metrics` (`cross_references` uses the first), with each
+    // verse split once for all of them
+    fn with_metrics(verses: &'a [Verse], synonym_mapper: &'m SynonymMapper, options: &XrefOptions, metrics: &[SimilarityMetric]) -> Self {
+        let (use_synonyms, filter) = (options.use_synonyms, options.words);
+        let by_words = metrics.iter().any(|metric| matches!(metric, SimilarityMetric::Jaccard(_)));
+        let by_names = metrics.contains(&SimilarityMetric::Names);
+        let ngram_size = metrics.iter().find_map(|metric| match metric {
+            SimilarityMetric::NGram(n) => Some(*n),
+            _ => None,
+        });
+
+        let (mut words, mut ngrams) = (Vec::new(), Vec::new());
+        let mut vocabulary = HashSet::new();
+        let mut candidates: Vec<Vec<&'a str>> = Vec::new();
+        for verse in verses {
+            let raw = raw_tokens(&verse.text);
+            if by_words || ngram_size.is_some() {
+                let tokens = kept_tokens(&raw, filter);
+                if by_words {
+                    words.push(words_of_tokens(&tokens, synonym_mapper, use_synonyms));
+                }
+                if let Some(n) = ngram_size {
+                    ngrams.push(ngrams_of_tokens(&tokens, n, synonym_mapper, use_synonyms));
+                }
+            }
+            if by_names {
+                vocabulary.extend(raw.iter().filter(|token| token.is_lowercase()).map(|token| token.word.to_string()));
+                candidates.push(raw.iter().filter(|token| token.may_be_name()).map(|token| token.word).collect());
+            }
+        }
+        // Which candidates are names is only known once the whole vocabulary is in
+        let names = candidates.into_iter().map(|candidates| names_among(candidates, &vocabulary)).collect();
+
+        let metric = metrics[0];
+        XrefCorpus { verses, synonym_mapper, metric, use_synonyms, filter, vocabulary, words, names, ngrams, ngram_size }
+    }
+
+    /// Split `verse` the way the corpus compares verses, for `xrefs`
+    pub fn source_tokens(&self, verse: &Verse) -> SourceTokens {
+        let raw = raw_tokens(&verse.text);
+        let tokens = kept_tokens(&raw, self.filter);
+        let words = words_of_tokens(&tokens, self.synonym_mapper, self.use_synonyms);
+        let names = if self.names.is_empty() {
+            Vec::new()
+        } else {
+            names_among(raw.iter().filter(|token| token.may_be_name()).map(|token| token.word), &self.vocabulary)
+        };
+        let ngrams = match self.ngram_size {
+            Some(n) => ngrams_of_tokens(&tokens, n, self.synonym_mapper, self.use_synonyms)
+                .into_iter()
+                .flat_map(|ngram| ngram.variations)
+                .collect(),
+            None => HashSet::new(),
+        };
+        SourceTokens { words, names, ngrams }
+    }
+
+    /// The verses in scope most like `source_verse`, best first, up to the limit (see
+    /// `cross_references`). The metric, synonyms and word filter are the ones the corpus was
+    /// built with.
+    pub fn cross_references(&self, source_verse: &Verse, options: &XrefOptions) -> Vec<CrossReference<'a>> {
+        self.xrefs(source_verse, &self.source_tokens(source_verse), options)
+            .into_iter()
+            .map(|xref| CrossReference { verse: xref.verse, score: xref.score, shared: xref.spans })
+            .collect()
+    }
+
+    /// As `cross_references`, for a `source_verse` already split by `source_tokens`, with what
+    /// each match shares with it and how its score was reached
+    pub fn xrefs(&self, source_verse: &Verse, source: &SourceTokens, options: &XrefOptions) -> Vec<Xref<'a>> {
+        self.xrefs_by(self.metric, source_verse, source, options)
+    }
+
+    // `xrefs` by one of the metrics the corpus was built for
+    fn xrefs_by(&self, metric: SimilarityMetric, source_verse: &Verse, source: &SourceTokens, options: &XrefOptions) -> Vec<Xref<'a>> {
+        let (scope, top) = (options.scope, options.top);
+        if source.words.is_empty() || (metric == SimilarityMetric::Names && source.names.is_empty()) {
+            return Vec::new();
+        }
+
+        // Score all other verses; each match keeps how its score was reached, and the
+        // n-grams it shares with the source verse (n-gram metric only)
+        let mut scored: Vec<(usize, f32, ScoreDetail, Vec<NgramMatch>)> = Vec::new();
+        for (index, v) in self.verses.iter().enumerate() {
+            let is_source = v.book.eq_ignore_ascii_case(&source_verse.book) && v.chapter == source_verse.chapter && v.verse == source_verse.verse;
+            if is_source || !scope.includes(source_verse, v) {
+                continue;
+            }
+            let score = match metric {
+                SimilarityMetric::Jaccard(threshold) => {
+                    let (shared, union) = sorted_overlap(&source.words, &self.words[index]);
+                    let sim = overlap_fraction(shared, union);
+                    // With --top any overlap qualifies; the ranking decides what is kept
+                    let qualifies = if top.is_some() { sim > 0.0 } else { sim >= threshold };
+                    qualifies.then(|| (sim, ScoreDetail::Overlap { shared, union }, Vec::new()))
+                }
+                SimilarityMetric::Names => {
+                    let (shared, union) = sorted_overlap(&source.names, &self.names[index]);
+                    let sim = overlap_fraction(shared, union);
+                    (sim > 0.0).then(|| (sim, ScoreDetail::Overlap { shared, union }, Vec::new()))
+                }
+                SimilarityMetric::NGram(_) => {
+                    let matches = shared_ngrams(&source.ngrams, &self.ngrams[index]);
+                    let phrases = distinct_phrases(&matches);
+                    (!phrases.is_empty()).then_some((phrases.len() as f32, ScoreDetail::Phrases(phrases), matches))
+                }
+            };
+            if let Some((score, detail, matches)) = score {
+                scored.push((index, score, detail, matches));
+            }
+        }
+
+        // Sort by similarity (highest first)
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        // Apply limit if specified (0 is no limit); --top K keeps the K best
+        if let Some(kept) = options.kept() {
+            scored.truncate(kept);
+        }
+
+        // Only the matches kept need their shared words listed
+        scored
+            .into_iter()
+            .map(|(index, score, detail, matches)| {
+                let verse = &self.verses[index];
+                let spans = ngram_highlight_spans(&matches);
+                let shared = match metric {
+                    SimilarityMetric::Jaccard(_) => sorted_shared(&source.words, &self.words[index]),
+                    SimilarityMetric::Names => sorted_shared(&source.names, &self.names[index]),
+                    // The phrases are merged for display; the score counts the n-grams
+                    SimilarityMetric::NGram(_) => {
+                        let mut phrases: Vec<String> = Vec::new();
+                        for &(start, end) in &spans {
+                            let phrase = verse.text[start..end].to_string();
+                            if !phrases.contains(&phrase) {
+                                phrases.push(phrase);
+                            }
+                        }
+                        phrases
+                    }
+                };
+                Xref { verse, score, metric, shared, spans, detail }
+            })
+            .collect()
+    }
+}
+
+/// Cross-references for every verse of `book`, in order, computed over one shared corpus.
+/// `progress` is called with the number of verses done and the total after each verse.
+pub fn book_cross_references<'a>(
+    corpus: &XrefCorpus<'a, '_>,
+    book: &str,
+    options: &XrefOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<(&'a Verse, Vec<CrossReference<'a>>)> {
+    // "Psalms" finds a Bible's "Psalm", and abbreviations work
+    let wanted = resolve_book(book);
+    let sources: Vec<&'a Verse> = corpus
+        .verses
+        .iter()
+        .filter(|v| v.book.eq_ignore_ascii_case(book) || (wanted.is_some() && resolve_book(&v.book) == wanted))
+        .collect();
+    let mut results = Vec::with_capacity(sources.len());
+    for (done, source) in sources.iter().enumerate() {
+        results.push((*source, corpus.cross_references(source, options)));
+        progress(done + 1, sources.len());
+    }
+    results
+}
+
+/// A chapter similar to a source chapter, as found by `chapter_cross_references`
+#[derive(Debug, Clone)]
+pub struct ChapterXref<'a> {
+    /// The chapter's verses, in order
+    pub verses: &'a [Verse],
+    /// As for `CrossReference`, computed over the chapters' whole text
+    pub score: f32,
+    /// The words, proper nouns or n-gram phrases the chapter shares with the source, in order
+    pub shared: Vec<String>,
+}
+
+impl ChapterXref<'_> {
+    /// The chapter's reference, e.g. "Genesis 2"
+    pub fn reference(&self) -> String {
+        format!("{} {}", self.verses[0].book, self.verses[0].chapter)
+    }
+}
+
+// A chapter's text as one metric sees it: its sorted words or names, or its n-grams
+enum ChapterTokens {
+    Words(Vec<String>),
+    Ngrams(Vec<Ngram>),
+}
+
+/// The chapters most like the chapter at `reference` (--cross-references-chapter), best first,
+/// for finding parallel passages such as Kings and Chronicles. Each chapter's verses are joined
+/// into one text and compared with `options.similarity`'s metric, so n-grams can run across
+/// verse breaks. Whole chapters share a smaller part of their words than verses do, so every
+/// chapter in scope sharing anything is ranked and `options.top` (or `limit`) keeps the best;
+/// a Jaccard threshold is not applied.
+pub fn chapter_cross_references<'a>(bible: &'a [Verse], synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions) -> Result<Vec<ChapterXref<'a>>, BibleError> {
+    let reference = match parse_reference(reference) {
+        Some(r @ Reference::Chapter(..)) => r,
+        Some(r) => return Err(BibleError::InvalidReference { reason: format!("'{}' is not a single chapter", r), expected: "'Book Chapter'" }),
+        None => return Err(BibleError::InvalidReference { reason: invalid_reference_reason(reference), expected: "'Book Chapter'" }),
+    };
+    let chapters: Vec<&'a [Verse]> = bible.chunk_by(|a, b| a.book == b.book && a.chapter == b.chapter).collect();
+    let Some(source_index) = chapters.iter().position(|chapter| reference.contains(&chapter[0])) else {
+        return Err(BibleError::NotFound { message: format!("{} not found.", reference), nearest: Vec::new() });
+    };
+
+    // Every chapter is tokenized once
+    let metric = parse_similarity_metric(&options.similarity);
+    let vocabulary = match metric {
+        SimilarityMetric::Names => lowercase_vocabulary(bible),
+        _ => HashSet::new(),
+    };
+    let tokens: Vec<ChapterTokens> = chapters
+        .iter()
+        .map(|chapter| {
+            let text = chapter.iter().map(|v| v.text.as_str()).collect::<Vec<_>>().join(" ");
+            match metric {
+                SimilarityMetric::Jaccard(_) => ChapterTokens::Words(extract_words(&text, synonym_mapper, options.use_synonyms, options.words)),
+                SimilarityMetric::Names => ChapterTokens::Words(extract_names(&text, &vocabulary)),
+                SimilarityMetric::NGram(n) => ChapterTokens::Ngrams(extract_ngrams(&text, n, synonym_mapper, options.use_synonyms, options.words)),
+            }
+        })
+        .collect();
+    let source_ngrams: HashSet<Vec<String>> = match &tokens[source_index] {
+        ChapterTokens::Ngrams(ngrams) => ngrams.iter().flat_map(|ngram| ngram.variations.iter().cloned()).collect(),
+        ChapterTokens::Words(_) => HashSet::new(),
+    };
+
+    let source = chapters[source_index];
+    let mut xrefs: Vec<ChapterXref<'a>> = Vec::new();
+    for (index, chapter) in chapters.iter().enumerate() {
+        if index == source_index || !options.scope.includes(&source[0], &chapter[0]) {
+            continue;
+        }
+        let (score, shared) = match (&tokens[source_index], &tokens[index]) {
+            (ChapterTokens::Words(source_words), ChapterTokens::Words(words)) => {
+                let score = sorted_jaccard_similarity(source_words, words);
+                (score, if score > 0.0 { sorted_shared(source_words, words) } else { Vec::new() })
+            }
+            (_, ChapterTokens::Ngrams(ngrams)) => {
+                let phrases = distinct_phrases(&shared_ngrams(&source_ngrams, ngrams));
+                (phrases.len() as f32, phrases)
+            }
+            _ => (0.0, Vec::new()),
+        };
+        if score > 0.0 {
+            xrefs.push(ChapterXref { verses: chapter, score, shared });
+        }
+    }
+
+    xrefs.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    if let Some(kept) = options.kept() {
+        xrefs.truncate(kept);
+    }
+    Ok(xrefs)
+}
+
+/// One metric's best matches in a `compare_metrics` comparison
+#[derive(Debug, Clone)]
+pub struct MetricRanking<'a> {
+    pub metric: SimilarityMetric,
+    /// The matches, best first
+    pub xrefs: Vec<Xref<'a>>,
+}
+
+/// The verses most like the verse at `reference` by each metric at once (--xref-compare), for
+/// seeing how their rankings differ: Jaccard word overlap, n-gram phrases (`options.similarity`'s
+/// n when it names one, else 2) and proper nouns. Every verse in scope sharing anything is
+/// ranked, as with --top, and `options.top` (or `limit`, 5 if neither) of each are kept. Each
+/// verse is split once for all three metrics.
+pub fn compare_metrics<'a>(bible: &'a [Verse], synonym_mapper: &SynonymMapper, reference: &str, options: &XrefOptions) -> Result<Vec<MetricRanking<'a>>, BibleError> {
+    let source = source_verse(bible, reference)?;
+    let n = match parse_similarity_metric(&options.similarity) {
+        SimilarityMetric::NGram(n) => n,
+        _ => 2,
+    };
+    // The Jaccard ranking ignores the threshold, so it carries none
+    let metrics = [SimilarityMetric::Jaccard(0.0), SimilarityMetric::NGram(n), SimilarityMetric::Names];
+    let corpus = XrefCorpus::with_metrics(bible, synonym_mapper, options, &metrics);
+    let tokens = corpus.source_tokens(source);
+    if tokens.words.is_empty() {
+        return Err(BibleError::NoResults {
+            message: "No significant words found in source verse.".to_string(),
+            hint: Some("Try --no-stopwords or a lower --min-word-len".to_string()),
+        });
+    }
+
+    let ranked = XrefOptions { top: Some(options.kept().unwrap_or(5)), ..options.clone() };
+    let rankings = metrics
+        .into_iter()
+        .map(|metric| MetricRanking { metric, xrefs: corpus.xrefs_by(metric, source, &tokens, &ranked) })
+        .collect();
+    Ok(rankings)
+}
+
+impl XrefCorpus<'_, '_> {
+    /// Describe the metric for display, e.g. "3-gram phrase matching"
+    pub fn description(&self) -> String {
+        format_metric_description(&self.metric)
+    }
+}
+
+// Jaccard similarity of two sorted, deduplicated word lists; the same score as
+// `calculate_jaccard_similarity` without building sets
+fn sorted_jaccard_similarity(words1: &[String], words2: &[String]) -> f32 {
+    let (shared, union) = sorted_overlap(words1, words2);
+    overlap_fraction(shared, union)
+}
+
+// Shared words over the distinct words either has; nothing at all scores 0.0
+fn overlap_fraction(shared: usize, union: usize) -> f32 {
+    if union == 0 {
+        0.0
+    } else {
+        shared as f32 / union as f32
+    }
+}
+
+lazy_static! {
+    // Common words left out of comparisons (stop words)
+    static ref STOP_WORDS: HashSet<&'static str> = [
+        "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from",
+        "has", "he", "in", "is", "it", "its", "of", "on", "that", "the", "to",
+        "was", "will", "with", "shall", "unto", "thee", "thou", "thy", "ye",
+        "hath", "his", "her", "him", "them", "they", "their", "all", "not",
+        "which", "there", "this", "these", "those", "when", "who", "what",
+        "into", "upon", "out", "up", "have", "had", "do", "did", "done",
+        "said", "came", "went", "been", "were", "being", "of"
+    ].iter().cloned().collect();
+}
+
+/// Which words of a verse count when comparing it with others, and how (see `--no-stopwords`,
+/// `--min-word-len` and `--lemmatize`). The default drops stop words and words under three
+/// letters, and compares words as written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordFilter {
+    pub drop_stopwords: bool,
+    /// Shortest word kept, in letters
+    pub min_len: usize,
+    /// Compare words by their dictionary form ("loved" and "loving" as "love"; see `lemma::lemmatize`)
+    pub lemmatize: bool,
+}
+
+impl Default for WordFilter {
+    fn default() -> Self {
+        WordFilter { drop_stopwords: true, min_len: 3, lemmatize: false }
+    }
+}
+
+impl WordFilter {
+    /// Keep every word, however short or common
+    pub fn all_words() -> Self {
+        WordFilter { drop_stopwords: false, min_len: 1, lemmatize: false }
+    }
+
+    fn keeps(&self, word: &str) -> bool {
+        word.chars().count() >= self.min_len && !(self.drop_stopwords && STOP_WORDS.contains(word))
+    }
+}
+
+/// Significant words of a text in reading order, lowercased, with their byte spans in the text
+pub fn significant_tokens(text: &str) -> Vec<(String, usize, usize)> {
+    filtered_tokens(text, WordFilter::default())
+}
+
+// A word of a text as written, without the punctuation around it, and its byte offset
+struct RawToken<'t> {
+    word: &'t str,
+    start: usize,
+    // Whether the word starts the text or follows a `.`, `!` or `?`
+    sentence_start: bool,
+}
+
+impl RawToken<'_> {
+    fn is_lowercase(&self) -> bool {
+        self.word.chars().next().is_some_and(|c| c.is_lowercase())
+    }
+
+    // A capitalized word of more than one letter that doesn't start a sentence; it is a proper
+    // noun unless it also appears in lowercase (see `extract_names`)
+    fn may_be_name(&self) -> bool {
+        self.word.chars().next().is_some_and(|c| c.is_uppercase()) && !self.sentence_start && self.word.chars().count() > 1
+    }
+}
+
+// Split a text into its words once, for the word, n-gram and proper-noun metrics alike
+fn raw_tokens(text: &str) -> Vec<RawToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut sentence_start = true;
+    for raw in text.split_whitespace() {
+        let word = raw.trim_matches(|c: char| !c.is_alphabetic());
+        if !word.is_empty() {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            tokens.push(RawToken { word, start, sentence_start });
+        }
+        sentence_start = raw.ends_with(['.', '!', '?']);
+    }
+    tokens
+}
+
+/// The words of a text that `filter` keeps, in reading order, lowercased (and lemmatized when
+/// the filter asks for it), with their byte spans
+pub fn filtered_tokens(text: &str, filter: WordFilter) -> Vec<(String, usize, usize)> {
+    kept_tokens(&raw_tokens(text), filter)
+}
+
+// `filtered_tokens` from words already split off
+fn kept_tokens(raw: &[RawToken], filter: WordFilter) -> Vec<(String, usize, usize)> {
+    raw.iter()
+        .map(|token| (token.word.to_lowercase(), token.start, token.start + token.word.len()))
+        .filter(|(w, _, _)| filter.keeps(w))
+        .map(|(w, start, end)| if filter.lemmatize { (lemmatize(&w), start, end) } else { (w, start, end) })
+        .collect()
+}
+
+/// Extract the distinct words of a text that `filter` keeps (sorted), optionally expanding with synonyms
+pub fn extract_words(text: &str, synonym_mapper: &SynonymMapper, use_synonyms: bool, filter: WordFilter) -> Vec<String> {
+    words_of_tokens(&filtered_tokens(text, filter), synonym_mapper, use_synonyms)
+}
+
+// `extract_words` from tokens already split off, so one split can feed several metrics
+fn words_of_tokens(tokens: &[(String, usize, usize)], synonym_mapper: &SynonymMapper, use_synonyms: bool) -> Vec<String> {
+    let words: Vec<String> = tokens.iter().map(|(w, _, _)| w.clone()).collect();
+
+    if use_synonyms {
+        let mut expanded_words = Vec::new();
+        for word in words {
+            if let Some(synonyms) = synonym_mapper.group(&word) {
+                expanded_words.extend_from_slice(synonyms);
+            } else {
+                expanded_words.push(word);
+            }
+        }
+        expanded_words.sort();
+        expanded_words.dedup();
+        expanded_words
+    } else {
+        let mut unique_words = words;
+        unique_words.sort();
+        unique_words.dedup();
+        unique_words
+    }
+}
+
+/// Every word that appears in lowercase somewhere in the Bible. A capitalized word whose
+/// lowercase form is in here ("Arise", "God") is an ordinary word, not a proper noun.
+pub fn lowercase_vocabulary(bible: &[Verse]) -> HashSet<String> {
+    bible
+        .iter()
+        .flat_map(|v| raw_tokens(&v.text))
+        .filter(|token| token.is_lowercase())
+        .map(|token| token.word.to_string())
+        .collect()
+}
+
+/// Extract proper nouns: capitalized words, in original case, that don't start a sentence
+/// and never appear in lowercase (see `lowercase_vocabulary`)
+pub fn extract_names(text: &str, vocabulary: &HashSet<String>) -> Vec<String> {
+    names_among(raw_tokens(text).iter().filter(|token| token.may_be_name()).map(|token| token.word), vocabulary)
+}
+
+// The distinct candidates (see `RawToken::may_be_name`) never seen in lowercase, sorted
+fn names_among<'t>(candidates: impl IntoIterator<Item = &'t str>, vocabulary: &HashSet<String>) -> Vec<String> {
+    let mut names: Vec<String> = candidates
+        .into_iter()
+        .filter(|word| !vocabulary.contains(&word.to_lowercase()))
+        .map(|word| word.to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Jaccard similarity of two word sets: shared words / all words, from 0.0 (disjoint)
+/// to 1.0 (identical). Empty inputs score 0.0.
+pub fn calculate_jaccard_similarity(words1: &[String], words2: &[String]) -> f32 {
+    if words1.is_empty() || words2.is_empty() {
+        return 0.0;
+    }
+
+    let set1: HashSet<_> = words1.iter().collect();
+    let set2: HashSet<_> = words2.iter().collect();
+
+    let intersection = set1.intersection(&set2).count();
+    let union = set1.union(&set2).count();
+
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f32 / union as f32
+    }
+}
+
+/// A run of consecutive significant words and the byte span it covers in its text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ngram {
+    /// The words themselves first, then (with synonyms) every synonym variation of them
+    pub variations: Vec<Vec<String>>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Consecutive runs of n words that `filter` keeps, in reading order.
+/// With synonyms, each n-gram also carries every synonym variation of its words.
+pub fn extract_ngrams(text: &str, n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool, filter: WordFilter) -> Vec<Ngram> {
+    ngrams_of_tokens(&filtered_tokens(text, filter), n, synonym_mapper, use_synonyms)
+}
+
+// `extract_ngrams` from tokens already split off
+fn ngrams_of_tokens(tokens: &[(String, usize, usize)], n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> Vec<Ngram> {
+    if n == 0 || tokens.len() < n {
+        return vec![];
+    }
+    
+    let mut ngrams = Vec::new();
+    
+    for window in tokens.windows(n) {
+        let ngram: Vec<String> = window.iter().map(|(w, _, _)| w.clone()).collect();
+        let (start, end) = (window[0].1, window[n - 1].2);
+        
+        if use_synonyms {
+            // Generate all synonym variations of this n-gram
+            let mut variations = vec![ngram.clone()];
+            
+            for (idx, word) in ngram.iter().enumerate() {
+                if let Some(synonyms) = synonym_mapper.group(word) {
+                    let mut new_variations = Vec::new();
+                    for variation in &variations {
+                        for synonym in synonyms {
+                            let mut new_var = variation.clone();
+                            new_var[idx] = synonym.clone();
+                            new_variations.push(new_var);
+                        }
+                    }
+                    variations.extend(new_variations);
+                }
+            }
+            
+            ngrams.push(Ngram { variations, start, end });
+        } else {
+            ngrams.push(Ngram { variations: vec![ngram], start, end });
+        }
+    }
+    
+    ngrams
+}
+
+/// A phrase of one text that shares an n-gram with another
+#[derive(Debug, Clone, PartialEq)]
+pub struct NgramMatch {
+    /// The shared words, e.g. "only begotten son"
+    pub phrase: String,
+    /// Byte span of the phrase in the text it was found in
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find the phrases of text2 that share an n-gram with text1, in text2's reading order
+/// (n-grams of significant words, as by the default `WordFilter`)
+pub fn find_ngram_matches(text1: &str, text2: &str, n: usize, synonym_mapper: &SynonymMapper, use_synonyms: bool) -> Vec<NgramMatch> {
+    let filter = WordFilter::default();
+    let source: HashSet<Vec<String>> = extract_ngrams(text1, n, synonym_mapper, use_synonyms, filter)
+        .into_iter()
+        .flat_map(|ngram| ngram.variations)
+        .collect();
+
+    shared_ngrams(&source, &extract_ngrams(text2, n, synonym_mapper, use_synonyms, filter))
+}
+
+// The n-grams of a text that are among `source`'s n-grams (or their variations)
+fn shared_ngrams(source: &HashSet<Vec<String>>, ngrams: &[Ngram]) -> Vec<NgramMatch> {
+    ngrams
+        .iter()
+        .filter(|ngram| ngram.variations.iter().any(|v| source.contains(v)))
+        .map(|ngram| NgramMatch { phrase: ngram.variations[0].join(" "), start: ngram.start, end: ngram.end })
+        .collect()
+}
+
+/// Number of distinct shared n-grams, used as the n-gram similarity score
+pub fn count_distinct_phrases(matches: &[NgramMatch]) -> usize {
+    let phrases: HashSet<&str> = matches.iter().map(|m| m.phrase.as_str()).collect();
+    phrases.len()
+}
+
+/// Highlight spans covering every matched phrase; overlapping n-grams merge into one span
+pub fn ngram_highlight_spans(matches: &[NgramMatch]) -> Vec<(usize, usize)> {
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for m in matches {
+        match spans.last_mut() {
+            Some(last) if m.start <= last.1 => last.1 = last.1.max(m.end),
+            _ => spans.push((m.start, m.end)),
+        }
+    }
+    spans
+}
+
+/// Length of the character n-grams `--find-like` compares texts by
+pub const FIND_LIKE_NGRAM: usize = 3;
+
+/// The distinct runs of `n` characters in a text, lowercased, with punctuation dropped and
+/// words separated (and surrounded) by single spaces, so "Born," and "born" compare equal
+pub fn char_ngrams(text: &str, n: usize) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    if n == 0 || words.is_empty() {
+        return HashSet::new();
+    }
+    let chars: Vec<char> = format!(" {} ", words.join(" ")).chars().collect();
+    chars.windows(n).map(|window| window.iter().collect()).collect()
+}
+
+/// Dice coefficient of two n-gram sets: twice the shared n-grams over their total, from 0.0
+/// (nothing shared) to 1.0 (identical). Empty inputs score 0.0.
+pub fn dice_similarity(ngrams1: &HashSet<String>, ngrams2: &HashSet<String>) -> f32 {
+    if ngrams1.is_empty() || ngrams2.is_empty() {
+        return 0.0;
+    }
+    let shared = ngrams1.intersection(ngrams2).count();
+    2.0 * shared as f32 / (ngrams1.len() + ngrams2.len()) as f32
+}
+
+/// How closely a verse's n-grams match a quoted text's: the average of their Dice coefficient
+/// and the share of the quote found in the verse, so a quote of part of a long verse still
+/// scores well without long verses winning on size alone
+pub fn phrase_similarity(quote: &HashSet<String>, verse: &HashSet<String>) -> f32 {
+    if quote.is_empty() {
+        return 0.0;
+    }
+    let contained = quote.intersection(verse).count() as f32 / quote.len() as f32;
+    (dice_similarity(quote, verse) + contained) / 2.0
+}
+
+/// The `top` verses whose wording is closest to an arbitrary, perhaps misremembered, text,
+/// best first. Character n-grams tolerate changed word endings and small slips ("a time for
+/// being born") that whole-word matching would miss.
+pub fn find_like<'a>(bible: &'a [Verse], text: &str, top: Option<usize>) -> Vec<CrossReference<'a>> {
+    let source = char_ngrams(text, FIND_LIKE_NGRAM);
+    if source.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<CrossReference> = bible
+        .iter()
+        .filter_map(|verse| {
+            let score = phrase_similarity(&source, &char_ngrams(&verse.text, FIND_LIKE_NGRAM));
+            (score > 0.0).then_some(CrossReference { verse, score, shared: Vec::new() })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    if let Some(top) = top {
+        matches.truncate(top);
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity_bar() {
+        assert_eq!(similarity_bar(0.0, 10, false), "░░░░░░░░░░");
+        assert_eq!(similarity_bar(0.5, 10, false), "█████░░░░░");
+        assert_eq!(similarity_bar(1.0, 10, false), "██████████");
+        assert_eq!(similarity_bar(0.62, 6, false), "████░░");
+        // Out-of-range scores still draw a bar of the given width
+        assert_eq!(similarity_bar(1.5, 4, false).chars().count(), 4);
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cross_references_of() {
+        let bible = vec![
+            Verse::new("John", 10, 11, "I am the good shepherd: the good shepherd giveth his life for the sheep."),
+            Verse::new("John", 10, 14, "I am the good shepherd, and know my sheep, and am known of mine."),
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+            Verse::new("Genesis", 1, 1, "In the beginning God created the heaven and the earth."),
+        ];
+        let mapper = SynonymMapper::new();
+        let options = XrefOptions { similarity: "0.1".to_string(), ..Default::default() };
+
+        let results = cross_references_of(&bible, &mapper, "jn 10:11", &options).unwrap();
+        assert_eq!(results.source.reference(), "John 10:11");
+        assert_eq!(results.keywords, ["giveth", "good", "life", "sheep", "shepherd"]);
+        let xrefs = results.xrefs;
+        let found: Vec<String> = xrefs.iter().map(|xref| xref.verse.reference()).collect();
+        assert_eq!(found, ["John 10:14", "Psalms 23:1"]);
+        assert!(xrefs[0].score > xrefs[1].score);
+        assert_eq!(xrefs[0].metric, SimilarityMetric::Jaccard(0.1));
+        assert_eq!(xrefs[0].shared, ["good", "sheep", "shepherd"]);
+        assert_eq!(xrefs[1].shared, ["shepherd"]);
+
+        // N-gram results carry the shared phrases and where they are
+        let bigrams = XrefOptions { similarity: "2-gram".to_string(), ..Default::default() };
+        let xrefs = cross_references_of(&bible, &mapper, "John 10:11", &bigrams).unwrap().xrefs;
+        assert_eq!(xrefs[0].verse.reference(), "John 10:14");
+        assert_eq!(xrefs[0].shared, ["good shepherd"]);
+        assert_eq!(&xrefs[0].verse.text[xrefs[0].spans[0].0..xrefs[0].spans[0].1], "good shepherd");
+
+        // The explained overlap is what the score was computed from
+        for xref in &cross_references_of(&bible, &mapper, "John 10:11", &options).unwrap().xrefs {
+            let ScoreDetail::Overlap { shared, union } = xref.detail else { panic!("{:?}", xref.detail) };
+            assert_eq!(shared, xref.shared.len());
+            assert!((shared as f32 / union as f32 - xref.score).abs() < 1e-6);
+        }
+        assert_eq!(format_explanation(&cross_references_of(&bible, &mapper, "jn 10:11", &options).unwrap().xrefs[0]), "3 shared / 8 words in either = 37.5%: good, sheep, shepherd");
+        let ScoreDetail::Phrases(phrases) = &xrefs[0].detail else { panic!("{:?}", xrefs[0].detail) };
+        assert_eq!(phrases.len() as f32, xrefs[0].score);
+        assert_eq!(format_explanation(&xrefs[0]), "1 shared 2-grams, 1 each: \"good shepherd\"");
+
+        assert_eq!(cross_references_of(&bible, &mapper, "John 10", &options).unwrap_err().kind(), "InvalidReference");
+        assert_eq!(cross_references_of(&bible, &mapper, "John 10:99", &options).unwrap_err().kind(), "NotFound");
+        assert!(cross_references_of(&bible, &mapper, "Genesis 1:1", &options).unwrap().xrefs.is_empty());
+    }
+
+    #[test]
+    fn test_find_like_misquoted_verse() {
+        let bible = vec![
+            Verse::new("Ecclesiastes", 3, 1, "To every thing there is a season, and a time to every purpose under the heaven:"),
+            Verse::new("Ecclesiastes", 3, 2, "A time to be born, and a time to die; a time to plant, and a time to pluck up that which is planted;"),
+            Verse::new("Ecclesiastes", 3, 4, "A time to weep, and a time to laugh; a time to mourn, and a time to dance;"),
+            Verse::new("Ecclesiastes", 3, 8, "A time to love, and a time to hate; a time of war, and a time of peace."),
+            Verse::new("John", 3, 16, "For God so loved the world, that he gave his only begotten Son"),
+        ];
+
+        let found = find_like(&bible, "a time to be borne and a time for dying", Some(3));
+
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].verse.reference(), "Ecclesiastes 3:2");
+        assert!(found.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        let all = find_like(&bible, "A time to be born", None);
+        assert_eq!(all.len(), bible.len());
+        assert_eq!(all.last().unwrap().verse.reference(), "John 3:16");
+        assert!(find_like(&bible, "?!", None).is_empty());
+
+        let a = char_ngrams("Born, and", 3);
+        assert_eq!(a, char_ngrams("born and", 3));
+        assert_eq!(dice_similarity(&a, &a), 1.0);
+        assert_eq!(phrase_similarity(&a, &HashSet::new()), 0.0);
+    }
+
+    #[test]
+    fn test_jaccard_empty_inputs() {
+        assert_eq!(calculate_jaccard_similarity(&[], &[]), 0.0);
+        assert_eq!(calculate_jaccard_similarity(&words(&["faith"]), &[]), 0.0);
+    }
+
+    #[test]
+    fn test_jaccard_identical_and_disjoint() {
+        let a = words(&["faith", "hope", "love"]);
+        assert_eq!(calculate_jaccard_similarity(&a, &a), 1.0);
+        assert_eq!(calculate_jaccard_similarity(&a, &words(&["light", "darkness"])), 0.0);
+        // Two shared out of four distinct words
+        assert_eq!(calculate_jaccard_similarity(&a, &words(&["faith", "hope", "grace"])), 0.5);
+    }
+
+    #[test]
+    fn test_extract_words_drops_stop_words() {
+        let mapper = SynonymMapper::new();
+        assert_eq!(extract_words("For God so loved the world, the world!", &mapper, false, WordFilter::default()), words(&["god", "loved", "world"]));
+        assert!(extract_words("", &mapper, false, WordFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn test_format_keywords() {
+        let mapper = SynonymMapper::new();
+        let text = "For God so loved the world, that he gave his only begotten Son, that whosoever believeth in him should not perish, but have everlasting life.";
+
+        assert_eq!(
+            format_keywords(&extract_words(text, &mapper, false, WordFilter::default())),
+            "Keywords (13): begotten, believeth, everlasting, gave, god, life, loved, only, perish, should, son, whosoever, world"
+        );
+        assert_eq!(format_keywords(&extract_words("Go ye.", &mapper, false, WordFilter::default())), "Keywords: none");
+    }
+
+    #[test]
+    fn test_word_filter_for_short_verses() {
+        let mapper = SynonymMapper::new();
+        // Every word of a short verse can fall to the default filter
+        assert_eq!(extract_words("Be ye holy; for I am holy, but ye are.", &mapper, false, WordFilter::default()), words(&["holy"]));
+        assert!(extract_words("Go ye.", &mapper, false, WordFilter::default()).is_empty());
+
+        let relaxed = WordFilter { drop_stopwords: false, min_len: 2, ..Default::default() };
+        assert_eq!(extract_words("Go ye.", &mapper, false, relaxed), words(&["go", "ye"]));
+        assert_eq!(extract_words("Jesus wept.", &mapper, false, WordFilter::all_words()), words(&["jesus", "wept"]));
+        assert_eq!(extract_words("I am he", &mapper, false, WordFilter::all_words()), words(&["am", "he", "i"]));
+        assert_eq!(extract_ngrams("Go ye.", 2, &mapper, false, relaxed).len(), 1);
+
+        // Cross-references for a verse the default filter leaves empty
+        let verse = |verse: u32, text: &str| Verse::new("Mark", 16, verse, text);
+        let bible = vec![verse(1, "Go ye."), verse(2, "Go ye therefore.")];
+        assert!(cross_references(&bible, &mapper, &bible[0], &XrefOptions::default()).is_empty());
+        let options = XrefOptions { words: relaxed, ..Default::default() };
+        assert_eq!(cross_references(&bible, &mapper, &bible[0], &options).len(), 1);
+    }
+
+    #[test]
+    fn test_lemmatized_words() {
+        let mapper = SynonymMapper::new();
+        let lemmas = WordFilter { lemmatize: true, ..Default::default() };
+        assert_eq!(extract_words("He loved them; loving, he loveth still.", &mapper, false, lemmas), words(&["love", "still"]));
+        assert_eq!(extract_words("He loved them; loving, he loveth still.", &mapper, false, WordFilter::default()), words(&["loved", "loveth", "loving", "still"]));
+
+        // Verses worded in different tenses become cross-references of each other
+        let verse = |book: &str, text: &str| Verse::new(book, 1, 1, text);
+        let bible = vec![verse("John", "The Father loveth the Son."), verse("Jude", "The Father loved the Son.")];
+        let options = XrefOptions { similarity: "0.9".to_string(), ..Default::default() };
+        assert!(cross_references(&bible, &mapper, &bible[0], &options).is_empty());
+        let options = XrefOptions { words: lemmas, ..options };
+        assert_eq!(cross_references(&bible, &mapper, &bible[0], &options).len(), 1);
+    }
+
+    #[test]
+    fn test_ngrams_empty_and_short_inputs() {
+        let mapper = SynonymMapper::new();
+        assert!(extract_ngrams("", 2, &mapper, false, WordFilter::default()).is_empty());
+        assert!(extract_ngrams("Jesus wept", 3, &mapper, false, WordFilter::default()).is_empty());
+        assert!(find_ngram_matches("", "Jesus wept", 2, &mapper, false).is_empty());
+    }
+
+    #[test]
+    fn test_identical_texts_share_every_ngram() {
+        let mapper = SynonymMapper::new();
+        let text = "In the beginning God created the heaven and the earth";
+
+        let matches = find_ngram_matches(text, text, 2, &mapper, false);
+
+        assert_eq!(count_distinct_phrases(&matches), extract_ngrams(text, 2, &mapper, false, WordFilter::default()).len());
+        assert_eq!(ngram_highlight_spans(&matches), vec![(7, text.len())]);
+    }
+
+    #[test]
+    fn test_xref_scope() {
+        let verse = |book: &str, verse: u32| Verse::new(book, 1, verse, "the good shepherd giveth his life");
+        let bible = vec![verse("John", 1), verse("John", 2), verse("Hebrews", 1), verse("Psalms", 1)];
+        let mapper = SynonymMapper::new();
+        let books = |scope: XrefScope| -> Vec<String> {
+            let options = XrefOptions { scope, ..Default::default() };
+            cross_references(&bible, &mapper, &bible[0], &options).iter().map(|x| x.verse.reference()).collect()
+        };
+
+        assert_eq!(books(XrefScope::Book), vec!["John 1:2"]);
+        assert_eq!(books(XrefScope::Testament), vec!["John 1:2", "Hebrews 1:1"]);
+        assert_eq!(books(XrefScope::All).len(), 3);
+    }
+
+    #[test]
+    fn test_top_ignores_threshold() {
+        let verse = |verse: u32, text: &str| Verse::new("Psalms", 23, verse, text);
+        let bible = vec![
+            verse(1, "The Lord is my shepherd"),
+            verse(2, "The Lord is my light and my salvation"),
+            verse(3, "The Lord shall preserve thy soul"),
+            verse(4, "Praise the Lord, O my soul"),
+            verse(5, "The good shepherd giveth his life for the sheep"),
+        ];
+        let mapper = SynonymMapper::new();
+        let strict = XrefOptions { similarity: "0.9".to_string(), ..Default::default() };
+        assert!(cross_references(&bible, &mapper, &bible[0], &strict).is_empty());
+
+        for k in 1..=4 {
+            let top = XrefOptions { top: Some(k), ..strict.clone() };
+            let found = cross_references(&bible, &mapper, &bible[0], &top);
+            assert_eq!(found.len(), k);
+            assert!(found.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        }
+        // A top of 0 keeps every verse sharing a word, as a limit of 0 does
+        let everything = XrefOptions { top: Some(0), ..strict.clone() };
+        assert_eq!(everything.kept(), None);
+        assert_eq!(cross_references(&bible, &mapper, &bible[0], &everything).len(), 4);
+        assert_eq!(XrefOptions { top: Some(2), limit: Some(0), ..Default::default() }.kept(), Some(2));
+    }
+
+    #[test]
+    fn test_book_cross_references() {
+        let verse = |book: &str, verse: u32, text: &str| Verse::new(book, 1, verse, text);
+        let bible = vec![
+            verse("Psalm", 1, "The Lord is my shepherd"),
+            verse("Psalm", 2, "The Lord is my light and my salvation"),
+            verse("Psalm", 3, "Praise the Lord, O my soul"),
+            verse("John", 1, "I am the good shepherd: the good shepherd giveth his life for the sheep"),
+            verse("John", 2, "The Lord shall be my shepherd and my light"),
+        ];
+        let mapper = SynonymMapper::new();
+
+        // Each source's best two, with their scores
+        let expected: [(&str, [&[&str]; 3]); 2] = [
+            ("0.1", [&["John 1:2 0.667", "Psalm 1:2 0.250"], &["John 1:2 0.500", "Psalm 1:1 0.250"], &["Psalm 1:1 0.250", "Psalm 1:2 0.200"]]),
+            ("2-gram", [&["John 1:2 1.000"], &[], &[]]),
+        ];
+        for (similarity, expected) in expected {
+            let options = XrefOptions { similarity: similarity.to_string(), limit: Some(2), ..Default::default() };
+            let corpus = XrefCorpus::new(&bible, &mapper, &options);
+            let mut progress = Vec::new();
+            let results = book_cross_references(&corpus, "Psalms", &options, |done, total| progress.push((done, total)));
+
+            assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+            let sources: Vec<String> = results.iter().map(|(v, _)| v.reference()).collect();
+            assert_eq!(sources, vec!["Psalm 1:1", "Psalm 1:2", "Psalm 1:3"]);
+            for ((source, xrefs), expected) in results.iter().zip(expected) {
+                let scored: Vec<String> = xrefs.iter().map(|x| format!("{} {:.3}", x.verse.reference(), x.score)).collect();
+                assert_eq!(scored, expected, "{} {}", similarity, source.reference());
+            }
+        }
+        assert!(book_cross_references(&XrefCorpus::new(&bible, &mapper, &XrefOptions::default()), "Mark", &XrefOptions::default(), |_, _| {}).is_empty());
+        assert_eq!(sorted_jaccard_similarity(&words(&["faith", "grace", "hope"]), &words(&["faith", "hope", "love"])), 0.5);
+    }
+
+    #[test]
+    fn test_chapter_cross_references() {
+        let bible = vec![
+            Verse::new("1 Kings", 10, 1, "And when the queen of Sheba heard of the fame of Solomon, she came to prove him with hard questions."),
+            Verse::new("1 Kings", 10, 2, "And she came to Jerusalem with a very great train, with camels that bare spices."),
+            Verse::new("1 Kings", 11, 1, "But king Solomon loved many strange women."),
+            Verse::new("2 Chronicles", 9, 1, "And when the queen of Sheba heard of the fame of Solomon, she came to prove Solomon with hard questions at Jerusalem,"),
+            Verse::new("2 Chronicles", 9, 2, "with a very great company, and camels that bare spices."),
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+            Verse::new("Psalms", 23, 2, "He maketh me to lie down in green pastures."),
+        ];
+        let mapper = SynonymMapper::new();
+
+        // The parallel chapter ranks first, by words and by phrases alike; unrelated ones drop out
+        for similarity in ["0.3", "2-gram", "names"] {
+            let options = XrefOptions { similarity: similarity.to_string(), ..Default::default() };
+            let xrefs = chapter_cross_references(&bible, &mapper, "2 Chr 9", &options).unwrap();
+            let found: Vec<String> = xrefs.iter().map(|xref| xref.reference()).collect();
+            assert_eq!(found[0], "1 Kings 10", "{}", similarity);
+            assert!(!found.contains(&"Psalms 23".to_string()), "{}", similarity);
+            assert!(!found.contains(&"2 Chronicles 9".to_string()), "the source chapter isn't its own parallel");
+            assert!(xrefs.windows(2).all(|w| w[0].score >= w[1].score));
+        }
+
+        // Scores are over the chapters' whole text, the same as for one verse holding it all
+        let options = XrefOptions::default();
+        let xrefs = chapter_cross_references(&bible, &mapper, "1 Kings 11", &options).unwrap();
+        assert_eq!(xrefs[0].shared, ["solomon"]);
+        let joined = |chapter: &[Verse]| chapter.iter().map(|v| v.text.as_str()).collect::<Vec<_>>().join(" ");
+        let expected = sorted_jaccard_similarity(&extract_words(&joined(&bible[2..3]), &mapper, false, options.words), &extract_words(&joined(xrefs[0].verses), &mapper, false, options.words));
+        assert_eq!(xrefs[0].score, expected);
+        let bigrams = XrefOptions { similarity: "2-gram".to_string(), ..Default::default() };
+        let xrefs = chapter_cross_references(&bible, &mapper, "1 Kings 10", &bigrams).unwrap();
+        assert_eq!(xrefs[0].score, xrefs[0].shared.len() as f32);
+        assert!(xrefs[0].shared.contains(&"camels bare".to_string()));
+
+        let limited = XrefOptions { limit: Some(1), ..Default::default() };
+        assert_eq!(chapter_cross_references(&bible, &mapper, "2 Chronicles 9", &limited).unwrap().len(), 1);
+        let same_book = XrefOptions { scope: XrefScope::Book, ..Default::default() };
+        assert_eq!(chapter_cross_references(&bible, &mapper, "1 Kings 10", &same_book).unwrap()[0].reference(), "1 Kings 11");
+        assert_eq!(chapter_cross_references(&bible, &mapper, "1 Kings 10:1", &options).unwrap_err().kind(), "InvalidReference");
+        assert_eq!(chapter_cross_references(&bible, &mapper, "Mark 1", &options).unwrap_err().kind(), "NotFound");
+    }
+
+    #[test]
+    fn test_compare_metrics() {
+        let bible = vec![
+            Verse::new("1 Kings", 10, 1, "And when the queen of Sheba heard of the fame of Solomon, she came to prove him with hard questions."),
+            Verse::new("1 Kings", 11, 1, "But king Solomon loved many strange women."),
+            Verse::new("2 Chronicles", 9, 1, "And when the queen of Sheba heard of the fame of Solomon, she came to prove Solomon with hard questions at Jerusalem,"),
+            Verse::new("Matthew", 12, 42, "The queen of the south shall rise up in the judgment with this generation."),
+            Verse::new("Psalms", 23, 1, "The LORD is my shepherd; I shall not want."),
+        ];
+        let mapper = SynonymMapper::new();
+
+        // Every metric has a column, each with the parallel verse first
+        let rankings = compare_metrics(&bible, &mapper, "1 Kings 10:1", &XrefOptions::default()).unwrap();
+        let metrics: Vec<SimilarityMetric> = rankings.iter().map(|ranking| ranking.metric).collect();
+        assert_eq!(metrics, [SimilarityMetric::Jaccard(0.0), SimilarityMetric::NGram(2), SimilarityMetric::Names]);
+        for ranking in &rankings {
+            assert!(!ranking.xrefs.is_empty(), "{:?}", ranking.metric);
+            assert_eq!(ranking.xrefs[0].verse.book, "2 Chronicles", "{:?}", ranking.metric);
+            assert!(ranking.xrefs.iter().all(|xref| xref.metric == ranking.metric));
+            // Neither the unrelated verse nor the source itself is ranked
+            assert!(ranking.xrefs.iter().all(|xref| xref.verse.book != "Psalms" && (xref.verse.book.as_str(), xref.verse.chapter) != ("1 Kings", 10)));
+        }
+        // The columns rank differently: "queen" alone is no phrase, and Solomon is no Matthew name
+        let found = |ranking: &MetricRanking| ranking.xrefs.iter().map(|xref| xref.verse.book.clone()).collect::<Vec<_>>();
+        assert!(found(&rankings[0]).contains(&"Matthew".to_string()));
+        assert!(!found(&rankings[1]).contains(&"Matthew".to_string()));
+        assert_eq!(found(&rankings[2]), ["2 Chronicles", "1 Kings"]);
+        assert_eq!(rankings[2].xrefs[1].shared, ["Solomon"]);
+
+        // Scores agree with the single-metric search, here with the threshold lifted by --top
+        let top = XrefOptions { top: Some(5), ..Default::default() };
+        let jaccard: Vec<f32> = cross_references_of(&bible, &mapper, "1 Kings 10:1", &top).unwrap().xrefs.iter().map(|xref| xref.score).collect();
+        assert_eq!(rankings[0].xrefs.iter().map(|xref| xref.score).collect::<Vec<_>>(), jaccard);
+        let names = XrefOptions { similarity: "names".to_string(), ..Default::default() };
+        let by_names = cross_references_of(&bible, &mapper, "1 Kings 10:1", &names).unwrap().xrefs;
+        assert_eq!(rankings[2].xrefs.iter().map(|xref| xref.score).collect::<Vec<_>>(), by_names.iter().map(|xref| xref.score).collect::<Vec<_>>());
+
+        let trigrams = XrefOptions { similarity: "3-gram".to_string(), limit: Some(1), ..Default::default() };
+        let rankings = compare_metrics(&bible, &mapper, "1 Kings 10:1", &trigrams).unwrap();
+        assert_eq!(rankings[1].metric, SimilarityMetric::NGram(3));
+        assert!(rankings.iter().all(|ranking| ranking.xrefs.len() == 1));
+        assert_eq!(compare_metrics(&bible, &mapper, "Mark 1:1", &trigrams).unwrap_err().kind(), "NotFound");
+    }
+
+    #[test]
+    fn test_parse_similarity_metric() {
+        assert_eq!(parse_similarity_metric("0.5"), SimilarityMetric::Jaccard(0.5));
+        assert_eq!(parse_similarity_metric("3-gram"), SimilarityMetric::NGram(3));
+        assert_eq!(parse_similarity_metric("2gram"), SimilarityMetric::NGram(2));
+        assert_eq!(parse_similarity_metric("Names"), SimilarityMetric::Names);
+        assert_eq!(parse_similarity_metric("7"), SimilarityMetric::Jaccard(1.0));
+    }
+
+    #[test]
+    fn test_ngrams_follow_reading_order() {
+        let mapper = SynonymMapper::new();
+        let source = "For God so loved the world";
+        // Same words in a different order share only "God loved"
+        let target = "The world, God loved it.";
+
+        let matches = find_ngram_matches(source, target, 2, &mapper, false);
+
+        assert_eq!(matches, vec![NgramMatch { phrase: "god loved".to_string(), start: 11, end: 20 }]);
+        assert_eq!(&target[matches[0].start..matches[0].end], "God loved");
+        assert!(find_ngram_matches(source, "world God", 2, &mapper, false).is_empty());
+    }
+
+    #[test]
+    fn test_ngram_phrases_skip_stop_words_and_merge() {
+        let mapper = SynonymMapper::new();
+        let source = "he gave his only begotten Son";
+        let target = "the only begotten Son of the Father";
+
+        let matches = find_ngram_matches(source, target, 2, &mapper, false);
+
+        let phrases: Vec<&str> = matches.iter().map(|m| m.phrase.as_str()).collect();
+        assert_eq!(phrases, vec!["only begotten", "begotten son"]);
+        assert_eq!(count_distinct_phrases(&matches), 2);
+        // Overlapping n-grams highlight as one phrase
+        let spans = ngram_highlight_spans(&matches);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(&target[spans[0].0..spans[0].1], "only begotten Son");
+    }
+
+    #[test]
+    fn test_ngram_synonym_variations() {
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("lord", vec!["lord".to_string(), "god".to_string()]);
+
+        let matches = find_ngram_matches("Praise the Lord always", "praise God", 2, &mapper, true);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].phrase, "praise god");
+    }
+
+    fn names_corpus() -> Vec<Verse> {
+        ["And the LORD said, Arise, go up to Jerusalem.", "for there is no god beside me; arise and go"]
+            .iter()
+            .map(|text| Verse::new("Test", 1, 1, *text))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_names() {
+        let vocabulary = lowercase_vocabulary(&names_corpus());
+
+        assert_eq!(
+            extract_names("And Jesus went up to Jerusalem. Then Peter said, Arise, O God of Israel", &vocabulary),
+            vec!["Israel", "Jerusalem", "Jesus", "Peter"]
+        );
+    }
+
+    #[test]
+    fn test_shared_names_score_above_common_words() {
+        let vocabulary = lowercase_vocabulary(&names_corpus());
+        let source = extract_names("And Jesus went up to Jerusalem with his disciples", &vocabulary);
+        let same_city = extract_names("And the king of Babylon came against Jerusalem", &vocabulary);
+        let common_words = extract_names("And they went up to the city with the disciples", &vocabulary);
+
+        let city_score = calculate_jaccard_similarity(&source, &same_city);
+        let common_score = calculate_jaccard_similarity(&source, &common_words);
+
+        assert!(city_score > 0.0);
+        assert_eq!(common_score, 0.0);
+    }
+}
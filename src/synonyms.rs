@@ -1,50 +1,16 @@
 use std::fs::{self, File};
-use std::io::{self, BufRead};
-use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::bible::clean_line;
 
-pub struct SynonymMapper {
-    pub synonyms: HashMap<String, Vec<String>>,
-}
+/// The default synonym groups, as written by `--create-synonyms`
+pub const DEFAULT_SYNONYMS: &str = r#"# Bible Search Tool - Synonym Configuration
+# Format: keyword: synonym1, synonym2, synonym3
+# Lines starting with # are comments and will be ignored
+# Keywords and synonyms are case-insensitive
 
-impl SynonymMapper {
-    pub fn new() -> Self {
-        SynonymMapper {
-            synonyms: HashMap::new(),
-        }
-    }
-    
-    pub fn load_from_file(filename: &str) -> io::Result<Self> {
-        let mut mapper = Self::new();
-        let file = File::open(filename)?;
-        let reader = io::BufReader::new(file);
-        
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            if let Some((key, values)) = line.split_once(':') {
-                let key = key.trim().to_lowercase();
-                let synonyms: Vec<String> = values
-                    .split(',')
-                    .map(|s| s.trim().to_lowercase())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                
-                if !synonyms.is_empty() {
-                    mapper.synonyms.insert(key, synonyms);
-                }
-            }
-        }
-        Ok(mapper)
-    }
-    
-    pub fn create_default_file(filename: &str) -> io::Result<()> {
-        let default_content = r#"# Bible Search Tool - Synonym Configuration
-# ... (content of the default file) ...
 # Deity references
 god: god, lord, almighty, creator, father, jehovah, yahweh, most high
 jesus: jesus, christ, savior, saviour, redeemer, messiah, son, lamb
@@ -76,29 +42,520 @@ spirit: spirit, soul, heart, mind
 word: word, words, scripture, law, commandment, testimony
 kingdom: kingdom, reign, dominion, rule
 "#;
-        fs::write(filename, default_content)?;
+
+/// A query's expansion: each distinct source word with the terms it expands to (see
+/// `SynonymMapper::expand_query`)
+pub type Expansion = Vec<(String, Vec<String>)>;
+
+// Most query expansions remembered before the cache starts over
+const EXPANSION_CACHE_SIZE: usize = 256;
+
+#[derive(Default)]
+pub struct SynonymMapper {
+    // The groups, by key word; only changed through methods that clear the cache
+    synonyms: HashMap<String, Vec<String>>,
+    // Query words left unexpanded (--no-synonyms-for)
+    suppressed: HashSet<String>,
+    // Expansions already worked out, by normalized query and `first_only`, for sessions that
+    // repeat a query (interactive refining, batch runs); cleared whenever the groups change
+    cache: Mutex<HashMap<(String, bool), Expansion>>,
+}
+
+impl SynonymMapper {
+    pub fn new() -> Self {
+        SynonymMapper {
+            synonyms: HashMap::new(),
+            suppressed: HashSet::new(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Never expand `word` in a query; other words keep their synonyms. Only the word itself
+    /// is affected: a word in the same group (`lord` for `god`) still expands to the whole group.
+    pub fn suppress(&mut self, word: &str) {
+        self.suppressed.insert(word.trim().to_lowercase());
+        self.clear_cache();
+    }
+    
+    // Forget the remembered query expansions, so the next ones see the current groups
+    fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Replace the groups with those of a synonyms file, keeping the expansion settings.
+    /// On error the current groups are kept.
+    pub fn reload(&mut self, filename: &str) -> io::Result<()> {
+        self.synonyms = Self::load_from_file(filename)?.synonyms;
+        self.clear_cache();
+        Ok(())
+    }
+
+    pub fn load_from_file(filename: &str) -> io::Result<Self> {
+        let mut mapper = Self::new();
+        let mut include_stack = Vec::new();
+        mapper.load_file_into(Path::new(filename), &mut include_stack)?;
+        Ok(mapper)
+    }
+
+    // Load one synonyms file into this mapper, following `@include` directives.
+    // `include_stack` holds the files currently being loaded, to break include cycles.
+    fn load_file_into(&mut self, path: &Path, include_stack: &mut Vec<PathBuf>) -> io::Result<()> {
+        let canonical = fs::canonicalize(path)?;
+        if include_stack.contains(&canonical) {
+            eprintln!("⚠️  Skipping recursive include of {}", path.display());
+            return Ok(());
+        }
+        include_stack.push(canonical);
+
+        let file = File::open(path)?;
+        let reader = io::BufReader::new(file);
+        let mut keys_in_file = HashSet::new();
+        
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = clean_line(&line, line_number).trim();
+            
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Include another synonyms file, relative to this one
+            if let Some(included) = line.strip_prefix("@include") {
+                let included = path.parent().unwrap_or_else(|| Path::new("")).join(included.trim());
+                self.load_file_into(&included, include_stack).map_err(|e| {
+                    io::Error::new(e.kind(), format!("{} (included from {} line {})", e, path.display(), line_number + 1))
+                })?;
+                continue;
+            }
+            
+            if let Some((key, synonyms)) = parse_group(line) {
+                // A key repeated within one file is probably a mistake; across includes it's how
+                // add-on files extend a base lexicon
+                if !keys_in_file.insert(key.clone()) {
+                    eprintln!("⚠️  Duplicate synonym key '{}' in {} (line {}), merging groups", key, path.display(), line_number + 1);
+                }
+                self.add_group(key, synonyms);
+            }
+        }
+
+        include_stack.pop();
         Ok(())
     }
     
-    pub fn expand_query(&self, query: &str) -> Vec<String> {
+    /// Synonym groups given as a map of key words to synonyms (as embedded in a JSON Bible),
+    /// normalized like the lines of a synonyms file
+    pub fn from_groups(groups: HashMap<String, Vec<String>>) -> Self {
+        let mut mapper = Self::new();
+        mapper.replace_groups(groups);
+        mapper
+    }
+
+    /// Replace the groups with those given as in `from_groups`, keeping the expansion settings
+    pub fn replace_groups(&mut self, groups: HashMap<String, Vec<String>>) {
+        self.synonyms.clear();
+        self.clear_cache();
+        for (key, synonyms) in groups {
+            let synonyms: Vec<String> = synonyms.iter().map(|s| clean_term(s)).filter(|s| !s.is_empty()).collect();
+            let key = clean_term(&key);
+            if !key.is_empty() && !synonyms.is_empty() {
+                self.add_group(key, synonyms);
+            }
+        }
+    }
+
+    /// The synonyms of the group keyed by `key`
+    pub fn group(&self, key: &str) -> Option<&[String]> {
+        self.synonyms.get(key).map(Vec::as_slice)
+    }
+
+    /// Set the synonyms of the group keyed by `key`, replacing any it had
+    pub fn set_group(&mut self, key: &str, synonyms: Vec<String>) {
+        self.synonyms.insert(key.to_string(), synonyms);
+        self.clear_cache();
+    }
+
+    /// The built-in groups written by `--create-synonyms`, without reading any file
+    pub fn builtin() -> Self {
+        let mut mapper = Self::new();
+        for line in DEFAULT_SYNONYMS.lines() {
+            if let Some((key, synonyms)) = parse_group(line.trim()) {
+                mapper.add_group(key, synonyms);
+            }
+        }
+        mapper
+    }
+
+    /// Write the default synonyms to `filename`. An existing file is left untouched, failing
+    /// with `ErrorKind::AlreadyExists`, unless `force` is set.
+    pub fn create_default_file(filename: &str, force: bool) -> io::Result<()> {
+        if force {
+            return fs::write(filename, DEFAULT_SYNONYMS);
+        }
+        // create_new checks and creates in one step, so a file can't appear in between
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(filename)?;
+        file.write_all(DEFAULT_SYNONYMS.as_bytes())
+    }
+
+    // Merge repeated keys instead of letting the last line win
+    fn add_group(&mut self, key: String, synonyms: Vec<String>) {
+        self.clear_cache();
+        if let Some(existing) = self.synonyms.get_mut(&key) {
+            existing.extend(synonyms);
+            existing.sort();
+            existing.dedup();
+        } else {
+            self.synonyms.insert(key, synonyms);
+        }
+    }
+    
+    /// Split a query into words, keeping together a run of words that spells a multi-word
+    /// synonym (`most high`), joined by single spaces, so it is expanded and searched as one
+    /// phrase. The longest listed phrase wins; excluded `-words` never join a phrase.
+    pub fn split_phrases(&self, query: &str) -> Vec<String> {
+        let phrases: HashSet<&str> = self
+            .synonyms
+            .iter()
+            .flat_map(|(key, synonyms)| std::iter::once(key).chain(synonyms))
+            .filter(|term| term.contains(' '))
+            .map(String::as_str)
+            .collect();
+        let longest = phrases.iter().map(|phrase| phrase.split(' ').count()).max().unwrap_or(1);
         let words: Vec<&str> = query.split_whitespace().collect();
-        let mut expanded_terms = Vec::new();
-        
-        for word in &words {
+        let clean = |word: &str| word.to_lowercase().trim_matches(|c: char| !c.is_alphabetic()).to_string();
+
+        let mut split = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            let length = (2..=longest.min(words.len() - i)).rev().find(|&n| {
+                let run = &words[i..i + n];
+                !run.iter().any(|w| w.starts_with('-'))
+                    && phrases.contains(run.iter().map(|w| clean(w)).collect::<Vec<_>>().join(" ").as_str())
+            });
+            let length = length.unwrap_or(1);
+            split.push(words[i..i + length].join(" "));
+            i += length;
+        }
+        split
+    }
+
+    /// Expand each distinct word of a query into its synonyms, keeping track of which word
+    /// produced which terms. Each word's terms are sorted and include the word itself. A
+    /// multi-word synonym in the query (see `split_phrases`) counts as one word. With
+    /// `first_only`, only the first word that has synonyms (the presumed topic, `faith` in
+    /// "faith without works") is expanded and the words after it are only themselves.
+    /// Expansions are remembered, so repeating a query doesn't scan the groups again.
+    pub fn expand_query(&self, query: &str, first_only: bool) -> Expansion {
+        // Case and spacing don't change an expansion, so queries differing only in them share it
+        let key = (query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase(), first_only);
+        if let Some(expansion) = self.cache.lock().ok().and_then(|cache| cache.get(&key).cloned()) {
+            return expansion;
+        }
+        let expansion = self.expand_uncached(&key.0, first_only);
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= EXPANSION_CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(key, expansion.clone());
+        }
+        expansion
+    }
+
+    fn expand_uncached(&self, query: &str, first_only: bool) -> Expansion {
+        let mut expansion: Expansion = Vec::new();
+        let mut expanded_one = false;
+
+        for word in self.split_phrases(query) {
             let clean_word = word.to_lowercase().trim_matches(|c: char| !c.is_alphabetic()).to_string();
-            if let Some(synonyms) = self.synonyms.get(&clean_word) {
-                expanded_terms.extend(synonyms.clone());
-            } else {
-                expanded_terms.push(clean_word);
+            if clean_word.is_empty() || expansion.iter().any(|(source, _)| source == &clean_word) {
+                continue;
+            }
+
+            // Expand through every group the word belongs to, whether it is
+            // the group's key or only one of its values
+            // (unless it was suppressed, or an earlier word already expanded with `first_only`)
+            let mut terms = Vec::new();
+            let expand = !(self.suppressed.contains(&clean_word) || (first_only && expanded_one));
+            for (key, synonyms) in self.synonyms.iter().filter(|_| expand) {
+                if key == &clean_word || synonyms.contains(&clean_word) {
+                    terms.push(key.clone());
+                    terms.extend(synonyms.clone());
+                }
             }
+            expanded_one |= !terms.is_empty();
+
+            // Always keep the literal word so exact matches are never lost
+            terms.push(clean_word.clone());
+            terms.sort();
+            terms.dedup();
+            expansion.push((clean_word, terms));
         }
-        
-        expanded_terms.sort();
-        expanded_terms.dedup();
-        expanded_terms
+        expansion
+    }
+
+    /// Every term a query expands to, every word expanded, sorted and without duplicates
+    pub fn expanded_terms(&self, query: &str) -> Vec<String> {
+        let mut terms: Vec<String> = self.expand_query(query, false).into_iter().flat_map(|(_, terms)| terms).collect();
+        terms.sort();
+        terms.dedup();
+        terms
     }
     
     pub fn get_synonym_count(&self) -> usize {
         self.synonyms.len()
     }
-}
\ No newline at end of file
+}
+
+// A term as it is kept: lowercased, with its words separated by single spaces
+fn clean_term(term: &str) -> String {
+    term.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+// Parse a "key: synonym, synonym" line into a lowercased group; None for lines that aren't groups
+fn parse_group(line: &str) -> Option<(String, Vec<String>)> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let (key, values) = line.split_once(':')?;
+    let synonyms: Vec<String> = values.split(',').map(clean_term).filter(|s| !s.is_empty()).collect();
+    if synonyms.is_empty() {
+        return None;
+    }
+    Some((key.trim().to_lowercase(), synonyms))
+}
+
+/// Describe an expansion for status lines, each source word first:
+/// `love → love, beloved, charity; faith → faith, trust`
+pub fn describe_expansion(expansion: &[(String, Vec<String>)]) -> String {
+    expansion
+        .iter()
+        .map(|(word, terms)| {
+            let others = terms.iter().filter(|term| *term != word).map(|term| term.as_str());
+            let terms: Vec<&str> = std::iter::once(word.as_str()).chain(others).collect();
+            format!("{} → {}", word, terms.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_duplicate_keys_are_merged() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "love: love, beloved").unwrap();
+        writeln!(temp_file, "peace: peace, rest").unwrap();
+        writeln!(temp_file, "love: charity, love, affection").unwrap();
+
+        let mapper = SynonymMapper::load_from_file(temp_file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(mapper.get_synonym_count(), 2);
+        assert_eq!(
+            mapper.synonyms["love"],
+            vec!["affection", "beloved", "charity", "love"]
+        );
+    }
+
+    #[test]
+    fn test_suppressed_words_stay_literal() {
+        let mut mapper = SynonymMapper::builtin();
+        mapper.suppress("God");
+
+        let expansion = mapper.expand_query("god love", false);
+
+        assert_eq!(expansion[0], ("god".to_string(), vec!["god".to_string()]));
+        assert!(expansion[1].1.contains(&"charity".to_string()));
+        // Words in the suppressed word's group still expand, to the whole group
+        assert!(mapper.expanded_terms("lord").contains(&"god".to_string()));
+    }
+
+    #[test]
+    fn test_default_file_matches_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("synonyms.txt");
+        SynonymMapper::create_default_file(path.to_str().unwrap(), false).unwrap();
+
+        let loaded = SynonymMapper::load_from_file(path.to_str().unwrap()).unwrap();
+        let builtin = SynonymMapper::builtin();
+
+        assert_eq!(loaded.synonyms, builtin.synonyms);
+        assert_eq!(builtin.synonyms["god"].last().map(|s| s.as_str()), Some("most high"));
+    }
+
+    #[test]
+    fn test_default_file_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("synonyms.txt");
+        let path = path.to_str().unwrap();
+        fs::write(path, "love: love, charity\n").unwrap();
+
+        let error = SynonymMapper::create_default_file(path, false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(path).unwrap(), "love: love, charity\n");
+
+        SynonymMapper::create_default_file(path, true).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), DEFAULT_SYNONYMS);
+    }
+
+    #[test]
+    fn test_bom_and_crlf() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all("\u{feff}love: love, charity\r\npeace: peace, rest\r\n".as_bytes()).unwrap();
+
+        let mapper = SynonymMapper::load_from_file(temp_file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(mapper.synonyms["love"], vec!["love", "charity"]);
+        assert_eq!(mapper.synonyms["peace"], vec!["peace", "rest"]);
+    }
+
+    #[test]
+    fn test_include_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("addons")).unwrap();
+        fs::write(dir.path().join("base.txt"), "# Base lexicon\nlove: love, charity\n@include addons/extra.txt\n").unwrap();
+        fs::write(dir.path().join("addons/extra.txt"), "love: beloved\ntemple: temple, sanctuary\n").unwrap();
+
+        let mapper = SynonymMapper::load_from_file(dir.path().join("base.txt").to_str().unwrap()).unwrap();
+
+        assert_eq!(mapper.synonyms["love"], vec!["beloved", "charity", "love"]);
+        assert_eq!(mapper.synonyms["temple"], vec!["temple", "sanctuary"]);
+    }
+
+    #[test]
+    fn test_self_include_does_not_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("self.txt");
+        fs::write(&path, "@include self.txt\npeace: peace, rest\n").unwrap();
+
+        let mapper = SynonymMapper::load_from_file(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(mapper.synonyms["peace"], vec!["peace", "rest"]);
+    }
+
+    #[test]
+    fn test_missing_include_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("base.txt");
+        fs::write(&path, "@include nowhere.txt\n").unwrap();
+
+        assert!(SynonymMapper::load_from_file(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_value_word_expands_to_its_group() {
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("love", vec!["loved".to_string(), "beloved".to_string()]);
+
+        let expanded = mapper.expanded_terms("beloved");
+
+        assert!(expanded.contains(&"love".to_string()));
+        assert!(expanded.contains(&"loved".to_string()));
+        assert!(expanded.contains(&"beloved".to_string()));
+    }
+
+    #[test]
+    fn test_literal_word_is_always_retained() {
+        let mut mapper = SynonymMapper::new();
+        // A group whose value list omits its own key
+        mapper.set_group("god", vec!["lord".to_string(), "almighty".to_string()]);
+
+        let expanded = mapper.expanded_terms("god");
+
+        assert!(expanded.contains(&"god".to_string()));
+        assert!(expanded.contains(&"lord".to_string()));
+    }
+
+    #[test]
+    fn test_expansion_is_grouped_by_source_word() {
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("love", vec!["love".to_string(), "charity".to_string(), "beloved".to_string()]);
+        mapper.set_group("faith", vec!["faith".to_string(), "trust".to_string()]);
+
+        let expansion = mapper.expand_query("Love faith grace love", false);
+
+        assert_eq!(
+            expansion,
+            vec![
+                ("love".to_string(), vec!["beloved".to_string(), "charity".to_string(), "love".to_string()]),
+                ("faith".to_string(), vec!["faith".to_string(), "trust".to_string()]),
+                ("grace".to_string(), vec!["grace".to_string()]),
+            ]
+        );
+        assert_eq!(describe_expansion(&expansion), "love → love, beloved, charity; faith → faith, trust; grace → grace");
+    }
+
+    #[test]
+    fn test_multi_word_synonyms_are_phrases() {
+        let mapper = SynonymMapper::builtin();
+
+        // "most high" is one of god's synonyms, so typing it is one query word
+        assert_eq!(mapper.split_phrases("the Most High  reigneth"), vec!["the", "Most High", "reigneth"]);
+        let expansion = mapper.expand_query("most high", false);
+        assert_eq!(expansion.len(), 1);
+        assert_eq!(expansion[0].0, "most high");
+        assert!(expansion[0].1.contains(&"god".to_string()));
+        assert!(mapper.expanded_terms("god").contains(&"most high".to_string()));
+        // Words that aren't a listed phrase stay apart
+        assert_eq!(mapper.split_phrases("most holy -high"), vec!["most", "holy", "-high"]);
+    }
+
+    #[test]
+    fn test_first_only_expands_the_topic_word() {
+        let mut mapper = SynonymMapper::builtin();
+
+        // "without" has no synonyms, so "faith" is the first keyword and "works" stays literal
+        let expansion = mapper.expand_query("without faith works love", true);
+        let every = mapper.expand_query("faith", false);
+        assert_eq!(expansion[0], ("without".to_string(), vec!["without".to_string()]));
+        assert_eq!(expansion[1], every[0]);
+        assert!(expansion[1].1.len() > 1);
+        assert_eq!(expansion[2], ("works".to_string(), vec!["works".to_string()]));
+        assert_eq!(expansion[3], ("love".to_string(), vec!["love".to_string()]));
+        // The same query expanded in full is remembered apart
+        assert!(mapper.expand_query("without faith works love", false)[3].1.contains(&"charity".to_string()));
+        assert_eq!(mapper.expand_query("without faith works love", true), expansion);
+
+        // A suppressed word isn't the topic; the next keyword is
+        mapper.suppress("faith");
+        let expansion = mapper.expand_query("faith love grace", true);
+        assert_eq!(expansion[0].1, vec!["faith".to_string()]);
+        assert!(expansion[1].1.contains(&"charity".to_string()));
+        assert_eq!(expansion[2].1, vec!["grace".to_string()]);
+    }
+
+    #[test]
+    fn test_repeated_expansions_are_cached() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "love: love, charity").unwrap();
+        let filename = temp_file.path().to_str().unwrap().to_string();
+        let filename = filename.as_str();
+        let mut mapper = SynonymMapper::load_from_file(filename).unwrap();
+
+        let first = mapper.expand_query("Love  faith", false);
+        assert_eq!(mapper.expand_query("love faith", false), first);
+        assert_eq!(mapper.cache.lock().unwrap().len(), 1, "differing case and spacing share one entry");
+
+        // The repeat came from the cache: a stale entry planted there is what it returns
+        mapper.cache.lock().unwrap().insert(("love faith".to_string(), false), Vec::new());
+        assert!(mapper.expand_query("love faith", false).is_empty());
+
+        // Changing a group starts over, so the change is seen straight away
+        mapper.set_group("faith", vec!["faith".to_string(), "trust".to_string()]);
+        assert_eq!(mapper.expand_query("love faith", false)[1].1, ["faith", "trust"]);
+        mapper.replace_groups(HashMap::from([("Faith".to_string(), vec!["Faith".to_string(), "Belief".to_string()])]));
+        assert_eq!(mapper.expand_query("love faith", false), [("love".to_string(), vec!["love".to_string()]), ("faith".to_string(), vec!["belief".to_string(), "faith".to_string()])]);
+
+        // Reloading the file, or changing how words expand, starts over
+        writeln!(temp_file, "love: beloved").unwrap();
+        mapper.reload(filename).unwrap();
+        let expansion = mapper.expand_query("love faith", false);
+        assert_eq!((expansion[0].1.clone(), expansion[1].1.clone()), (vec!["beloved".to_string(), "charity".to_string(), "love".to_string()], vec!["faith".to_string()]));
+        mapper.suppress("love");
+        assert_eq!(mapper.expand_query("love faith", false)[0].1, ["love"]);
+    }
+}
@@ -0,0 +1,201 @@
+// topic.rs
+// Vocabulary profile of a topic (--topic-profile): the words that most often appear alongside it,
+// and the topic's shortest verses for memorizing (--memorize)
+
+use std::collections::{HashMap, HashSet};
+use colored::*;
+use serde::Serialize;
+use crate::bible::{Bible, NoteDisplay, Verse};
+use crate::error::BibleError;
+use crate::filter::VerseFilter;
+use crate::lemma::lemmatize;
+use crate::output::{format_results, OutputFormat};
+use crate::search::{search_verses, SearchHit, SearchOptions};
+use crate::similarity::significant_tokens;
+use crate::synonyms::SynonymMapper;
+
+/// A word that co-occurs with a topic and the number of matching verses it appears in
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WordCount {
+    pub word: String,
+    pub count: usize,
+}
+
+/// The most common words in the verses matching a topic
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicProfile {
+    pub keyword: String,
+    /// The keyword and its synonyms, as searched for
+    pub terms: Vec<String>,
+    /// Number of verses that matched the topic
+    pub verses: usize,
+    /// Co-occurring words, most frequent first (ties alphabetical)
+    pub words: Vec<WordCount>,
+}
+
+/// Build the profile of `keyword`: find the verses matching it or any of its synonyms, then count,
+/// once per verse, the significant words they contain other than the topic terms themselves
+pub fn topic_profile(bible: &Bible, synonym_mapper: &SynonymMapper, keyword: &str, filter: &VerseFilter, limit: usize) -> TopicProfile {
+    let (terms, hits) = topic_verses(bible, synonym_mapper, keyword, filter);
+    // A word is a topic term when it has a term's dictionary form ("shepherds" for
+    // "shepherd"), not when it only contains one ("lawyer" isn't "law")
+    let term_lemmas: HashSet<String> = terms.iter().map(|term| lemmatize(term)).collect();
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for hit in &hits {
+        let mut words: Vec<String> = significant_tokens(&hit.verse.text)
+            .into_iter()
+            .map(|(word, _, _)| word)
+            .filter(|word| !term_lemmas.contains(&lemmatize(word)))
+            .collect();
+        words.sort();
+        words.dedup();
+        for word in words {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut words: Vec<WordCount> = counts.into_iter().map(|(word, count)| WordCount { word, count }).collect();
+    words.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    words.truncate(limit);
+
+    TopicProfile { keyword: keyword.to_string(), terms, verses: hits.len(), words }
+}
+
+// A topic's terms (the keyword and its synonyms) and the verses passing `filter` that contain any of them
+fn topic_verses<'a>(bible: &'a Bible, synonym_mapper: &SynonymMapper, keyword: &str, filter: &VerseFilter) -> (Vec<String>, Vec<SearchHit<'a>>) {
+    let terms: Vec<String> = synonym_mapper.expanded_terms(&keyword.to_lowercase());
+    let options = SearchOptions { filter: filter.clone(), ..Default::default() };
+    let hits = search_verses(bible, std::slice::from_ref(&terms), &options);
+    (terms, hits)
+}
+
+/// The shortest verses on a topic (--memorize): the verses matching `keyword` or any of its
+/// synonyms, fewest words first (then fewest characters, then Bible order), at most `limit`
+pub fn memory_verses<'a>(bible: &'a Bible, synonym_mapper: &SynonymMapper, keyword: &str, filter: &VerseFilter, limit: usize) -> Vec<&'a Verse> {
+    let (_, hits) = topic_verses(bible, synonym_mapper, keyword, filter);
+    let mut verses: Vec<&Verse> = hits.into_iter().map(|hit| hit.verse).collect();
+    verses.sort_by_key(|verse| (verse.text.split_whitespace().count(), verse.text.chars().count()));
+    verses.truncate(limit);
+    verses
+}
+
+/// Print memory verses with their word counts and any notes `notes` shows, or as JSON for a
+/// JSON `format`. No verses is a `NoResults` error.
+pub fn print_memory_verses(verses: &[&Verse], keyword: &str, format: OutputFormat, notes: NoteDisplay, use_color: bool) -> Result<(), BibleError> {
+    if verses.is_empty() {
+        return Err(BibleError::NoResults { message: format!("No verses found for '{}'.", keyword), hint: None });
+    }
+    if format.is_json() {
+        println!("{}", format_results(verses, format, notes));
+        return Ok(());
+    }
+    println!("Shortest verses on '{}':\n", keyword);
+    for verse in verses {
+        let count = format!("({} words)", verse.text.split_whitespace().count());
+        println!("{} {}", verse.shown(notes), if use_color { count.dimmed().to_string() } else { count });
+    }
+    Ok(())
+}
+
+/// Print a topic profile as a bar chart, or as JSON when `json` is set
+pub fn print_topic_profile(profile: &TopicProfile, json: bool, use_color: bool) {
+    if json {
+        match serde_json::to_string_pretty(profile) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("🔥 Error serializing profile: {}", e),
+        }
+        return;
+    }
+
+    println!(
+        "Words used with '{}' ({}) in {} verses:\n",
+        profile.keyword,
+        profile.terms.join(", "),
+        profile.verses
+    );
+    if profile.words.is_empty() {
+        println!("{}", "No co-occurring words found.".yellow());
+        return;
+    }
+
+    const BAR_WIDTH: usize = 30;
+    let max_count = profile.words[0].count;
+    let word_width = profile.words.iter().map(|w| w.word.len()).max().unwrap_or(0);
+    for entry in &profile.words {
+        let bar = "█".repeat((entry.count * BAR_WIDTH).div_ceil(max_count));
+        let bar = if use_color { bar.cyan().to_string() } else { bar };
+        println!("  {:<width$} {:>5} {}", entry.word, entry.count, bar, width = word_width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bible::Verse;
+
+    fn verse(book: &str, text: &str) -> Verse {
+        Verse::new(book, 1, 1, text)
+    }
+
+    #[test]
+    fn test_topic_profile_counts_co_occurring_words() {
+        let bible = Bible::new(vec![
+            verse("Psalms", "The LORD is my shepherd; I shall not want."),
+            verse("John", "I am the good shepherd: the good shepherd giveth his life for the sheep."),
+            verse("Luke", "Shepherds abiding in the field, keeping watch over their flock."),
+            verse("Genesis", "the flock of Laban"),
+        ]);
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("shepherd", vec!["shepherd".to_string(), "pastor".to_string()]);
+
+        let profile = topic_profile(&bible, &mapper, "Shepherd", &VerseFilter::default(), 3);
+
+        assert_eq!(profile.verses, 3);
+        assert_eq!(profile.terms, vec!["pastor", "shepherd"]);
+        // "good" appears twice in John but counts once; "shepherds" is the topic itself
+        assert_eq!(profile.words.len(), 3);
+        assert!(profile.words.iter().all(|w| w.count == 1));
+        assert!(!profile.words.iter().any(|w| w.word.contains("shepherd")));
+        assert_eq!(profile.words[0].word, "abiding");
+    }
+
+    #[test]
+    fn test_words_containing_a_term_still_count() {
+        let bible = Bible::new(vec![
+            verse("Luke", "Then answered one of the lawyers, and said, Master, thus saying thou reproachest us also."),
+            verse("Luke", "What is written in the law? how readest thou? Laws were given since."),
+        ]);
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("law", vec!["law".to_string(), "sin".to_string()]);
+
+        let profile = topic_profile(&bible, &mapper, "law", &VerseFilter::default(), 50);
+        let words: Vec<&str> = profile.words.iter().map(|w| w.word.as_str()).collect();
+        // "lawyers" and "since" only contain a term; "laws" is one
+        assert!(words.contains(&"lawyers") && words.contains(&"since"), "{:?}", words);
+        assert!(!words.contains(&"law") && !words.contains(&"laws"));
+    }
+
+    #[test]
+    fn test_memory_verses_are_short_and_on_topic() {
+        let bible = Bible::new(vec![
+            verse("1 Corinthians", "Charity suffereth long, and is kind; charity envieth not; charity vaunteth not itself, is not puffed up,"),
+            verse("John", "Greater love hath no man than this, that a man lay down his life for his friends."),
+            verse("1 John", "God is love."),
+            verse("Romans", "Let love be without dissimulation."),
+            verse("John", "Jesus wept."),
+            verse("1 Corinthians", "Let all your things be done with charity."),
+        ]);
+        let mut mapper = SynonymMapper::new();
+        mapper.set_group("love", vec!["love".to_string(), "charity".to_string()]);
+
+        let verses = memory_verses(&bible, &mapper, "love", &VerseFilter::default(), 3);
+        let texts: Vec<&str> = verses.iter().map(|v| v.text.as_str()).collect();
+        // Shortest first; "Jesus wept." is shorter but not about love
+        assert_eq!(texts, ["God is love.", "Let love be without dissimulation.", "Let all your things be done with charity."]);
+        assert_eq!(memory_verses(&bible, &mapper, "love", &VerseFilter::default(), 10).len(), 5);
+        // A topic with no verses is an error, whatever the format
+        let none = memory_verses(&bible, &mapper, "zeal", &VerseFilter::default(), 3);
+        assert_eq!(print_memory_verses(&none, "zeal", OutputFormat::Json, NoteDisplay::default(), false).unwrap_err().kind(), "NoResults");
+    }
+}
@@ -0,0 +1,158 @@
+// translations.rs
+// Registry mapping translation codes (kjv, asv, ...) to Bible files
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::Deserialize;
+
+/// Name of the registry file looked up in the current directory by default
+pub const DEFAULT_REGISTRY_FILE: &str = "translations.toml";
+
+/// Code, name and file of each translation `builtin` registers
+pub const BUILTIN_TRANSLATIONS: [(&str, &str, &str); 5] = [
+    ("erv", "English Revised Version", "bibles/erv.txt"),
+    ("kjv", "King James Version", "bibles/kjv.txt"),
+    ("asv", "American Standard Version", "bibles/asv.txt"),
+    ("esv", "English Standard Version", "bibles/ESV.json"),
+    ("nasb", "New American Standard Bible", "bibles/NASB.json"),
+];
+
+/// A single translation entry in the registry
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Translation {
+    #[serde(skip)]
+    pub code: String,
+    pub name: Option<String>,
+    pub path: String,
+}
+
+/// Translation codes mapped to files, loaded from a `translations.toml` like:
+///
+/// ```toml
+/// [kjv]
+/// name = "King James Version"
+/// path = "bibles/kjv.txt"
+/// ```
+#[derive(Debug, Clone)]
+pub struct TranslationRegistry {
+    translations: BTreeMap<String, Translation>,
+}
+
+impl TranslationRegistry {
+    /// The translations bundled in the `bibles/` directory
+    pub fn builtin() -> Self {
+        let translations = BUILTIN_TRANSLATIONS
+            .iter()
+            .map(|(code, name, path)| {
+                (code.to_string(), Translation { code: code.to_string(), name: Some(name.to_string()), path: path.to_string() })
+            })
+            .collect();
+        TranslationRegistry { translations }
+    }
+
+    /// Parse a registry from TOML text. Relative paths are resolved against `base_dir`.
+    pub fn parse(content: &str, base_dir: &Path) -> io::Result<Self> {
+        let parsed: BTreeMap<String, Translation> = toml::from_str(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse translations: {}", e)))?;
+
+        let translations = parsed
+            .into_iter()
+            .map(|(code, mut translation)| {
+                let code = code.to_lowercase();
+                translation.code = code.clone();
+                if Path::new(&translation.path).is_relative() && !base_dir.as_os_str().is_empty() {
+                    translation.path = base_dir.join(&translation.path).to_string_lossy().to_string();
+                }
+                (code, translation)
+            })
+            .collect();
+        Ok(TranslationRegistry { translations })
+    }
+
+    /// Load a registry file
+    pub fn load_from_file(filename: &str) -> io::Result<Self> {
+        let content = fs::read_to_string(filename)?;
+        let base_dir = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&content, base_dir)
+    }
+
+    /// Load a registry file, falling back to the built-in translations when it doesn't exist
+    pub fn load_or_builtin(filename: &str) -> io::Result<Self> {
+        if Path::new(filename).exists() {
+            Self::load_from_file(filename)
+        } else {
+            Ok(Self::builtin())
+        }
+    }
+
+    pub fn get(&self, code: &str) -> Option<&Translation> {
+        self.translations.get(&code.to_lowercase())
+    }
+
+    pub fn codes(&self) -> Vec<&str> {
+        self.translations.keys().map(|c| c.as_str()).collect()
+    }
+
+    /// Look up a translation and check its file exists, with a user-facing error otherwise
+    pub fn resolve(&self, code: &str) -> Result<&Translation, String> {
+        let translation = self.get(code).ok_or_else(|| {
+            format!("Unknown translation '{}'. Available translations: {}", code, self.codes().join(", "))
+        })?;
+        if !Path::new(&translation.path).exists() {
+            return Err(format!("File for translation '{}' not found: {}", translation.code, translation.path));
+        }
+        Ok(translation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_registry() {
+        let content = r#"
+            [kjv]
+            name = "King James Version"
+            path = "bibles/kjv.txt"
+
+            [WEB]
+            path = "/data/web.json"
+        "#;
+
+        let registry = TranslationRegistry::parse(content, Path::new("/opt/bible")).unwrap();
+
+        assert_eq!(registry.codes(), vec!["kjv", "web"]);
+        assert_eq!(registry.get("KJV").unwrap().path, "/opt/bible/bibles/kjv.txt");
+        assert_eq!(registry.get("kjv").unwrap().name.as_deref(), Some("King James Version"));
+        assert_eq!(registry.get("web").unwrap().path, "/data/web.json");
+        assert_eq!(registry.get("web").unwrap().name, None);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("web.txt"), "WEB\nWorld English Bible\n").unwrap();
+        let content = "[web]\npath = \"web.txt\"\n\n[missing]\npath = \"missing.txt\"\n";
+
+        let registry = TranslationRegistry::parse(content, dir.path()).unwrap();
+
+        assert!(registry.resolve("web").is_ok());
+        assert!(registry.resolve("missing").unwrap_err().contains("not found"));
+        assert!(registry.resolve("xyz").unwrap_err().contains("Unknown translation"));
+    }
+
+    #[test]
+    fn test_builtin_matches_shipped_registry() {
+        let shipped = TranslationRegistry::load_from_file(DEFAULT_REGISTRY_FILE).unwrap();
+        let builtin = TranslationRegistry::builtin();
+
+        assert_eq!(shipped.codes(), builtin.codes());
+        for code in builtin.codes() {
+            assert_eq!(shipped.get(code), builtin.get(code));
+        }
+    }
+}
@@ -0,0 +1,404 @@
+// validate.rs
+// Checking that a Bible file parses cleanly (--validate) and is complete (--check-integrity)
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+use colored::*;
+use serde::Serialize;
+use crate::bible::{load_bible_report, Verse};
+use crate::books::{book_rank, resolve_book};
+use crate::json_parser::{is_json_format, load_bible_dir, load_bible_json, load_bible_stored};
+
+/// Verses that many modern translations omit (or footnote) for text-critical reasons
+const KNOWN_OMISSIONS: &[(&str, u32, u32)] = &[
+    ("Matthew", 17, 21),
+    ("Matthew", 18, 11),
+    ("Matthew", 23, 14),
+    ("Mark", 7, 16),
+    ("Mark", 9, 44),
+    ("Mark", 9, 46),
+    ("Mark", 11, 26),
+    ("Mark", 15, 28),
+    ("Luke", 17, 36),
+    ("Luke", 23, 17),
+    ("John", 5, 4),
+    ("Acts", 8, 37),
+    ("Acts", 15, 34),
+    ("Acts", 24, 7),
+    ("Acts", 28, 29),
+    ("Romans", 16, 24),
+];
+
+/// A line of a text Bible that couldn't be parsed as a verse
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MalformedLine {
+    pub line: usize,
+    pub content: String,
+}
+
+/// A file in a Bible directory that was skipped
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SkippedFile {
+    pub file: String,
+    pub reason: String,
+}
+
+/// A missing verse, or a whole missing chapter when `verse` is `None`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Gap {
+    pub book: String,
+    pub chapter: u32,
+    pub verse: Option<u32>,
+}
+
+impl std::fmt::Display for Gap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.verse {
+            Some(verse) => write!(f, "{} {}:{}", self.book, self.chapter, verse),
+            None => write!(f, "{} {} (whole chapter)", self.book, self.chapter),
+        }
+    }
+}
+
+/// Everything `--validate` found in a Bible file
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub file: String,
+    pub verse_count: usize,
+    pub malformed: Vec<MalformedLine>,
+    pub skipped_files: Vec<SkippedFile>,
+    /// References that occur more than once, e.g. "John 3:16"
+    pub duplicates: Vec<String>,
+    pub gaps: Vec<Gap>,
+}
+
+impl ValidationReport {
+    pub fn has_problems(&self) -> bool {
+        self.verse_count == 0
+            || !self.malformed.is_empty()
+            || !self.skipped_files.is_empty()
+            || !self.duplicates.is_empty()
+            || !self.gaps.is_empty()
+    }
+}
+
+/// Load a Bible file (text, JSON or a directory) and check it for problems
+pub fn validate_file(path: &str) -> io::Result<ValidationReport> {
+    let mut malformed = Vec::new();
+    let mut skipped_files = Vec::new();
+
+    let verses = if Path::new(path).is_dir() {
+        let load = load_bible_dir(path)?;
+        skipped_files = load.skipped.into_iter().map(|(file, reason)| SkippedFile { file, reason }).collect();
+        load.verses
+    } else if path.ends_with(".json") || is_json_format(path) {
+        load_bible_json(path)?
+    } else {
+        let load = load_bible_report(path)?;
+        malformed = load.malformed.into_iter().map(|(line, content)| MalformedLine { line, content }).collect();
+        load.verses
+    };
+
+    Ok(ValidationReport {
+        file: path.to_string(),
+        verse_count: verses.len(),
+        malformed,
+        skipped_files,
+        duplicates: find_duplicates(&verses),
+        gaps: find_gaps(&verses),
+    })
+}
+
+// References that appear more than once, in the order they first repeat
+fn find_duplicates(verses: &[Verse]) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    let mut reported = BTreeSet::new();
+    let mut duplicates = Vec::new();
+    for verse in verses {
+        let key = (verse.book.as_str(), verse.chapter, verse.verse);
+        if !seen.insert(key) && reported.insert(key) {
+            duplicates.push(format!("{} {}:{}", verse.book, verse.chapter, verse.verse));
+        }
+    }
+    duplicates
+}
+
+// Verses grouped by book (in canonical order), then chapter
+fn group_by_book(verses: &[Verse]) -> Vec<(&str, BTreeMap<u32, BTreeSet<u32>>)> {
+    let mut books: Vec<(&str, BTreeMap<u32, BTreeSet<u32>>)> = Vec::new();
+    for verse in verses {
+        let index = match books.iter().position(|(book, _)| *book == verse.book) {
+            Some(index) => index,
+            None => {
+                books.push((verse.book.as_str(), BTreeMap::new()));
+                books.len() - 1
+            }
+        };
+        books[index].1.entry(verse.chapter).or_default().insert(verse.verse);
+    }
+    books.sort_by_key(|(book, _)| book_rank(book).unwrap_or(usize::MAX));
+    books
+}
+
+// Chapters missing from 1..=last chapter, and verses missing from 1..=last verse of each chapter
+fn find_gaps(verses: &[Verse]) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    for (book, chapters) in group_by_book(verses) {
+        let last_chapter = chapters.keys().next_back().copied().unwrap_or(0);
+        for chapter in 1..=last_chapter {
+            let Some(numbers) = chapters.get(&chapter) else {
+                gaps.push(Gap { book: book.to_string(), chapter, verse: None });
+                continue;
+            };
+            let last_verse = numbers.iter().next_back().copied().unwrap_or(0);
+            for verse in (1..=last_verse).filter(|v| !numbers.contains(v)) {
+                gaps.push(Gap { book: book.to_string(), chapter, verse: Some(verse) });
+            }
+        }
+    }
+    gaps
+}
+
+/// Is this verse on the built-in list of known textual omissions?
+pub fn is_known_omission(book: &str, chapter: u32, verse: u32) -> bool {
+    let book = resolve_book(book).unwrap_or(book);
+    KNOWN_OMISSIONS.iter().any(|&(b, c, v)| b == book && c == chapter && v == verse)
+}
+
+/// Result of `--check-integrity`: ordering, duplicate and completeness problems per book
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub file: String,
+    pub verse_count: usize,
+    /// Verses that appear after a later verse of the same book, e.g. "Genesis 1:3 after Genesis 1:5"
+    pub out_of_order: Vec<String>,
+    pub duplicates: Vec<String>,
+    /// Missing verses and chapters that aren't known omissions
+    pub missing: Vec<Gap>,
+    /// Missing verses found on the known-omissions list (not errors)
+    pub known_omissions: Vec<Gap>,
+}
+
+impl IntegrityReport {
+    pub fn has_errors(&self) -> bool {
+        !self.out_of_order.is_empty() || !self.duplicates.is_empty() || !self.missing.is_empty()
+    }
+}
+
+/// Check that each book's chapters and verses, in the order given, form a contiguous ascending sequence
+pub fn check_integrity(file: &str, verses: &[Verse]) -> IntegrityReport {
+    let (known_omissions, missing) = find_gaps(verses)
+        .into_iter()
+        .partition(|gap| gap.verse.is_some_and(|v| is_known_omission(&gap.book, gap.chapter, v)));
+
+    IntegrityReport {
+        file: file.to_string(),
+        verse_count: verses.len(),
+        out_of_order: find_out_of_order(verses),
+        duplicates: find_duplicates(verses),
+        missing,
+        known_omissions,
+    }
+}
+
+/// Load a Bible file in its stored order and check its integrity
+pub fn check_file_integrity(path: &str) -> io::Result<IntegrityReport> {
+    let verses = load_bible_stored(path)?;
+    Ok(check_integrity(path, &verses))
+}
+
+// Verses whose (chapter, verse) goes backwards relative to the previous verse of the same book
+fn find_out_of_order(verses: &[Verse]) -> Vec<String> {
+    let mut last_seen: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    let mut out_of_order = Vec::new();
+    for verse in verses {
+        let position = (verse.chapter, verse.verse);
+        if let Some(&(chapter, number)) = last_seen.get(verse.book.as_str()) {
+            if position < (chapter, number) {
+                out_of_order.push(format!(
+                    "{} {}:{} after {} {}:{}",
+                    verse.book, verse.chapter, verse.verse, verse.book, chapter, number
+                ));
+                continue;
+            }
+        }
+        last_seen.insert(verse.book.as_str(), position);
+    }
+    out_of_order
+}
+
+/// Print an integrity report as text, or as JSON when `json` is set
+pub fn print_integrity_report(report: &IntegrityReport, json: bool) {
+    if json {
+        match serde_json::to_string_pretty(report) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("🔥 Error serializing report: {}", e),
+        }
+        return;
+    }
+
+    println!("Checked {}: {} verses", report.file, report.verse_count);
+    if !report.out_of_order.is_empty() {
+        println!("{} {} verse(s) out of order:", "🔥".red(), report.out_of_order.len());
+        for entry in &report.out_of_order {
+            println!("   {}", entry);
+        }
+    }
+    if !report.duplicates.is_empty() {
+        println!("{} {} duplicate reference(s):", "🔥".red(), report.duplicates.len());
+        for reference in &report.duplicates {
+            println!("   {}", reference);
+        }
+    }
+    if !report.missing.is_empty() {
+        println!("{} {} missing:", "🔥".red(), report.missing.len());
+        for gap in &report.missing {
+            println!("   {}", gap);
+        }
+    }
+    if !report.known_omissions.is_empty() {
+        let list: Vec<String> = report.known_omissions.iter().map(|g| g.to_string()).collect();
+        println!("ℹ️  {} known textual omission(s): {}", list.len(), list.join(", "));
+    }
+    if !report.has_errors() {
+        println!("{} Chapters and verses are complete and in order.", "✅".green());
+    }
+}
+
+/// Print a validation report as text, or as JSON when `json` is set
+pub fn print_report(report: &ValidationReport, json: bool) {
+    if json {
+        match serde_json::to_string_pretty(report) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("🔥 Error serializing report: {}", e),
+        }
+        return;
+    }
+
+    println!("Validated {}: {} verses", report.file, report.verse_count);
+    if report.verse_count == 0 {
+        println!("{} No verses found.", "🔥".red());
+    }
+    if !report.malformed.is_empty() {
+        println!("{} {} malformed line(s):", "⚠️ ".yellow(), report.malformed.len());
+        for line in &report.malformed {
+            println!("   line {}: {}", line.line, line.content);
+        }
+    }
+    if !report.skipped_files.is_empty() {
+        println!("{} {} skipped file(s):", "⚠️ ".yellow(), report.skipped_files.len());
+        for skipped in &report.skipped_files {
+            println!("   {}: {}", skipped.file, skipped.reason);
+        }
+    }
+    if !report.duplicates.is_empty() {
+        println!("{} {} duplicate reference(s):", "⚠️ ".yellow(), report.duplicates.len());
+        for reference in &report.duplicates {
+            println!("   {}", reference);
+        }
+    }
+    if !report.gaps.is_empty() {
+        println!("{} {} gap(s):", "⚠️ ".yellow(), report.gaps.len());
+        for gap in &report.gaps {
+            println!("   missing {}", gap);
+        }
+    }
+    if !report.has_problems() {
+        println!("{} No problems found.", "✅".green());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn validate_text(content: &str) -> ValidationReport {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(content.as_bytes()).unwrap();
+        validate_file(temp_file.path().to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_clean_file() {
+        let report = validate_text("TST\nTest Version\nGenesis 1:1\tIn the beginning.\nGenesis 1:2\tAnd the earth.\n");
+
+        assert_eq!(report.verse_count, 2);
+        assert!(!report.has_problems());
+    }
+
+    #[test]
+    fn test_reports_problems() {
+        let content = "TST\nTest Version\n\
+            Genesis 1:1\tIn the beginning.\n\
+            Genesis 1:3\tLet there be light.\n\
+            not a verse\n\
+            \n\
+            Genesis 1:3\tLet there be light.\n\
+            Genesis 3:1\tNow the serpent.\n";
+        let report = validate_text(content);
+
+        assert!(report.has_problems());
+        assert_eq!(report.malformed, vec![MalformedLine { line: 5, content: "not a verse".to_string() }]);
+        assert_eq!(report.duplicates, vec!["Genesis 1:3".to_string()]);
+        assert_eq!(
+            report.gaps,
+            vec![
+                Gap { book: "Genesis".to_string(), chapter: 1, verse: Some(2) },
+                Gap { book: "Genesis".to_string(), chapter: 2, verse: None },
+            ]
+        );
+    }
+
+    fn verse(book: &str, chapter: u32, number: u32) -> Verse {
+        Verse::new(book, chapter, number, "")
+    }
+
+    #[test]
+    fn test_integrity_known_omission_is_not_an_error() {
+        // Acts 1:1, 2:1, ... 7:1, then 8:1-36 and 8:38-40
+        let mut verses: Vec<Verse> = (1..8).map(|chapter| verse("Acts", chapter, 1)).collect();
+        verses.extend((1..=40).filter(|&v| v != 37).map(|v| verse("Acts", 8, v)));
+        let report = check_integrity("test", &verses);
+
+        assert!(!report.has_errors());
+        assert_eq!(report.known_omissions, vec![Gap { book: "Acts".to_string(), chapter: 8, verse: Some(37) }]);
+    }
+
+    #[test]
+    fn test_integrity_errors() {
+        let verses = vec![
+            verse("John", 1, 1),
+            verse("John", 1, 3),
+            verse("John", 1, 2),
+            verse("John", 1, 3),
+            verse("John", 1, 5),
+        ];
+        let report = check_integrity("test", &verses);
+
+        assert!(report.has_errors());
+        assert_eq!(report.out_of_order, vec!["John 1:2 after John 1:3".to_string()]);
+        assert_eq!(report.duplicates, vec!["John 1:3".to_string()]);
+        assert_eq!(report.missing, vec![Gap { book: "John".to_string(), chapter: 1, verse: Some(4) }]);
+        assert!(report.known_omissions.is_empty());
+    }
+
+    #[test]
+    fn test_integrity_checks_the_stored_order() {
+        // JSON and directory Bibles are checked as stored, not as sorted for searching
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("bible.json");
+        std::fs::write(&json, r#"{ "John": { "1": { "1": "In the beginning", "3": "All things", "2": "The same" } } }"#).unwrap();
+        let report = check_file_integrity(json.to_str().unwrap()).unwrap();
+        assert_eq!(report.out_of_order, ["John 1:2 after John 1:3"]);
+
+        let books = dir.path().join("books");
+        std::fs::create_dir(&books).unwrap();
+        std::fs::write(books.join("a.txt"), "John 2:1 And the third day\nJohn 1:1 In the beginning\n").unwrap();
+        std::fs::write(books.join("b.txt"), "Genesis 1:1 In the beginning\n").unwrap();
+        let report = check_file_integrity(books.to_str().unwrap()).unwrap();
+        assert_eq!(report.out_of_order, ["John 1:1 after John 2:1"]);
+        assert_eq!(report.verse_count, 3);
+    }
+}
@@ -0,0 +1,142 @@
+// versification.rs
+// Maps references between verse-numbering schemes (--versification FILE)
+
+use std::fs;
+use std::io;
+use crate::reference::{parse_reference, Reference};
+
+/// One mapped run of verses: `from_start..=from_end` of a chapter in the input scheme
+/// corresponds to the same number of verses starting at `to_start` in the Bible's scheme
+#[derive(Debug, Clone, PartialEq)]
+struct Mapping {
+    from_book: String,
+    from_chapter: u32,
+    from_start: u32,
+    from_end: u32,
+    to_book: String,
+    to_chapter: u32,
+    to_start: u32,
+}
+
+impl Mapping {
+    fn covers(&self, book: &str, chapter: u32, verse: u32) -> bool {
+        self.from_book.eq_ignore_ascii_case(book)
+            && self.from_chapter == chapter
+            && (self.from_start..=self.from_end).contains(&verse)
+    }
+
+    fn target(&self, verse: u32) -> u32 {
+        self.to_start + (verse - self.from_start)
+    }
+}
+
+/// A versification map, loaded from lines like:
+///
+/// ```text
+/// # Hebrew numbering = English numbering
+/// Psalms 51:3-21 = Psalms 51:1-19
+/// Malachi 3:19 = Malachi 4:1
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Versification {
+    mappings: Vec<Mapping>,
+}
+
+impl Versification {
+    /// Parse a map; blank lines and `#` comments are ignored. Both sides of a line must be a
+    /// verse or a range, and cover the same number of verses.
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let mut mappings = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = crate::bible::clean_line(line, index).trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |reason: &str| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}: {}", index + 1, reason, line))
+            };
+
+            let (from, to) = line.split_once('=').ok_or_else(|| invalid("expected 'FROM = TO'"))?;
+            let (from_book, from_chapter, from_start, from_end) =
+                verse_span(from).ok_or_else(|| invalid("expected a verse or verse range before '='"))?;
+            let (to_book, to_chapter, to_start, to_end) =
+                verse_span(to).ok_or_else(|| invalid("expected a verse or verse range after '='"))?;
+            if from_end - from_start != to_end - to_start {
+                return Err(invalid("both sides must cover the same number of verses"));
+            }
+
+            mappings.push(Mapping { from_book, from_chapter, from_start, from_end, to_book, to_chapter, to_start });
+        }
+        Ok(Versification { mappings })
+    }
+
+    /// Load a map file
+    pub fn load_from_file(filename: &str) -> io::Result<Self> {
+        let content = fs::read_to_string(filename)?;
+        Self::parse(&content).map_err(|e| io::Error::new(e.kind(), format!("{} ({})", e, filename)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Translate a verse or range into the Bible's numbering. Returns `None` when the map
+    /// doesn't cover it (or only covers part of a range), in which case it's used unchanged.
+    pub fn map(&self, reference: &Reference) -> Option<Reference> {
+        match *reference {
+            Reference::Verse(ref book, chapter, verse) => {
+                let mapping = self.mappings.iter().find(|m| m.covers(book, chapter, verse))?;
+                Some(Reference::Verse(mapping.to_book.clone(), mapping.to_chapter, mapping.target(verse)))
+            }
+            Reference::Range(ref book, chapter, start, end) => {
+                let mapping = self.mappings.iter().find(|m| m.covers(book, chapter, start) && m.covers(book, chapter, end))?;
+                Some(Reference::Range(mapping.to_book.clone(), mapping.to_chapter, mapping.target(start), mapping.target(end)))
+            }
+            Reference::Book(_) | Reference::Chapter(..) | Reference::Chapters(..) => None,
+        }
+    }
+
+    /// Translate a typed reference, giving back the Bible-numbered reference as text
+    pub fn map_input(&self, input: &str) -> Option<String> {
+        self.map(&parse_reference(input)?).map(|r| r.to_string())
+    }
+}
+
+// Book, chapter and inclusive verse span of a verse or range reference
+fn verse_span(text: &str) -> Option<(String, u32, u32, u32)> {
+    match parse_reference(text)? {
+        Reference::Verse(book, chapter, verse) => Some((book, chapter, verse, verse)),
+        Reference::Range(book, chapter, start, end) => Some((book, chapter, start, end)),
+        Reference::Book(_) | Reference::Chapter(..) | Reference::Chapters(..) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_map() {
+        let map = Versification::load_from_file("versification/masoretic.txt").unwrap();
+
+        // Hebrew Psalm 51:3 is the English 51:1, once the two title verses are taken out
+        assert_eq!(map.map_input("Ps 51:3").as_deref(), Some("Psalms 51:1"));
+        assert_eq!(map.map_input("Psalm 51:3-4").as_deref(), Some("Psalms 51:1-2"));
+        assert_eq!(map.map_input("Malachi 3:19").as_deref(), Some("Malachi 4:1"));
+        // Unmapped verses, ranges running off the end of a mapping and chapters are left alone
+        assert_eq!(map.map_input("Psalms 51:2"), None);
+        assert_eq!(map.map_input("Psalms 51:20-22"), None);
+        assert_eq!(map.map_input("Psalms 51"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Versification::parse("Psalms 51:3-21 Psalms 51:1-19").unwrap_err().to_string().contains("line 1"));
+        assert!(Versification::parse("# ok\nPsalms 51 = Psalms 51:1").is_err());
+        assert!(Versification::parse("Psalms 51:3-21 = Psalms 51:1-18").unwrap_err().to_string().contains("same number"));
+    }
+}
@@ -0,0 +1,125 @@
+// vocab.rs
+// Vocabulary size (--vocab): how many distinct words a Bible, or each of its books, uses
+
+use std::collections::HashSet;
+use serde::Serialize;
+use crate::bible::{Bible, Verse};
+use crate::filter::VerseFilter;
+use crate::reference::Reference;
+use crate::similarity::{filtered_tokens, WordFilter};
+
+/// Word counts of a set of verses
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Vocabulary {
+    pub verses: usize,
+    /// Every word kept by the filter, repeats included
+    pub words: usize,
+    /// Distinct words kept by the filter
+    pub distinct: usize,
+}
+
+/// A book's vocabulary
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BookVocabulary {
+    pub book: String,
+    #[serde(flatten)]
+    pub vocabulary: Vocabulary,
+}
+
+/// The vocabulary of the verses passing a filter, and of each of their books when asked for
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct VocabularyReport {
+    #[serde(flatten)]
+    pub overall: Vocabulary,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub books: Vec<BookVocabulary>,
+}
+
+// Count the words `words` keeps in some verses, adding the distinct ones to `seen`
+fn count_words<'a>(verses: impl IntoIterator<Item = &'a Verse>, words: WordFilter, seen: &mut HashSet<String>) -> Vocabulary {
+    let mut vocabulary = Vocabulary::default();
+    for verse in verses {
+        vocabulary.verses += 1;
+        for (word, _, _) in filtered_tokens(&verse.text, words) {
+            vocabulary.words += 1;
+            seen.insert(word);
+        }
+    }
+    vocabulary.distinct = seen.len();
+    vocabulary
+}
+
+/// Count the distinct words (as cross-references see them: lowercased, and without stop words
+/// or short words unless `words` keeps them) of the verses passing `filter`, overall and, with
+/// `by_book`, per book in Bible order
+pub fn vocabulary(bible: &Bible, filter: &VerseFilter, words: WordFilter, by_book: bool) -> VocabularyReport {
+    let mut seen = HashSet::new();
+    let overall = count_words(bible.iter().filter(|v| filter.matches(v)), words, &mut seen);
+
+    let mut books = Vec::new();
+    if by_book {
+        for book in bible.books() {
+            let verses: Vec<&Verse> = bible.passage(&Reference::Book(book.to_string())).iter().filter(|v| filter.matches(v)).collect();
+            if !verses.is_empty() {
+                let vocabulary = count_words(verses, words, &mut HashSet::new());
+                books.push(BookVocabulary { book: book.to_string(), vocabulary });
+            }
+        }
+    }
+    VocabularyReport { overall, books }
+}
+
+/// The report as text: the overall counts, then a line per book
+pub fn format_vocabulary(report: &VocabularyReport) -> String {
+    let overall = &report.overall;
+    let mut text = format!("{} distinct words in {} words ({} verses)", overall.distinct, overall.words, overall.verses);
+    let width = report.books.iter().map(|b| b.book.chars().count()).max().unwrap_or(0);
+    for book in &report.books {
+        let vocabulary = &book.vocabulary;
+        text.push_str(&format!("\n  {:<width$}  {:>6} distinct in {:>7} words", book.book, vocabulary.distinct, vocabulary.words, width = width));
+    }
+    text
+}
+
+// Print the vocabulary report (--vocab), as text or JSON
+pub fn print_vocabulary(report: &VocabularyReport, json: bool) {
+    if json {
+        match serde_json::to_string_pretty(report) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("🔥 Error serializing vocabulary: {}", e),
+        }
+    } else {
+        println!("{}", format_vocabulary(report));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vocabulary_counts() {
+        let bible = Bible::new(vec![
+            Verse::new("Genesis", 1, 1, "In the beginning God created the heaven and the earth."),
+            Verse::new("Genesis", 1, 2, "And the earth was without form, and void."),
+            Verse::new("John", 1, 1, "In the beginning was the Word, and the Word was with God."),
+        ]);
+        let all = VerseFilter::default();
+
+        let report = vocabulary(&bible, &all, WordFilter::default(), true);
+        // beginning, god, created, heaven, earth, without, form, void, word; stop words left out
+        assert_eq!(report.overall, Vocabulary { verses: 3, words: 13, distinct: 9 });
+        assert_eq!(report.books.iter().map(|b| (b.book.as_str(), b.vocabulary.distinct)).collect::<Vec<_>>(), [("Genesis", 8), ("John", 3)]);
+        // The same report every time, and per book only when asked for
+        assert_eq!(vocabulary(&bible, &all, WordFilter::default(), true), report);
+        assert!(vocabulary(&bible, &all, WordFilter::default(), false).books.is_empty());
+
+        // Keeping stop words counts them everywhere, overall and per book alike
+        let every = vocabulary(&bible, &all, WordFilter::all_words(), true);
+        assert!(every.overall.distinct > report.overall.distinct);
+        assert_eq!(every.books[1].vocabulary, Vocabulary { verses: 1, words: 12, distinct: 8 });
+
+        let john = VerseFilter { book: Some("jn".to_string()), ..Default::default() };
+        assert_eq!(vocabulary(&bible, &john, WordFilter::default(), false).overall.distinct, 3);
+    }
+}
@@ -0,0 +1,26 @@
+// color.rs
+// --color decides escapes even when output is piped, which the colored crate alone wouldn't
+
+use std::process::Command;
+
+fn books(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bible_tool"))
+        .arg("--books")
+        .args(args)
+        .env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_color_choice_on_a_pipe() {
+    assert!(books(&["--color", "always"]).contains("\x1b["));
+    // A pipe isn't a terminal, so auto (the default) leaves color out
+    assert!(!books(&[]).contains("\x1b["));
+    assert!(!books(&["--color", "auto"]).contains("\x1b["));
+    assert!(!books(&["--color", "never"]).contains("\x1b["));
+    assert!(!books(&["--no-color"]).contains("\x1b["));
+}
@@ -0,0 +1,55 @@
+// http.rs
+// --http serves the JSON API from a Bible loaded once (built with the `server` feature)
+#![cfg(feature = "server")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+
+// GET a path, returning the status line and the parsed JSON body
+fn get(address: &str, path: &str) -> (String, serde_json::Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, address).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_http_api() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("bible.txt");
+    std::fs::write(
+        &file,
+        "John 3:16\tFor God so loved the world, that he gave his only begotten Son.\n\
+         1 John 4:8\tHe that loveth not knoweth not God; for God is love.\n",
+    )
+    .unwrap();
+
+    // Port 0 lets the system pick a free port, which the ready line reports
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bible_tool"))
+        .args(["--http", "127.0.0.1:0", "-q", "--file", file.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let address = line.trim().rsplit("http://").next().unwrap().to_string();
+
+    let (status, verse) = get(&address, "/verse/1%20John/4/8");
+    assert!(status.contains("200"), "{}", status);
+    assert_eq!(verse["results"][0]["text"], "He that loveth not knoweth not God; for God is love.");
+
+    let (_, found) = get(&address, "/search?q=loved+world");
+    assert_eq!(found["count"], 1);
+    assert_eq!(found["results"][0]["reference"], "John 3:16");
+
+    let (status, missing) = get(&address, "/verse/Mark/1/1");
+    assert!(status.contains("404"), "{}", status);
+    assert_eq!(missing["error"], "NotFound");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
@@ -0,0 +1,50 @@
+// serve.rs
+// --serve answers each request line as it arrives, from a Bible loaded once
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_serve_exchanges_requests() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("bible.txt");
+    std::fs::write(
+        &file,
+        "John 3:16\tFor God so loved the world, that he gave his only begotten Son.\n\
+         John 3:17\tFor God sent not his Son into the world to condemn the world.\n\
+         Psalms 23:1\tThe LORD is my shepherd; I shall not want.\n",
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bible_tool"))
+        .args(["--serve", "--file", file.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    // Each response is read before the next request is sent, as an editor would
+    let mut ask = |request: &str| -> serde_json::Value {
+        writeln!(stdin, "{}", request).unwrap();
+        stdin.flush().unwrap();
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    };
+
+    let found = ask(r#"{"id": 1, "type": "search", "query": "shepherd"}"#);
+    assert_eq!(found["id"], 1);
+    assert_eq!(found["count"], 1);
+    assert_eq!(found["results"][0]["reference"], "Psalms 23:1");
+
+    let passage = ask(r#"{"id": 2, "type": "reference", "reference": "John 3:16-17"}"#);
+    assert_eq!(passage["count"], 2);
+    assert_eq!(passage["results"][1]["text"], "For God sent not his Son into the world to condemn the world.");
+
+    let missing = ask(r#"{"id": 3, "type": "reference", "reference": "Mark 1:1"}"#);
+    assert_eq!((missing["id"].clone(), missing["error"].clone()), (serde_json::json!(3), serde_json::json!("NotFound")));
+
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}